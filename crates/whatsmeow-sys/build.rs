@@ -93,6 +93,8 @@ fn generate_msvc_import_lib(target_dir: &Path) {
     let status = cmd.status().expect("failed to execute generate_lib.ps1");
 
     if !status.success() {
-        panic!("MSVC import library generation failed. Ensure Visual Studio with C++ tools is installed.");
+        panic!(
+            "MSVC import library generation failed. Ensure Visual Studio with C++ tools is installed."
+        );
     }
 }
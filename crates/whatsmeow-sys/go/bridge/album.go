@@ -0,0 +1,92 @@
+package main
+
+import (
+	"fmt"
+
+	"go.mau.fi/whatsmeow"
+	waCommon "go.mau.fi/whatsmeow/proto/waCommon"
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"go.mau.fi/whatsmeow/types"
+	"google.golang.org/protobuf/proto"
+)
+
+// AlbumItem is one image of an album
+type AlbumItem struct {
+	Data     []byte `json:"data"`
+	MimeType string `json:"mime_type"`
+}
+
+// SendAlbum sends images grouped as an album and returns their message IDs.
+// All images are uploaded before anything is sent, so a failed upload sends nothing.
+func (c *Client) SendAlbum(jidStr string, items []AlbumItem, caption string) ([]string, error) {
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+
+	if !c.connected {
+		return nil, errNotConnected
+	}
+
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		return nil, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	uploads := make([]whatsmeow.UploadResponse, len(items))
+	for i, item := range items {
+		uploads[i], err = c.client.Upload(c.ctx, item.Data, whatsmeow.MediaImage)
+		if err != nil {
+			return nil, fmt.Errorf("upload of image %d failed: %w", i, err)
+		}
+	}
+
+	// The album message announces the image count; each image then points
+	// back at it through a message association
+	albumID := c.client.GenerateMessageID()
+	album := &waProto.Message{
+		AlbumMessage: &waProto.AlbumMessage{
+			ExpectedImageCount: proto.Uint32(uint32(len(items))),
+		},
+	}
+	if _, err := c.client.SendMessage(c.ctx, jid, album, whatsmeow.SendRequestExtra{ID: albumID}); err != nil {
+		return nil, fmt.Errorf("send album failed: %w", err)
+	}
+
+	parent := &waCommon.MessageKey{
+		RemoteJID: proto.String(jid.String()),
+		FromMe:    proto.Bool(true),
+		ID:        proto.String(albumID),
+	}
+
+	ids := make([]string, 0, len(items))
+	for i, item := range items {
+		uploaded := uploads[i]
+		msg := &waProto.Message{
+			ImageMessage: &waProto.ImageMessage{
+				URL:           proto.String(uploaded.URL),
+				DirectPath:    proto.String(uploaded.DirectPath),
+				MediaKey:      uploaded.MediaKey,
+				Mimetype:      proto.String(item.MimeType),
+				FileEncSHA256: uploaded.FileEncSHA256,
+				FileSHA256:    uploaded.FileSHA256,
+				FileLength:    proto.Uint64(uint64(len(item.Data))),
+			},
+			MessageContextInfo: &waProto.MessageContextInfo{
+				MessageAssociation: &waProto.MessageAssociation{
+					AssociationType:  waProto.MessageAssociation_MEDIA_ALBUM.Enum(),
+					ParentMessageKey: parent,
+				},
+			},
+		}
+		if i == 0 && caption != "" {
+			msg.ImageMessage.Caption = proto.String(caption)
+		}
+
+		resp, err := c.client.SendMessage(c.ctx, jid, msg)
+		if err != nil {
+			return ids, fmt.Errorf("send of image %d failed: %w", i, err)
+		}
+		ids = append(ids, resp.ID)
+	}
+
+	return ids, nil
+}
@@ -0,0 +1,50 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+
+	"go.mau.fi/whatsmeow/appstate"
+)
+
+// errInvalidCollection marks errors caused by an unknown app state collection
+var errInvalidCollection = errors.New("invalid app state collection")
+
+var appStateCollections = map[string]appstate.WAPatchName{
+	string(appstate.WAPatchCriticalBlock):      appstate.WAPatchCriticalBlock,
+	string(appstate.WAPatchCriticalUnblockLow): appstate.WAPatchCriticalUnblockLow,
+	string(appstate.WAPatchRegular):            appstate.WAPatchRegular,
+	string(appstate.WAPatchRegularLow):         appstate.WAPatchRegularLow,
+	string(appstate.WAPatchRegularHigh):        appstate.WAPatchRegularHigh,
+}
+
+// ResyncAppState fully re-fetches a named app state collection. Contacts,
+// push names and chat settings arrive as the usual events; an
+// AppStateSyncComplete event follows once the collection is applied.
+func (c *Client) ResyncAppState(name string) error {
+	patch, ok := appStateCollections[name]
+	if !ok {
+		err := fmt.Errorf("%w: %q", errInvalidCollection, name)
+		c.setLastError(err)
+		return err
+	}
+
+	if err := c.client.FetchAppState(c.ctx, patch, true, false); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("app state resync failed: %w", err)
+	}
+
+	return nil
+}
+
+// catchUpAppState fetches patches for every collection after new app state
+// keys arrive. whatsmeow only re-syncs collections that never synced, so
+// patches that failed to decrypt while a key was missing would otherwise
+// wait for the next server notification.
+func (c *Client) catchUpAppState() {
+	for _, patch := range appStateCollections {
+		if err := c.client.FetchAppState(c.ctx, patch, false, false); err != nil {
+			c.setLastError(err)
+		}
+	}
+}
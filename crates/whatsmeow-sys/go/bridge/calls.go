@@ -0,0 +1,22 @@
+package main
+
+import (
+	"fmt"
+
+	"go.mau.fi/whatsmeow/types"
+)
+
+// RejectCall declines an incoming call offer
+func (c *Client) RejectCall(fromStr, callID string) error {
+	from, err := types.ParseJID(fromStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid JID: %w", err)
+	}
+
+	if err := c.client.RejectCall(c.ctx, from, callID); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("reject call failed: %w", err)
+	}
+	return nil
+}
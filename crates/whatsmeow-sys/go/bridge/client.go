@@ -2,16 +2,21 @@ package main
 
 import (
 	"context"
+	"errors"
 	"fmt"
+	"sort"
 	"sync"
+	"time"
 
 	_ "github.com/mattn/go-sqlite3"
 	"go.mau.fi/whatsmeow"
+	"go.mau.fi/whatsmeow/appstate"
 	waCompanionReg "go.mau.fi/whatsmeow/proto/waCompanionReg"
 	waProto "go.mau.fi/whatsmeow/proto/waE2E"
 	"go.mau.fi/whatsmeow/store"
 	"go.mau.fi/whatsmeow/store/sqlstore"
 	"go.mau.fi/whatsmeow/types"
+	"go.mau.fi/whatsmeow/types/events"
 	waLog "go.mau.fi/whatsmeow/util/log"
 	"google.golang.org/protobuf/proto"
 )
@@ -21,17 +26,101 @@ type Client struct {
 	mu         sync.RWMutex
 	client     *whatsmeow.Client
 	store      *sqlstore.Container
+	history    *HistoryStore
 	eventQueue chan []byte
 	ctx        context.Context
 	cancel     context.CancelFunc
 	connected  bool
 	lastError  string
+	clock      serverClock
+}
+
+// SendOptions holds optional parameters for an outgoing message
+type SendOptions struct {
+	LinkPreview *LinkPreview `json:"link_preview,omitempty"`
+	// Timestamp (unix seconds) reported for the message instead of the
+	// server's; WhatsApp itself always stamps messages on receipt
+	Timestamp int64 `json:"timestamp,omitempty"`
+	// MessageID is used as the stanza id instead of a generated one, so a
+	// retried send replaces rather than duplicates the message
+	MessageID string `json:"message_id,omitempty"`
+}
+
+// LinkPreview describes a rich preview attached to a text message
+type LinkPreview struct {
+	URL         string `json:"url"`
+	Title       string `json:"title"`
+	Description string `json:"description,omitempty"`
+	Thumbnail   []byte `json:"thumbnail,omitempty"`
+}
+
+// ContactInfo is a contact from the local store
+type ContactInfo struct {
+	Found        bool   `json:"found"`
+	FirstName    string `json:"first_name,omitempty"`
+	FullName     string `json:"full_name,omitempty"`
+	PushName     string `json:"push_name,omitempty"`
+	BusinessName string `json:"business_name,omitempty"`
+}
+
+// LIDMapping pairs a LID with the phone number JID it stands for
+type LIDMapping struct {
+	LID   types.JID `json:"lid"`
+	Phone types.JID `json:"phone"`
+}
+
+// RegistrationStatus reports whether a phone number has a WhatsApp account
+type RegistrationStatus struct {
+	Query string    `json:"query"`
+	JID   types.JID `json:"jid"`
+	IsIn  bool      `json:"is_in"`
+}
+
+// SendResult identifies a sent message
+type SendResult struct {
+	ID        string    `json:"id"`
+	Timestamp time.Time `json:"timestamp"`
+}
+
+func newSendResult(resp whatsmeow.SendResponse) SendResult {
+	return SendResult{ID: resp.ID, Timestamp: resp.Timestamp}
+}
+
+// LinkedDevice describes a device linked to the account
+type LinkedDevice struct {
+	JID       types.JID `json:"jid"`
+	Device    uint16    `json:"device"`
+	IsPrimary bool      `json:"is_primary"`
+	IsSelf    bool      `json:"is_self"`
 }
 
 // ClientConfig holds configuration for creating a new client
 type ClientConfig struct {
 	DbPath     string
 	DeviceName string
+	StoreOptions
+}
+
+// StoreOptions tunes how the sqlite session database is opened
+type StoreOptions struct {
+	// WALMode switches the database to write-ahead logging
+	WALMode bool `json:"wal_mode"`
+}
+
+// dsn builds the sqlite connection string for the session database
+func (o StoreOptions) dsn(dbPath string) string {
+	dsn := fmt.Sprintf("file:%s?_foreign_keys=on", dbPath)
+	if o.WALMode {
+		// NORMAL sync is durable against crashes of this process in WAL mode
+		dsn += "&_journal_mode=WAL&_synchronous=NORMAL"
+	}
+	return dsn
+}
+
+// SetClientVersion overrides the WhatsApp Web version advertised on connect.
+// The version is process-wide and applies to connections made afterwards.
+func SetClientVersion(major, minor, patch uint32) {
+	store.SetWAVersion(store.WAVersionContainer{major, minor, patch})
 }
 
 // NewClient creates a new WhatsApp client with the given configuration
@@ -47,9 +136,7 @@ func NewClient(config ClientConfig) (*Client, error) {
 	store.DeviceProps.PlatformType = waCompanionReg.DeviceProps_DESKTOP.Enum()
 
 	// Initialize database (new API requires context)
-	container, err := sqlstore.New(ctx, "sqlite3",
-		fmt.Sprintf("file:%s?_foreign_keys=on", config.DbPath),
-		waLog.Noop)
+	container, err := sqlstore.New(ctx, "sqlite3", config.dsn(config.DbPath), waLog.Noop)
 	if err != nil {
 		return nil, fmt.Errorf("failed to open store: %w", err)
 	}
@@ -60,12 +147,20 @@ func NewClient(config ClientConfig) (*Client, error) {
 		return nil, fmt.Errorf("failed to get device: %w", err)
 	}
 
+	history, err := OpenHistoryStore(config.dsn(config.DbPath))
+	if err != nil {
+		return nil, err
+	}
+
 	client := whatsmeow.NewClient(device, waLog.Noop)
+	// Reconnection (with backoff and jitter) is driven from the Rust side
+	client.EnableAutoReconnect = false
 	clientCtx, cancel := context.WithCancel(context.Background())
 
 	c := &Client{
 		client:     client,
 		store:      container,
+		history:    history,
 		eventQueue: make(chan []byte, 1024),
 		ctx:        clientCtx,
 		cancel:     cancel,
@@ -115,11 +210,31 @@ func (c *Client) Connect() error {
 
 // handleEvent processes any WhatsMeow event
 func (c *Client) handleEvent(evt interface{}) {
+	c.recordHistory(evt)
+	c.clock.observe(evt)
+
+	if sync, ok := evt.(*events.HistorySync); ok {
+		c.spillHistorySync(sync)
+		return
+	}
+
 	data, err := MarshalEvent(evt)
 	if err != nil {
 		return
 	}
+	c.enqueue(data)
+
+	if msg, ok := evt.(*events.Message); ok && msg.Message.GetPollUpdateMessage() != nil {
+		c.handlePollVote(msg)
+	}
+	if msg, ok := evt.(*events.Message); ok && msg.Message.GetProtocolMessage().GetType() == waProto.ProtocolMessage_APP_STATE_SYNC_KEY_SHARE {
+		// whatsmeow has stored the keys by the time the event is dispatched
+		go c.catchUpAppState()
+	}
+}
 
+// enqueue adds a marshaled event to the queue, dropping the oldest when full
+func (c *Client) enqueue(data []byte) {
 	select {
 	case c.eventQueue <- data:
 	default:
@@ -132,6 +247,225 @@ func (c *Client) handleEvent(evt interface{}) {
 	}
 }
 
+// recordHistory persists live and history-synced messages for later queries
+// and keeps each chat's read mark up to date
+func (c *Client) recordHistory(evt interface{}) {
+	switch v := evt.(type) {
+	case *events.Message:
+		_ = c.history.Save(v)
+		if v.Info.IsFromMe {
+			// Replying in a chat implies everything before it was seen
+			_ = c.history.MarkReadUntil(v.Info.Chat.String(), v.Info.Timestamp.Unix())
+		}
+	case *events.Receipt:
+		if v.IsFromMe && (v.Type == types.ReceiptTypeRead || v.Type == types.ReceiptTypeReadSelf) {
+			// Read on another of our devices
+			_ = c.history.MarkReadIDs(v.Chat.String(), v.MessageIDs)
+		}
+	case *events.Star:
+		// Starred or unstarred on another device
+		_ = c.history.SetStarred(v.ChatJID.String(), v.MessageID, v.Action.GetStarred())
+	case *events.HistorySync:
+		for _, conv := range v.Data.GetConversations() {
+			chatJID, err := types.ParseJID(conv.GetID())
+			if err != nil {
+				continue
+			}
+
+			var incoming []int64
+			for _, hm := range conv.GetMessages() {
+				msg, err := c.client.ParseWebMessage(chatJID, hm.GetMessage())
+				if err != nil {
+					continue
+				}
+				_ = c.history.Save(msg)
+				if !msg.Info.IsFromMe {
+					incoming = append(incoming, msg.Info.Timestamp.Unix())
+				}
+			}
+
+			// Everything but the newest UnreadCount incoming messages was read
+			unread := int(conv.GetUnreadCount())
+			if len(incoming) > unread {
+				sort.Slice(incoming, func(i, j int) bool { return incoming[i] > incoming[j] })
+				_ = c.history.MarkReadUntil(chatJID.String(), incoming[unread])
+			}
+		}
+	}
+}
+
+// errNotConnected marks operations attempted while disconnected
+var errNotConnected = errors.New("not connected")
+
+// isNotConnected reports whether err means the connection is gone, either
+// known up front or discovered by whatsmeow mid-request
+func isNotConnected(err error) bool {
+	return errors.Is(err, errNotConnected) || errors.Is(err, whatsmeow.ErrNotConnected)
+}
+
+// errUnknownMessage marks lookups of messages missing from the history store
+var errUnknownMessage = errors.New("unknown message id")
+
+// StarMessage stars or unstars a stored message. The change is synced to
+// the account's other devices through app state.
+func (c *Client) StarMessage(chatStr, id string, starred bool) error {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid chat JID: %w", err)
+	}
+
+	msg, err := c.history.Get(chat.String(), id)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("message lookup failed: %w", err)
+	}
+	if msg == nil {
+		err := fmt.Errorf("%w %q", errUnknownMessage, id)
+		c.setLastError(err)
+		return err
+	}
+
+	sender, err := types.ParseJID(msg.Sender)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid stored sender: %w", err)
+	}
+
+	patch := appstate.BuildStar(chat, sender, id, msg.IsFromMe, starred)
+	if err := c.client.SendAppState(c.ctx, patch); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("star failed: %w", err)
+	}
+
+	_ = c.history.SetStarred(chat.String(), id, starred)
+	return nil
+}
+
+// StarredMessages returns the starred stored messages in a chat
+func (c *Client) StarredMessages(chatStr string) ([]StoredMessage, error) {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	messages, err := c.history.Starred(chat.String())
+	if err != nil {
+		c.setLastError(err)
+		return nil, err
+	}
+
+	return messages, nil
+}
+
+// MarkChatUnread flags a chat as unread on all of the account's devices
+func (c *Client) MarkChatUnread(chatStr string) error {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid chat JID: %w", err)
+	}
+
+	patch := appstate.BuildMarkChatAsRead(chat, false, time.Time{}, nil)
+	if err := c.client.SendAppState(c.ctx, patch); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("mark unread failed: %w", err)
+	}
+
+	_ = c.history.MarkUnread(chat.String())
+	return nil
+}
+
+// MuteChat mutes a chat for the given duration (zero mutes until unmuted)
+// or unmutes it. The setting syncs to the account's other devices.
+func (c *Client) MuteChat(chatStr string, mute bool, duration time.Duration) error {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid chat JID: %w", err)
+	}
+
+	patch := appstate.BuildMute(chat, mute, duration)
+	if err := c.client.SendAppState(c.ctx, patch); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("mute failed: %w", err)
+	}
+	return nil
+}
+
+// ClearChat deletes a chat's messages from the local history store. Other
+// devices and the server are not affected.
+func (c *Client) ClearChat(chatStr string) error {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid chat JID: %w", err)
+	}
+
+	if err := c.history.ClearChat(chat.String()); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("clear chat failed: %w", err)
+	}
+
+	return nil
+}
+
+// UnreadCounts returns per-chat unread message counts from the local history
+func (c *Client) UnreadCounts() (map[string]int, error) {
+	counts, err := c.history.UnreadCounts()
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("unread count query failed: %w", err)
+	}
+	return counts, nil
+}
+
+// StoreStats counts what the session database holds
+type StoreStats struct {
+	Messages int64 `json:"messages"`
+	Chats    int64 `json:"chats"`
+	Contacts int64 `json:"contacts"`
+}
+
+// StoreStats returns message, chat and contact counts for capacity planning
+func (c *Client) StoreStats() (StoreStats, error) {
+	messages, chats, err := c.history.Counts()
+	if err != nil {
+		c.setLastError(err)
+		return StoreStats{}, fmt.Errorf("history count query failed: %w", err)
+	}
+
+	contacts, err := c.client.Store.Contacts.GetAllContacts(c.ctx)
+	if err != nil {
+		c.setLastError(err)
+		return StoreStats{}, fmt.Errorf("contact count query failed: %w", err)
+	}
+
+	return StoreStats{
+		Messages: messages,
+		Chats:    chats,
+		Contacts: int64(len(contacts)),
+	}, nil
+}
+
+// QueryMessages returns stored messages for a chat, oldest first
+func (c *Client) QueryMessages(chatStr string, limit int, beforeID string) ([]StoredMessage, error) {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	messages, err := c.history.Query(chat.String(), limit, beforeID)
+	if err != nil {
+		c.setLastError(err)
+		return nil, err
+	}
+
+	return messages, nil
+}
+
 // PollEvent retrieves the next event (non-blocking)
 func (c *Client) PollEvent() []byte {
 	select {
@@ -143,18 +477,23 @@ func (c *Client) PollEvent() []byte {
 }
 
 // SendMessage sends a text message to the specified JID
-func (c *Client) SendMessage(jidStr, text string) error {
+func (c *Client) SendMessage(jidStr, text string) (SendResult, error) {
+	return c.SendMessageEx(jidStr, text, SendOptions{})
+}
+
+// SendMessageEx sends a text message with optional send options
+func (c *Client) SendMessageEx(jidStr, text string, opts SendOptions) (SendResult, error) {
 	c.mu.RLock()
 	defer c.mu.RUnlock()
 
 	if !c.connected {
-		return fmt.Errorf("not connected")
+		return SendResult{}, errNotConnected
 	}
 
 	// Parse JID
 	jid, err := types.ParseJID(jidStr)
 	if err != nil {
-		return fmt.Errorf("invalid JID: %w", err)
+		return SendResult{}, fmt.Errorf("invalid JID: %w", err)
 	}
 
 	// Create text message
@@ -164,39 +503,66 @@ func (c *Client) SendMessage(jidStr, text string) error {
 		},
 	}
 
+	// Attach link preview if provided
+	if preview := opts.LinkPreview; preview != nil {
+		ext := msg.ExtendedTextMessage
+		ext.MatchedText = proto.String(preview.URL)
+		ext.Title = proto.String(preview.Title)
+		ext.PreviewType = waProto.ExtendedTextMessage_NONE.Enum()
+		if preview.Description != "" {
+			ext.Description = proto.String(preview.Description)
+		}
+		if len(preview.Thumbnail) > 0 {
+			ext.JPEGThumbnail = preview.Thumbnail
+		}
+	}
+
 	// Send the message
-	_, err = c.client.SendMessage(c.ctx, jid, msg)
+	var extra whatsmeow.SendRequestExtra
+	if opts.MessageID != "" {
+		extra.ID = types.MessageID(opts.MessageID)
+	}
+	resp, err := c.client.SendMessage(c.ctx, jid, msg, extra)
 	if err != nil {
 		c.mu.RUnlock()
 		c.mu.Lock()
 		c.lastError = err.Error()
 		c.mu.Unlock()
 		c.mu.RLock()
-		return fmt.Errorf("send failed: %w", err)
+		return SendResult{}, fmt.Errorf("send failed: %w", err)
 	}
 
-	return nil
+	result := newSendResult(resp)
+	if opts.Timestamp > 0 {
+		result.Timestamp = time.Unix(opts.Timestamp, 0)
+	}
+	return result, nil
 }
 
 // SendImage sends an image message to the specified JID
-func (c *Client) SendImage(jidStr string, imageData []byte, mimeType, caption string) error {
+func (c *Client) SendImage(jidStr string, imageData []byte, mimeType, caption string) (SendResult, error) {
+	return c.SendImageEx(jidStr, imageData, mimeType, caption, nil)
+}
+
+// SendImageEx sends an image with an optional JPEG thumbnail shown while it loads
+func (c *Client) SendImageEx(jidStr string, imageData []byte, mimeType, caption string, thumbnail []byte) (SendResult, error) {
 	c.mu.RLock()
 	defer c.mu.RUnlock()
 
 	if !c.connected {
-		return fmt.Errorf("not connected")
+		return SendResult{}, errNotConnected
 	}
 
 	// Parse JID
 	jid, err := types.ParseJID(jidStr)
 	if err != nil {
-		return fmt.Errorf("invalid JID: %w", err)
+		return SendResult{}, fmt.Errorf("invalid JID: %w", err)
 	}
 
 	// Upload the image to WhatsApp servers
 	uploaded, err := c.client.Upload(c.ctx, imageData, whatsmeow.MediaImage)
 	if err != nil {
-		return fmt.Errorf("upload failed: %w", err)
+		return SendResult{}, fmt.Errorf("upload failed: %w", err)
 	}
 
 	// Create image message
@@ -217,13 +583,60 @@ func (c *Client) SendImage(jidStr string, imageData []byte, mimeType, caption st
 		msg.ImageMessage.Caption = proto.String(caption)
 	}
 
+	if len(thumbnail) > 0 {
+		msg.ImageMessage.JPEGThumbnail = thumbnail
+	}
+
 	// Send the message
-	_, err = c.client.SendMessage(c.ctx, jid, msg)
+	resp, err := c.client.SendMessage(c.ctx, jid, msg)
 	if err != nil {
-		return fmt.Errorf("send failed: %w", err)
+		return SendResult{}, fmt.Errorf("send failed: %w", err)
 	}
 
-	return nil
+	return newSendResult(resp), nil
+}
+
+// SendSticker sends a WebP sticker to the specified JID
+func (c *Client) SendSticker(jidStr string, data []byte, width, height uint32, animated bool) (SendResult, error) {
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+
+	if !c.connected {
+		return SendResult{}, errNotConnected
+	}
+
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		return SendResult{}, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	// Stickers are uploaded as images
+	uploaded, err := c.client.Upload(c.ctx, data, whatsmeow.MediaImage)
+	if err != nil {
+		return SendResult{}, fmt.Errorf("upload failed: %w", err)
+	}
+
+	msg := &waProto.Message{
+		StickerMessage: &waProto.StickerMessage{
+			URL:           proto.String(uploaded.URL),
+			DirectPath:    proto.String(uploaded.DirectPath),
+			MediaKey:      uploaded.MediaKey,
+			Mimetype:      proto.String("image/webp"),
+			FileEncSHA256: uploaded.FileEncSHA256,
+			FileSHA256:    uploaded.FileSHA256,
+			FileLength:    proto.Uint64(uint64(len(data))),
+			Width:         proto.Uint32(width),
+			Height:        proto.Uint32(height),
+			IsAnimated:    proto.Bool(animated),
+		},
+	}
+
+	resp, err := c.client.SendMessage(c.ctx, jid, msg)
+	if err != nil {
+		return SendResult{}, fmt.Errorf("send failed: %w", err)
+	}
+
+	return newSendResult(resp), nil
 }
 
 // Disconnect closes the connection
@@ -239,6 +652,9 @@ func (c *Client) Disconnect() {
 func (c *Client) Destroy() {
 	c.cancel()
 	c.Disconnect()
+	if c.history != nil {
+		c.history.Close()
+	}
 	if c.store != nil {
 		c.store.Close()
 	}
@@ -250,3 +666,227 @@ func (c *Client) LastError() string {
 	defer c.mu.RUnlock()
 	return c.lastError
 }
+
+// setLastError records an error message for wm_last_error
+func (c *Client) setLastError(err error) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	c.lastError = err.Error()
+}
+
+// OwnJID returns the logged-in account's device JID, or nil before pairing
+func (c *Client) OwnJID() *types.JID {
+	return c.client.Store.ID
+}
+
+// LinkedDevices lists all devices linked to the logged-in account
+func (c *Client) LinkedDevices() ([]LinkedDevice, error) {
+	own := c.client.Store.ID
+	if own == nil {
+		return nil, fmt.Errorf("not logged in")
+	}
+
+	jids, err := c.client.GetUserDevices(c.ctx, []types.JID{own.ToNonAD()})
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("device query failed: %w", err)
+	}
+
+	devices := make([]LinkedDevice, 0, len(jids))
+	for _, jid := range jids {
+		devices = append(devices, LinkedDevice{
+			JID:       jid,
+			Device:    jid.Device,
+			IsPrimary: jid.Device == 0,
+			IsSelf:    jid.Device == own.Device,
+		})
+	}
+
+	return devices, nil
+}
+
+// RemoveDevice unlinks a device. Companions can only unlink themselves;
+// other devices must be removed from the primary phone.
+func (c *Client) RemoveDevice(jidStr string) error {
+	own := c.client.Store.ID
+	if own == nil {
+		return fmt.Errorf("not logged in")
+	}
+
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid JID: %w", err)
+	}
+
+	if jid.User != own.User || jid.Device != own.Device {
+		err := fmt.Errorf("only the primary device can unlink other devices")
+		c.setLastError(err)
+		return err
+	}
+
+	if err := c.client.Logout(c.ctx); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("logout failed: %w", err)
+	}
+
+	return nil
+}
+
+// ResolveLID maps a LID to its phone number JID using the local store.
+// Phone is empty when the mapping has not been learned yet.
+func (c *Client) ResolveLID(lidStr string) (LIDMapping, error) {
+	lid, err := types.ParseJID(lidStr)
+	if err != nil {
+		c.setLastError(err)
+		return LIDMapping{}, fmt.Errorf("invalid JID: %w", err)
+	}
+	if lid.Server != types.HiddenUserServer {
+		err := fmt.Errorf("not a LID: %s", lidStr)
+		c.setLastError(err)
+		return LIDMapping{}, err
+	}
+
+	phone, err := c.client.Store.LIDs.GetPNForLID(c.ctx, lid.ToNonAD())
+	if err != nil {
+		c.setLastError(err)
+		return LIDMapping{}, fmt.Errorf("LID lookup failed: %w", err)
+	}
+
+	return LIDMapping{LID: lid.ToNonAD(), Phone: phone}, nil
+}
+
+// GetContact looks up a contact in the local store
+func (c *Client) GetContact(jidStr string) (ContactInfo, error) {
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return ContactInfo{}, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	info, err := c.client.Store.Contacts.GetContact(c.ctx, jid.ToNonAD())
+	if err != nil {
+		c.setLastError(err)
+		return ContactInfo{}, fmt.Errorf("contact lookup failed: %w", err)
+	}
+
+	return ContactInfo{
+		Found:        info.Found,
+		FirstName:    info.FirstName,
+		FullName:     info.FullName,
+		PushName:     info.PushName,
+		BusinessName: info.BusinessName,
+	}, nil
+}
+
+// IsOnWhatsApp checks which phone numbers (in international format) are registered
+func (c *Client) IsOnWhatsApp(phones []string) ([]RegistrationStatus, error) {
+	resp, err := c.client.IsOnWhatsApp(c.ctx, phones)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("registration query failed: %w", err)
+	}
+
+	statuses := make([]RegistrationStatus, 0, len(resp))
+	for _, r := range resp {
+		statuses = append(statuses, RegistrationStatus{
+			Query: r.Query,
+			JID:   r.JID,
+			IsIn:  r.IsIn,
+		})
+	}
+
+	return statuses, nil
+}
+
+// SubscribePresence asks the server to send presence updates for a contact.
+// Subscriptions only last for the current connection.
+func (c *Client) SubscribePresence(jidStr string) error {
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid JID: %w", err)
+	}
+
+	if err := c.client.SubscribePresence(c.ctx, jid); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("subscribe presence failed: %w", err)
+	}
+
+	return nil
+}
+
+// SendChatPresence shows or clears the typing/recording indicator in a chat.
+// state is "composing" or "paused"; media is "" or "audio".
+func (c *Client) SendChatPresence(jidStr, state, media string) error {
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("invalid JID: %w", err)
+	}
+
+	err = c.client.SendChatPresence(c.ctx, jid, types.ChatPresence(state), types.ChatPresenceMedia(media))
+	if err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("send chat presence failed: %w", err)
+	}
+
+	return nil
+}
+
+// MarkRead sends read receipts for the given messages in a chat and moves
+// the chat's read mark past them
+func (c *Client) MarkRead(chatStr, senderStr string, ids []string) error {
+	chat, sender, msgIDs, err := parseReceiptTarget(chatStr, senderStr, ids)
+	if err != nil {
+		return err
+	}
+
+	if err := c.client.MarkRead(c.ctx, msgIDs, time.Now(), chat, sender); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("mark read failed: %w", err)
+	}
+
+	_ = c.history.MarkReadIDs(chat.String(), ids)
+	return nil
+}
+
+// MarkPlayed sends "played" receipts for voice notes and other media in a
+// chat, which turns the sender's microphone icon blue
+func (c *Client) MarkPlayed(chatStr, senderStr string, ids []string) error {
+	chat, sender, msgIDs, err := parseReceiptTarget(chatStr, senderStr, ids)
+	if err != nil {
+		return err
+	}
+
+	if err := c.client.MarkRead(c.ctx, msgIDs, time.Now(), chat, sender, types.ReceiptTypePlayed); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("mark played failed: %w", err)
+	}
+
+	return nil
+}
+
+// parseReceiptTarget parses the chat, optional sender and message IDs a
+// receipt is sent for
+func parseReceiptTarget(chatStr, senderStr string, ids []string) (types.JID, types.JID, []types.MessageID, error) {
+	chat, err := types.ParseJID(chatStr)
+	if err != nil {
+		return types.JID{}, types.JID{}, nil, fmt.Errorf("invalid chat JID: %w", err)
+	}
+
+	var sender types.JID
+	if senderStr != "" {
+		sender, err = types.ParseJID(senderStr)
+		if err != nil {
+			return types.JID{}, types.JID{}, nil, fmt.Errorf("invalid sender JID: %w", err)
+		}
+	}
+
+	msgIDs := make([]types.MessageID, len(ids))
+	for i, id := range ids {
+		msgIDs[i] = types.MessageID(id)
+	}
+
+	return chat, sender, msgIDs, nil
+}
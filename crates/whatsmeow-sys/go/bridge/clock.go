@@ -0,0 +1,47 @@
+package main
+
+import (
+	"sync"
+	"time"
+
+	"go.mau.fi/whatsmeow/types/events"
+)
+
+// serverClock estimates WhatsApp's clock from the server timestamps of
+// messages delivered live. Messages queued while offline are skipped, since
+// their timestamps are from when they were sent, not delivered.
+type serverClock struct {
+	mu         sync.Mutex
+	live       bool
+	serverTime time.Time
+	sampledAt  time.Time
+}
+
+func (s *serverClock) observe(evt interface{}) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	switch v := evt.(type) {
+	case *events.OfflineSyncCompleted:
+		s.live = true
+	case *events.Disconnected:
+		s.live = false
+	case *events.Message:
+		if s.live && !v.Info.IsFromMe && !v.Info.Timestamp.IsZero() {
+			s.serverTime = v.Info.Timestamp
+			s.sampledAt = time.Now()
+		}
+	}
+}
+
+// Now returns the estimated server time in unix milliseconds, or 0 before
+// the first live message
+func (s *serverClock) Now() int64 {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if s.sampledAt.IsZero() {
+		return 0
+	}
+	return s.serverTime.Add(time.Since(s.sampledAt)).UnixMilli()
+}
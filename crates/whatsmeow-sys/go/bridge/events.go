@@ -32,14 +32,20 @@ func MarshalEvent(evt interface{}) ([]byte, error) {
 		eventType = "disconnected"
 	case *events.LoggedOut:
 		eventType = "logged_out"
+	case *events.StreamReplaced:
+		eventType = "stream_replaced"
 	case *events.Message:
 		eventType = "message"
+	case *events.UndecryptableMessage:
+		eventType = "undecryptable_message"
 	case *events.Receipt:
 		eventType = "receipt"
 	case *events.Presence:
 		eventType = "presence"
 	case *events.HistorySync:
 		eventType = "history_sync"
+	case *events.CallOffer:
+		eventType = "call_offer"
 	case *events.PushNameSetting:
 		eventType = "push_name"
 	case *events.ChatPresence:
@@ -48,6 +54,10 @@ func MarshalEvent(evt interface{}) ([]byte, error) {
 		eventType = "offline_sync_preview"
 	case *events.OfflineSyncCompleted:
 		eventType = "offline_sync_completed"
+	case *events.AppStateSyncComplete:
+		eventType = "app_state_sync_complete"
+	case *events.DeleteChat:
+		eventType = "delete_chat"
 	default:
 		// Use reflection to get type name for unknown events
 		t := reflect.TypeOf(evt)
@@ -57,14 +67,20 @@ func MarshalEvent(evt interface{}) ([]byte, error) {
 		eventType = fmt.Sprintf("unknown_%s", t.Name())
 	}
 
+	return MarshalTypedEvent(eventType, evt)
+}
+
+// MarshalTypedEvent wraps any value in the unified event format under the
+// given type (also used for events the bridge synthesizes itself)
+func MarshalTypedEvent(eventType string, data interface{}) ([]byte, error) {
 	event := Event{
 		Type:      eventType,
 		Timestamp: time.Now().UnixMilli(),
 		Data:      nil,
 	}
 
-	// Marshal the complete original event struct
-	rawData, err := json.Marshal(evt)
+	// Marshal the complete event struct
+	rawData, err := json.Marshal(data)
 	if err != nil {
 		return nil, err
 	}
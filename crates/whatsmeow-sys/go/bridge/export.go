@@ -7,7 +7,10 @@ package main
 import "C"
 
 import (
+	"encoding/json"
+	"errors"
 	"sync"
+	"time"
 	"unsafe"
 )
 
@@ -19,8 +22,19 @@ const (
 	WM_ERR_DISCONNECTED     = -3
 	WM_ERR_INVALID_HANDLE   = -4
 	WM_ERR_BUFFER_TOO_SMALL = -5
+	WM_ERR_INVALID_ARGUMENT = -6
+	WM_ERR_UNSUPPORTED      = -7
 )
 
+// failureCode maps a failed network operation to an error code, telling a
+// dropped connection apart from other failures
+func failureCode(err error) C.int {
+	if isNotConnected(err) {
+		return WM_ERR_DISCONNECTED
+	}
+	return WM_ERR_CONNECT
+}
+
 // Global client registry
 var (
 	clientsMu sync.RWMutex
@@ -50,6 +64,44 @@ func wm_client_new(dbPath *C.char, deviceName *C.char) C.uintptr_t {
 	return C.uintptr_t(id)
 }
 
+//export wm_client_new_ex
+func wm_client_new_ex(dbPath *C.char, deviceName *C.char, optionsJSON *C.char) C.uintptr_t {
+	config := ClientConfig{
+		DbPath:     C.GoString(dbPath),
+		DeviceName: C.GoString(deviceName),
+	}
+
+	if optionsJSON != nil {
+		if err := json.Unmarshal([]byte(C.GoString(optionsJSON)), &config.StoreOptions); err != nil {
+			return 0
+		}
+	}
+
+	client, err := NewClient(config)
+	if err != nil {
+		return 0
+	}
+
+	clientsMu.Lock()
+	defer clientsMu.Unlock()
+
+	id := nextID
+	nextID++
+	clients[id] = client
+
+	return C.uintptr_t(id)
+}
+
+//export wm_set_client_version
+func wm_set_client_version(major C.int, minor C.int, patch C.int) C.int {
+	if major <= 0 || minor < 0 || patch < 0 {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	SetClientVersion(uint32(major), uint32(minor), uint32(patch))
+	return WM_OK
+}
+
 //export wm_client_connect
 func wm_client_connect(handle C.uintptr_t) C.int {
 	client := getClient(uintptr(handle))
@@ -109,22 +161,44 @@ func wm_poll_event(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
 }
 
 //export wm_send_message
-func wm_send_message(handle C.uintptr_t, jid *C.char, text *C.char) C.int {
+func wm_send_message(handle C.uintptr_t, jid *C.char, text *C.char, buf *C.char, bufLen C.int) C.int {
 	client := getClient(uintptr(handle))
 	if client == nil {
 		return WM_ERR_INVALID_HANDLE
 	}
 
-	err := client.SendMessage(C.GoString(jid), C.GoString(text))
+	result, err := client.SendMessage(C.GoString(jid), C.GoString(text))
 	if err != nil {
-		return WM_ERR_CONNECT
+		return failureCode(err)
 	}
 
-	return WM_OK
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_message_ex
+func wm_send_message_ex(handle C.uintptr_t, jid *C.char, text *C.char, options *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var opts SendOptions
+	if options != nil {
+		if err := json.Unmarshal([]byte(C.GoString(options)), &opts); err != nil {
+			return WM_ERR_INVALID_ARGUMENT
+		}
+	}
+
+	result, err := client.SendMessageEx(C.GoString(jid), C.GoString(text), opts)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
 }
 
 //export wm_send_image
-func wm_send_image(handle C.uintptr_t, jid *C.char, data *C.char, dataLen C.int, mimeType *C.char, caption *C.char) C.int {
+func wm_send_image(handle C.uintptr_t, jid *C.char, data *C.char, dataLen C.int, mimeType *C.char, caption *C.char, buf *C.char, bufLen C.int) C.int {
 	client := getClient(uintptr(handle))
 	if client == nil {
 		return WM_ERR_INVALID_HANDLE
@@ -138,9 +212,687 @@ func wm_send_image(handle C.uintptr_t, jid *C.char, data *C.char, dataLen C.int,
 		captionStr = C.GoString(caption)
 	}
 
-	err := client.SendImage(C.GoString(jid), imageData, C.GoString(mimeType), captionStr)
+	result, err := client.SendImage(C.GoString(jid), imageData, C.GoString(mimeType), captionStr)
 	if err != nil {
-		return WM_ERR_CONNECT
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_image_ex
+func wm_send_image_ex(handle C.uintptr_t, jid *C.char, data *C.char, dataLen C.int, mimeType *C.char, caption *C.char, thumb *C.char, thumbLen C.int, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	imageData := C.GoBytes(unsafe.Pointer(data), dataLen)
+
+	var captionStr string
+	if caption != nil {
+		captionStr = C.GoString(caption)
+	}
+
+	var thumbnail []byte
+	if thumb != nil && thumbLen > 0 {
+		thumbnail = C.GoBytes(unsafe.Pointer(thumb), thumbLen)
+	}
+
+	result, err := client.SendImageEx(C.GoString(jid), imageData, C.GoString(mimeType), captionStr, thumbnail)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_album
+func wm_send_album(handle C.uintptr_t, jid *C.char, itemsJSON *C.char, caption *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var items []AlbumItem
+	if err := json.Unmarshal([]byte(C.GoString(itemsJSON)), &items); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	ids, err := client.SendAlbum(C.GoString(jid), items, C.GoString(caption))
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(ids, buf, bufLen)
+}
+
+//export wm_send_sticker
+func wm_send_sticker(handle C.uintptr_t, jid *C.char, data *C.char, dataLen C.int, width C.int, height C.int, animated C.int, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	stickerData := C.GoBytes(unsafe.Pointer(data), dataLen)
+
+	result, err := client.SendSticker(C.GoString(jid), stickerData, uint32(width), uint32(height), animated != 0)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_image_url
+func wm_send_image_url(handle C.uintptr_t, jid *C.char, url *C.char, mimeType *C.char, caption *C.char, thumb *C.char, thumbLen C.int, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var thumbnail []byte
+	if thumb != nil && thumbLen > 0 {
+		thumbnail = C.GoBytes(unsafe.Pointer(thumb), thumbLen)
+	}
+
+	result, err := client.SendImageURL(C.GoString(jid), C.GoString(url), C.GoString(mimeType), C.GoString(caption), thumbnail)
+	if errors.Is(err, errRemoteMedia) {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_media
+func wm_send_media(handle C.uintptr_t, jid *C.char, kind *C.char, data *C.char, dataLen C.int, mimeType *C.char, fileName *C.char, caption *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	mediaData := C.GoBytes(unsafe.Pointer(data), dataLen)
+
+	result, err := client.SendMedia(C.GoString(jid), C.GoString(kind), mediaData, C.GoString(mimeType), C.GoString(fileName), C.GoString(caption))
+	if errors.Is(err, errInvalidMediaKind) {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_buttons
+func wm_send_buttons(handle C.uintptr_t, jid *C.char, buttonsJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var buttons ButtonsMessage
+	if err := json.Unmarshal([]byte(C.GoString(buttonsJSON)), &buttons); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	result, err := client.SendButtons(C.GoString(jid), buttons)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_list
+func wm_send_list(handle C.uintptr_t, jid *C.char, listJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var list ListMessage
+	if err := json.Unmarshal([]byte(C.GoString(listJSON)), &list); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	result, err := client.SendList(C.GoString(jid), list)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_template
+func wm_send_template(handle C.uintptr_t, jid *C.char, templateJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var template TemplateMessage
+	if err := json.Unmarshal([]byte(C.GoString(templateJSON)), &template); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	result, err := client.SendTemplate(C.GoString(jid), template)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_payment_request
+func wm_send_payment_request(handle C.uintptr_t, jid *C.char, requestJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var req PaymentRequest
+	if err := json.Unmarshal([]byte(C.GoString(requestJSON)), &req); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	result, err := client.SendPaymentRequest(C.GoString(jid), req)
+	if errors.Is(err, errPaymentsUnsupported) {
+		return WM_ERR_UNSUPPORTED
+	}
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_send_raw
+func wm_send_raw(handle C.uintptr_t, jid *C.char, messageJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	result, err := client.SendRaw(C.GoString(jid), C.GoString(messageJSON))
+	if errors.Is(err, errInvalidRawMessage) {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(result, buf, bufLen)
+}
+
+//export wm_reject_call
+func wm_reject_call(handle C.uintptr_t, from *C.char, callID *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.RejectCall(C.GoString(from), C.GoString(callID)); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_mark_read
+func wm_mark_read(handle C.uintptr_t, chat *C.char, sender *C.char, idsJSON *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var ids []string
+	if err := json.Unmarshal([]byte(C.GoString(idsJSON)), &ids); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	if err := client.MarkRead(C.GoString(chat), C.GoString(sender), ids); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_mark_played
+func wm_mark_played(handle C.uintptr_t, chat *C.char, sender *C.char, idsJSON *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var ids []string
+	if err := json.Unmarshal([]byte(C.GoString(idsJSON)), &ids); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	if err := client.MarkPlayed(C.GoString(chat), C.GoString(sender), ids); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_subscribe_presence
+func wm_subscribe_presence(handle C.uintptr_t, jid *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.SubscribePresence(C.GoString(jid)); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_send_chat_presence
+func wm_send_chat_presence(handle C.uintptr_t, jid *C.char, state *C.char, media *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.SendChatPresence(C.GoString(jid), C.GoString(state), C.GoString(media)); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_get_contact
+func wm_get_contact(handle C.uintptr_t, jid *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	info, err := client.GetContact(C.GoString(jid))
+	if err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return writeJSON(info, buf, bufLen)
+}
+
+//export wm_resolve_lid
+func wm_resolve_lid(handle C.uintptr_t, lid *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	mapping, err := client.ResolveLID(C.GoString(lid))
+	if err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return writeJSON(mapping, buf, bufLen)
+}
+
+//export wm_get_profile_picture
+func wm_get_profile_picture(handle C.uintptr_t, jid *C.char, full C.int, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	picture, err := client.ProfilePicture(C.GoString(jid), full != 0)
+	if err != nil {
+		return failureCode(err)
+	}
+	if picture == nil {
+		return 0
+	}
+
+	return writeJSON(picture, buf, bufLen)
+}
+
+//export wm_get_own_jid
+func wm_get_own_jid(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	own := client.OwnJID()
+	if own == nil {
+		return 0
+	}
+
+	return writeBuffer([]byte(own.String()), buf, bufLen)
+}
+
+//export wm_get_push_name
+func wm_get_push_name(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	return writeBuffer([]byte(client.PushName()), buf, bufLen)
+}
+
+//export wm_set_push_name
+func wm_set_push_name(handle C.uintptr_t, name *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	err := client.SetPushName(C.GoString(name))
+	if errors.Is(err, errInvalidPushName) {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_server_time
+func wm_server_time(handle C.uintptr_t) C.longlong {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	return C.longlong(client.clock.Now())
+}
+
+//export wm_is_on_whatsapp
+func wm_is_on_whatsapp(handle C.uintptr_t, phonesJSON *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var phones []string
+	if err := json.Unmarshal([]byte(C.GoString(phonesJSON)), &phones); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	statuses, err := client.IsOnWhatsApp(phones)
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(statuses, buf, bufLen)
+}
+
+//export wm_query_messages
+func wm_query_messages(handle C.uintptr_t, chat *C.char, limit C.int, beforeID *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	var before string
+	if beforeID != nil {
+		before = C.GoString(beforeID)
+	}
+
+	messages, err := client.QueryMessages(C.GoString(chat), int(limit), before)
+	if err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return writeJSON(messages, buf, bufLen)
+}
+
+//export wm_star_message
+func wm_star_message(handle C.uintptr_t, chat *C.char, messageID *C.char, starred C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.StarMessage(C.GoString(chat), C.GoString(messageID), starred != 0); err != nil {
+		if errors.Is(err, errUnknownMessage) {
+			return WM_ERR_INVALID_ARGUMENT
+		}
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_get_starred
+func wm_get_starred(handle C.uintptr_t, chat *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	messages, err := client.StarredMessages(C.GoString(chat))
+	if err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return writeJSON(messages, buf, bufLen)
+}
+
+//export wm_resync_app_state
+func wm_resync_app_state(handle C.uintptr_t, name *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.ResyncAppState(C.GoString(name)); err != nil {
+		if errors.Is(err, errInvalidCollection) {
+			return WM_ERR_INVALID_ARGUMENT
+		}
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_mark_chat_unread
+func wm_mark_chat_unread(handle C.uintptr_t, jid *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.MarkChatUnread(C.GoString(jid)); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_mute_chat
+func wm_mute_chat(handle C.uintptr_t, jid *C.char, mute C.int, durationSecs C.longlong) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	duration := time.Duration(durationSecs) * time.Second
+	if err := client.MuteChat(C.GoString(jid), mute != 0, duration); err != nil {
+		return failureCode(err)
+	}
+
+	return WM_OK
+}
+
+//export wm_clear_chat
+func wm_clear_chat(handle C.uintptr_t, jid *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.ClearChat(C.GoString(jid)); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return WM_OK
+}
+
+//export wm_export_session
+func wm_export_session(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	blob, err := client.ExportSession()
+	if err != nil {
+		return WM_ERR_INIT
+	}
+	defer zeroBytes(blob)
+
+	return writeBuffer(blob, buf, bufLen)
+}
+
+//export wm_import_session
+func wm_import_session(handle C.uintptr_t, data *C.char, dataLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	blob := C.GoBytes(unsafe.Pointer(data), dataLen)
+	defer zeroBytes(blob)
+
+	if err := client.ImportSession(blob); err != nil {
+		if errors.Is(err, errSessionImport) {
+			return WM_ERR_INVALID_ARGUMENT
+		}
+		return WM_ERR_INIT
+	}
+
+	return WM_OK
+}
+
+//export wm_get_unread_counts
+func wm_get_unread_counts(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	counts, err := client.UnreadCounts()
+	if err != nil {
+		return WM_ERR_INIT
+	}
+
+	return writeJSON(counts, buf, bufLen)
+}
+
+//export wm_get_store_stats
+func wm_get_store_stats(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	stats, err := client.StoreStats()
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(stats, buf, bufLen)
+}
+
+//export wm_get_linked_devices
+func wm_get_linked_devices(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	devices, err := client.LinkedDevices()
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(devices, buf, bufLen)
+}
+
+//export wm_remove_device
+func wm_remove_device(handle C.uintptr_t, jid *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.RemoveDevice(C.GoString(jid)); err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return WM_OK
+}
+
+//export wm_get_joined_groups
+func wm_get_joined_groups(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	groups, err := client.JoinedGroups()
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(groups, buf, bufLen)
+}
+
+//export wm_get_group_info_from_link
+func wm_get_group_info_from_link(handle C.uintptr_t, code *C.char, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	meta, err := client.GroupInfoFromLink(C.GoString(code))
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(meta, buf, bufLen)
+}
+
+//export wm_get_privacy_settings
+func wm_get_privacy_settings(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	settings, err := client.PrivacySettings()
+	if err != nil {
+		return failureCode(err)
+	}
+
+	return writeJSON(settings, buf, bufLen)
+}
+
+//export wm_set_privacy_setting
+func wm_set_privacy_setting(handle C.uintptr_t, key *C.char, value *C.char) C.int {
+	client := getClient(uintptr(handle))
+	if client == nil {
+		return WM_ERR_INVALID_HANDLE
+	}
+
+	if err := client.SetPrivacySetting(C.GoString(key), C.GoString(value)); err != nil {
+		if errors.Is(err, errInvalidPrivacy) {
+			return WM_ERR_INVALID_ARGUMENT
+		}
+		return failureCode(err)
 	}
 
 	return WM_OK
@@ -169,6 +921,30 @@ func wm_last_error(handle C.uintptr_t, buf *C.char, bufLen C.int) C.int {
 	return C.int(len(msg))
 }
 
+// writeBuffer copies data into a caller-provided buffer, returning its length
+func writeBuffer(data []byte, buf *C.char, bufLen C.int) C.int {
+	if len(data) == 0 {
+		return 0
+	}
+
+	if len(data) > int(bufLen) {
+		return WM_ERR_BUFFER_TOO_SMALL
+	}
+
+	C.memcpy(unsafe.Pointer(buf), unsafe.Pointer(&data[0]), C.size_t(len(data)))
+	return C.int(len(data))
+}
+
+// writeJSON marshals v into a caller-provided buffer
+func writeJSON(v interface{}, buf *C.char, bufLen C.int) C.int {
+	data, err := json.Marshal(v)
+	if err != nil {
+		return WM_ERR_INVALID_ARGUMENT
+	}
+
+	return writeBuffer(data, buf, bufLen)
+}
+
 func getClient(handle uintptr) *Client {
 	clientsMu.RLock()
 	defer clientsMu.RUnlock()
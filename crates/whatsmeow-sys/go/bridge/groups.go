@@ -0,0 +1,78 @@
+package main
+
+import (
+	"fmt"
+	"time"
+
+	"go.mau.fi/whatsmeow/types"
+)
+
+// GroupMetadata is the subset of types.GroupInfo exposed to Rust
+type GroupMetadata struct {
+	JID              types.JID          `json:"jid"`
+	Name             string             `json:"name"`
+	Topic            string             `json:"topic,omitempty"`
+	Owner            string             `json:"owner,omitempty"`
+	Created          time.Time          `json:"created"`
+	IsAnnounce       bool               `json:"is_announce"`
+	IsLocked         bool               `json:"is_locked"`
+	ParticipantCount int                `json:"participant_count"`
+	Participants     []GroupParticipant `json:"participants"`
+}
+
+// GroupParticipant is a member of a group
+type GroupParticipant struct {
+	JID          types.JID `json:"jid"`
+	IsAdmin      bool      `json:"is_admin"`
+	IsSuperAdmin bool      `json:"is_super_admin"`
+}
+
+func groupMetadataFromInfo(info *types.GroupInfo) GroupMetadata {
+	meta := GroupMetadata{
+		JID:              info.JID,
+		Name:             info.Name,
+		Topic:            info.Topic,
+		Created:          info.GroupCreated,
+		IsAnnounce:       info.IsAnnounce,
+		IsLocked:         info.IsLocked,
+		ParticipantCount: len(info.Participants),
+		Participants:     make([]GroupParticipant, 0, len(info.Participants)),
+	}
+	if !info.OwnerJID.IsEmpty() {
+		meta.Owner = info.OwnerJID.String()
+	}
+	for _, p := range info.Participants {
+		meta.Participants = append(meta.Participants, GroupParticipant{
+			JID:          p.JID,
+			IsAdmin:      p.IsAdmin,
+			IsSuperAdmin: p.IsSuperAdmin,
+		})
+	}
+	return meta
+}
+
+// JoinedGroups lists the groups this account is a member of
+func (c *Client) JoinedGroups() ([]GroupMetadata, error) {
+	infos, err := c.client.GetJoinedGroups(c.ctx)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("joined groups query failed: %w", err)
+	}
+
+	groups := make([]GroupMetadata, 0, len(infos))
+	for _, info := range infos {
+		groups = append(groups, groupMetadataFromInfo(info))
+	}
+	return groups, nil
+}
+
+// GroupInfoFromLink resolves an invite code to group metadata without joining
+func (c *Client) GroupInfoFromLink(code string) (GroupMetadata, error) {
+	info, err := c.client.GetGroupInfoFromLink(c.ctx, code)
+	if err != nil {
+		c.setLastError(err)
+		return GroupMetadata{}, fmt.Errorf("invite lookup failed: %w", err)
+	}
+
+	return groupMetadataFromInfo(info), nil
+}
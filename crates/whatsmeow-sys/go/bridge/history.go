@@ -0,0 +1,350 @@
+package main
+
+import (
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"time"
+
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"go.mau.fi/whatsmeow/types/events"
+)
+
+// StoredMessage is a message persisted in the bridge's local history table
+type StoredMessage struct {
+	ID        string          `json:"id"`
+	Chat      string          `json:"chat"`
+	Sender    string          `json:"sender"`
+	IsFromMe  bool            `json:"is_from_me"`
+	PushName  string          `json:"push_name"`
+	Timestamp time.Time       `json:"timestamp"`
+	Type      string          `json:"message_type"`
+	Text      string          `json:"text"`
+	Message   json.RawMessage `json:"message,omitempty"`
+}
+
+// HistoryStore keeps received messages so they can be queried later.
+// whatsmeow's own store does not persist message contents.
+type HistoryStore struct {
+	db *sql.DB
+}
+
+const historySchema = `
+CREATE TABLE IF NOT EXISTS rs_messages (
+	chat         TEXT    NOT NULL,
+	id           TEXT    NOT NULL,
+	sender       TEXT    NOT NULL,
+	is_from_me   INTEGER NOT NULL,
+	push_name    TEXT    NOT NULL DEFAULT '',
+	timestamp    INTEGER NOT NULL,
+	message_type TEXT    NOT NULL DEFAULT '',
+	text         TEXT    NOT NULL DEFAULT '',
+	raw          TEXT,
+	PRIMARY KEY (chat, id)
+);
+CREATE INDEX IF NOT EXISTS rs_messages_chat_ts ON rs_messages (chat, timestamp);
+CREATE TABLE IF NOT EXISTS rs_starred (
+	chat TEXT NOT NULL,
+	id   TEXT NOT NULL,
+	PRIMARY KEY (chat, id)
+);
+CREATE TABLE IF NOT EXISTS rs_chat_reads (
+	chat       TEXT    PRIMARY KEY,
+	read_until INTEGER NOT NULL
+);
+`
+
+// OpenHistoryStore opens (and migrates) the history table in the session database
+func OpenHistoryStore(dsn string) (*HistoryStore, error) {
+	db, err := sql.Open("sqlite3", dsn)
+	if err != nil {
+		return nil, fmt.Errorf("failed to open history store: %w", err)
+	}
+
+	if _, err := db.Exec(historySchema); err != nil {
+		db.Close()
+		return nil, fmt.Errorf("failed to migrate history store: %w", err)
+	}
+
+	return &HistoryStore{db: db}, nil
+}
+
+// Save inserts or updates a message
+func (h *HistoryStore) Save(evt *events.Message) error {
+	raw, err := json.Marshal(evt.Message)
+	if err != nil {
+		return err
+	}
+
+	_, err = h.db.Exec(`
+		INSERT INTO rs_messages (chat, id, sender, is_from_me, push_name, timestamp, message_type, text, raw)
+		VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+		ON CONFLICT (chat, id) DO UPDATE SET
+			push_name = excluded.push_name,
+			message_type = excluded.message_type,
+			text = excluded.text,
+			raw = excluded.raw`,
+		evt.Info.Chat.String(),
+		evt.Info.ID,
+		evt.Info.Sender.String(),
+		evt.Info.IsFromMe,
+		evt.Info.PushName,
+		evt.Info.Timestamp.Unix(),
+		evt.Info.Type,
+		messageText(evt.Message),
+		string(raw),
+	)
+	return err
+}
+
+// Query returns up to limit messages in a chat, oldest first. If beforeID is
+// set, only messages older than that message are returned.
+func (h *HistoryStore) Query(chat string, limit int, beforeID string) ([]StoredMessage, error) {
+	query := `SELECT id, chat, sender, is_from_me, push_name, timestamp, message_type, text, raw
+		FROM rs_messages WHERE chat = ?`
+	args := []interface{}{chat}
+
+	if beforeID != "" {
+		var ts int64
+		err := h.db.QueryRow(`SELECT timestamp FROM rs_messages WHERE chat = ? AND id = ?`, chat, beforeID).Scan(&ts)
+		if errors.Is(err, sql.ErrNoRows) {
+			return nil, fmt.Errorf("unknown message id %q", beforeID)
+		} else if err != nil {
+			return nil, err
+		}
+
+		query += ` AND (timestamp < ? OR (timestamp = ? AND id < ?))`
+		args = append(args, ts, ts, beforeID)
+	}
+
+	query += ` ORDER BY timestamp DESC, id DESC LIMIT ?`
+	args = append(args, limit)
+
+	rows, err := h.db.Query(query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+
+	messages := make([]StoredMessage, 0, limit)
+	for rows.Next() {
+		msg, err := scanMessage(rows)
+		if err != nil {
+			return nil, err
+		}
+		messages = append(messages, msg)
+	}
+
+	// Reverse into chronological order
+	for i, j := 0, len(messages)-1; i < j; i, j = i+1, j-1 {
+		messages[i], messages[j] = messages[j], messages[i]
+	}
+
+	return messages, rows.Err()
+}
+
+// Get returns a single stored message, or nil if unknown
+func (h *HistoryStore) Get(chat, id string) (*StoredMessage, error) {
+	row := h.db.QueryRow(`SELECT id, chat, sender, is_from_me, push_name, timestamp, message_type, text, raw
+		FROM rs_messages WHERE chat = ? AND id = ?`, chat, id)
+
+	msg, err := scanMessage(row)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil
+	} else if err != nil {
+		return nil, err
+	}
+	return &msg, nil
+}
+
+// SetStarred records the starred flag of a message
+func (h *HistoryStore) SetStarred(chat, id string, starred bool) error {
+	var err error
+	if starred {
+		_, err = h.db.Exec(`INSERT OR IGNORE INTO rs_starred (chat, id) VALUES (?, ?)`, chat, id)
+	} else {
+		_, err = h.db.Exec(`DELETE FROM rs_starred WHERE chat = ? AND id = ?`, chat, id)
+	}
+	return err
+}
+
+// Starred returns the starred messages in a chat, oldest first
+func (h *HistoryStore) Starred(chat string) ([]StoredMessage, error) {
+	rows, err := h.db.Query(`SELECT m.id, m.chat, m.sender, m.is_from_me, m.push_name, m.timestamp, m.message_type, m.text, m.raw
+		FROM rs_messages m
+		JOIN rs_starred s ON s.chat = m.chat AND s.id = m.id
+		WHERE m.chat = ?
+		ORDER BY m.timestamp, m.id`, chat)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+
+	messages := []StoredMessage{}
+	for rows.Next() {
+		msg, err := scanMessage(rows)
+		if err != nil {
+			return nil, err
+		}
+		messages = append(messages, msg)
+	}
+
+	return messages, rows.Err()
+}
+
+// scanMessage reads one rs_messages row in the column order used by queries
+func scanMessage(row interface{ Scan(...interface{}) error }) (StoredMessage, error) {
+	var msg StoredMessage
+	var ts int64
+	var raw sql.NullString
+	if err := row.Scan(&msg.ID, &msg.Chat, &msg.Sender, &msg.IsFromMe, &msg.PushName, &ts, &msg.Type, &msg.Text, &raw); err != nil {
+		return StoredMessage{}, err
+	}
+	msg.Timestamp = time.Unix(ts, 0).UTC()
+	if raw.Valid && raw.String != "" && raw.String != "null" {
+		msg.Message = json.RawMessage(raw.String)
+	}
+	return msg, nil
+}
+
+// Message loads the stored protobuf of a message, or nil if unknown
+func (h *HistoryStore) Message(chat, id string) (*waProto.Message, error) {
+	var raw sql.NullString
+	err := h.db.QueryRow(`SELECT raw FROM rs_messages WHERE chat = ? AND id = ?`, chat, id).Scan(&raw)
+	if errors.Is(err, sql.ErrNoRows) || !raw.Valid {
+		return nil, nil
+	} else if err != nil {
+		return nil, err
+	}
+
+	var msg waProto.Message
+	if err := json.Unmarshal([]byte(raw.String), &msg); err != nil {
+		return nil, err
+	}
+	return &msg, nil
+}
+
+// MarkReadUntil records that every message in a chat up to ts has been read.
+// The mark never moves backwards.
+func (h *HistoryStore) MarkReadUntil(chat string, ts int64) error {
+	_, err := h.db.Exec(`
+		INSERT INTO rs_chat_reads (chat, read_until) VALUES (?, ?)
+		ON CONFLICT (chat) DO UPDATE SET read_until = MAX(read_until, excluded.read_until)`,
+		chat, ts)
+	return err
+}
+
+// MarkReadIDs moves a chat's read mark up to the newest of the given messages.
+// Unknown IDs are ignored.
+func (h *HistoryStore) MarkReadIDs(chat string, ids []string) error {
+	var newest int64
+	for _, id := range ids {
+		var ts int64
+		err := h.db.QueryRow(`SELECT timestamp FROM rs_messages WHERE chat = ? AND id = ?`, chat, id).Scan(&ts)
+		if errors.Is(err, sql.ErrNoRows) {
+			continue
+		} else if err != nil {
+			return err
+		}
+		if ts > newest {
+			newest = ts
+		}
+	}
+
+	if newest == 0 {
+		return nil
+	}
+	return h.MarkReadUntil(chat, newest)
+}
+
+// MarkUnread moves a chat's read mark back so its newest incoming message
+// counts as unread again
+func (h *HistoryStore) MarkUnread(chat string) error {
+	var newest sql.NullInt64
+	err := h.db.QueryRow(`SELECT MAX(timestamp) FROM rs_messages WHERE chat = ? AND is_from_me = 0`, chat).Scan(&newest)
+	if err != nil || !newest.Valid {
+		return err
+	}
+
+	_, err = h.db.Exec(`
+		INSERT INTO rs_chat_reads (chat, read_until) VALUES (?, ?)
+		ON CONFLICT (chat) DO UPDATE SET read_until = MIN(read_until, excluded.read_until)`,
+		chat, newest.Int64-1)
+	return err
+}
+
+// ClearChat deletes every stored message of a chat along with its stars and read mark
+func (h *HistoryStore) ClearChat(chat string) error {
+	tx, err := h.db.Begin()
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+
+	for _, table := range []string{"rs_messages", "rs_starred", "rs_chat_reads"} {
+		if _, err := tx.Exec(`DELETE FROM `+table+` WHERE chat = ?`, chat); err != nil {
+			return err
+		}
+	}
+
+	return tx.Commit()
+}
+
+// UnreadCounts returns the number of incoming messages newer than each chat's
+// read mark. Chats with nothing unread are omitted.
+func (h *HistoryStore) UnreadCounts() (map[string]int, error) {
+	rows, err := h.db.Query(`
+		SELECT m.chat, COUNT(*)
+		FROM rs_messages m
+		LEFT JOIN rs_chat_reads r ON r.chat = m.chat
+		WHERE m.is_from_me = 0 AND m.timestamp > COALESCE(r.read_until, 0)
+		GROUP BY m.chat`)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+
+	counts := make(map[string]int)
+	for rows.Next() {
+		var chat string
+		var count int
+		if err := rows.Scan(&chat, &count); err != nil {
+			return nil, err
+		}
+		counts[chat] = count
+	}
+
+	return counts, rows.Err()
+}
+
+// Counts returns the number of stored messages and of chats they belong to
+func (h *HistoryStore) Counts() (messages, chats int64, err error) {
+	err = h.db.QueryRow(`SELECT COUNT(*), COUNT(DISTINCT chat) FROM rs_messages`).Scan(&messages, &chats)
+	return messages, chats, err
+}
+
+// Close releases the database handle
+func (h *HistoryStore) Close() error {
+	return h.db.Close()
+}
+
+// messageText extracts the displayable text of a message, if any
+func messageText(msg *waProto.Message) string {
+	switch {
+	case msg == nil:
+		return ""
+	case msg.GetConversation() != "":
+		return msg.GetConversation()
+	case msg.GetExtendedTextMessage() != nil:
+		return msg.GetExtendedTextMessage().GetText()
+	case msg.GetImageMessage() != nil:
+		return msg.GetImageMessage().GetCaption()
+	case msg.GetVideoMessage() != nil:
+		return msg.GetVideoMessage().GetCaption()
+	case msg.GetDocumentMessage() != nil:
+		return msg.GetDocumentMessage().GetCaption()
+	default:
+		return ""
+	}
+}
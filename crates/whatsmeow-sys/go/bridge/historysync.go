@@ -0,0 +1,70 @@
+package main
+
+import (
+	"bufio"
+	"encoding/json"
+	"fmt"
+	"os"
+
+	"go.mau.fi/whatsmeow/types/events"
+)
+
+// HistorySyncFile points the Rust side at a history sync payload written to
+// disk, since full syncs are far larger than the event buffer
+type HistorySyncFile struct {
+	Path          string `json:"path"`
+	SyncType      string `json:"sync_type"`
+	Progress      uint32 `json:"progress"`
+	Conversations int    `json:"conversations"`
+}
+
+// spillHistorySync writes a history sync's conversations to a temp file, one
+// JSON document per line, and queues a small event naming the file. The
+// reader deletes the file once it has streamed it.
+func (c *Client) spillHistorySync(evt *events.HistorySync) {
+	file, err := c.writeHistorySync(evt)
+	if err != nil {
+		c.setLastError(err)
+		// Still report that a sync happened, without its contents
+		if data, err := MarshalTypedEvent("history_sync", nil); err == nil {
+			c.enqueue(data)
+		}
+		return
+	}
+
+	data, err := MarshalTypedEvent("history_sync_file", file)
+	if err != nil {
+		_ = os.Remove(file.Path)
+		return
+	}
+	c.enqueue(data)
+}
+
+func (c *Client) writeHistorySync(evt *events.HistorySync) (HistorySyncFile, error) {
+	f, err := os.CreateTemp("", "whatsmeow-history-*.jsonl")
+	if err != nil {
+		return HistorySyncFile{}, fmt.Errorf("history spill failed: %w", err)
+	}
+	defer f.Close()
+
+	w := bufio.NewWriter(f)
+	enc := json.NewEncoder(w)
+	conversations := evt.Data.GetConversations()
+	for _, conv := range conversations {
+		if err := enc.Encode(conv); err != nil {
+			_ = os.Remove(f.Name())
+			return HistorySyncFile{}, fmt.Errorf("history spill failed: %w", err)
+		}
+	}
+	if err := w.Flush(); err != nil {
+		_ = os.Remove(f.Name())
+		return HistorySyncFile{}, fmt.Errorf("history spill failed: %w", err)
+	}
+
+	return HistorySyncFile{
+		Path:          f.Name(),
+		SyncType:      evt.Data.GetSyncType().String(),
+		Progress:      evt.Data.GetProgress(),
+		Conversations: len(conversations),
+	}, nil
+}
@@ -0,0 +1,113 @@
+package main
+
+import (
+	"fmt"
+
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"go.mau.fi/whatsmeow/types"
+	"google.golang.org/protobuf/proto"
+)
+
+// Button is a quick-reply button
+type Button struct {
+	ID    string `json:"id"`
+	Title string `json:"title"`
+}
+
+// ButtonsMessage is a text body with up to three reply buttons
+type ButtonsMessage struct {
+	Body    string   `json:"body"`
+	Buttons []Button `json:"buttons"`
+}
+
+// ListRow is one selectable entry of a list menu
+type ListRow struct {
+	ID          string `json:"id"`
+	Title       string `json:"title"`
+	Description string `json:"description,omitempty"`
+}
+
+// ListSection groups rows of a list menu under a title
+type ListSection struct {
+	Title string    `json:"title"`
+	Rows  []ListRow `json:"rows"`
+}
+
+// ListMessage is a text body with a button opening a sectioned menu
+type ListMessage struct {
+	Body       string        `json:"body"`
+	ButtonText string        `json:"button_text"`
+	Sections   []ListSection `json:"sections"`
+}
+
+// SendButtons sends a message with reply buttons
+func (c *Client) SendButtons(jidStr string, buttons ButtonsMessage) (SendResult, error) {
+	protoButtons := make([]*waProto.ButtonsMessage_Button, 0, len(buttons.Buttons))
+	for _, b := range buttons.Buttons {
+		protoButtons = append(protoButtons, &waProto.ButtonsMessage_Button{
+			ButtonID: proto.String(b.ID),
+			ButtonText: &waProto.ButtonsMessage_Button_ButtonText{
+				DisplayText: proto.String(b.Title),
+			},
+			Type: waProto.ButtonsMessage_Button_RESPONSE.Enum(),
+		})
+	}
+
+	return c.sendInteractive(jidStr, &waProto.Message{
+		ButtonsMessage: &waProto.ButtonsMessage{
+			ContentText: proto.String(buttons.Body),
+			HeaderType:  waProto.ButtonsMessage_EMPTY.Enum(),
+			Buttons:     protoButtons,
+		},
+	})
+}
+
+// SendList sends a message with a single-select list menu
+func (c *Client) SendList(jidStr string, list ListMessage) (SendResult, error) {
+	sections := make([]*waProto.ListMessage_Section, 0, len(list.Sections))
+	for _, s := range list.Sections {
+		rows := make([]*waProto.ListMessage_Row, 0, len(s.Rows))
+		for _, r := range s.Rows {
+			rows = append(rows, &waProto.ListMessage_Row{
+				RowID:       proto.String(r.ID),
+				Title:       proto.String(r.Title),
+				Description: proto.String(r.Description),
+			})
+		}
+		sections = append(sections, &waProto.ListMessage_Section{
+			Title: proto.String(s.Title),
+			Rows:  rows,
+		})
+	}
+
+	return c.sendInteractive(jidStr, &waProto.Message{
+		ListMessage: &waProto.ListMessage{
+			Description: proto.String(list.Body),
+			ButtonText:  proto.String(list.ButtonText),
+			ListType:    waProto.ListMessage_SINGLE_SELECT.Enum(),
+			Sections:    sections,
+		},
+	})
+}
+
+// sendInteractive sends a prebuilt message
+func (c *Client) sendInteractive(jidStr string, msg *waProto.Message) (SendResult, error) {
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+
+	if !c.connected {
+		return SendResult{}, errNotConnected
+	}
+
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		return SendResult{}, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	resp, err := c.client.SendMessage(c.ctx, jid, msg)
+	if err != nil {
+		return SendResult{}, fmt.Errorf("send failed: %w", err)
+	}
+
+	return newSendResult(resp), nil
+}
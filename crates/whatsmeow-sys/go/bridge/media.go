@@ -0,0 +1,162 @@
+package main
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"go.mau.fi/whatsmeow"
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"google.golang.org/protobuf/proto"
+)
+
+// errInvalidMediaKind marks media kinds other than video, audio or document
+var errInvalidMediaKind = errors.New("invalid media kind")
+
+// errRemoteMedia marks image URLs that can't be fetched or aren't images
+var errRemoteMedia = errors.New("remote media unavailable")
+
+// maxRemoteImageSize caps images fetched by URL (WhatsApp's own image limit)
+const maxRemoteImageSize = 16 << 20
+
+// remoteHTTP fetches media for SendImageURL
+var remoteHTTP = &http.Client{Timeout: 60 * time.Second}
+
+// SendImageURL downloads an image and sends it, so its bytes never cross
+// the FFI boundary. mimeType overrides the detected type when non-empty.
+func (c *Client) SendImageURL(jidStr, url, mimeType, caption string, thumbnail []byte) (SendResult, error) {
+	data, detected, err := fetchRemote(c.ctx, url, maxRemoteImageSize)
+	if err != nil {
+		c.setLastError(err)
+		return SendResult{}, err
+	}
+
+	if mimeType == "" {
+		mimeType = detected
+	}
+	if !strings.HasPrefix(mimeType, "image/") {
+		err := fmt.Errorf("%w: %s is %s, not an image", errRemoteMedia, url, mimeType)
+		c.setLastError(err)
+		return SendResult{}, err
+	}
+
+	return c.SendImageEx(jidStr, data, mimeType, caption, thumbnail)
+}
+
+// fetchRemote downloads at most limit bytes from url, returning the body and
+// its sniffed MIME type
+func fetchRemote(ctx context.Context, url string, limit int64) ([]byte, string, error) {
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, url, nil)
+	if err != nil {
+		return nil, "", fmt.Errorf("%w: %v", errRemoteMedia, err)
+	}
+
+	resp, err := remoteHTTP.Do(req)
+	if err != nil {
+		return nil, "", fmt.Errorf("%w: %v", errRemoteMedia, err)
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, "", fmt.Errorf("%w: %s returned HTTP %d", errRemoteMedia, url, resp.StatusCode)
+	}
+
+	data, err := io.ReadAll(io.LimitReader(resp.Body, limit+1))
+	if err != nil {
+		return nil, "", fmt.Errorf("%w: %v", errRemoteMedia, err)
+	}
+	if int64(len(data)) > limit {
+		return nil, "", fmt.Errorf("%w: %s is larger than %d bytes", errRemoteMedia, url, limit)
+	}
+
+	return data, http.DetectContentType(data), nil
+}
+
+// SendMedia uploads and sends a video, audio or document message.
+// fileName is only used for documents; caption is ignored for audio.
+func (c *Client) SendMedia(jidStr, kind string, data []byte, mimeType, fileName, caption string) (SendResult, error) {
+	var mediaType whatsmeow.MediaType
+	switch kind {
+	case "video":
+		mediaType = whatsmeow.MediaVideo
+	case "audio":
+		mediaType = whatsmeow.MediaAudio
+	case "document":
+		mediaType = whatsmeow.MediaDocument
+	default:
+		return SendResult{}, fmt.Errorf("%w: %q", errInvalidMediaKind, kind)
+	}
+
+	uploaded, err := c.upload(data, mediaType)
+	if err != nil {
+		return SendResult{}, err
+	}
+
+	var captionPtr *string
+	if caption != "" {
+		captionPtr = proto.String(caption)
+	}
+
+	msg := &waProto.Message{}
+	switch kind {
+	case "video":
+		msg.VideoMessage = &waProto.VideoMessage{
+			URL:           proto.String(uploaded.URL),
+			DirectPath:    proto.String(uploaded.DirectPath),
+			MediaKey:      uploaded.MediaKey,
+			Mimetype:      proto.String(mimeType),
+			FileEncSHA256: uploaded.FileEncSHA256,
+			FileSHA256:    uploaded.FileSHA256,
+			FileLength:    proto.Uint64(uint64(len(data))),
+			Caption:       captionPtr,
+		}
+	case "audio":
+		msg.AudioMessage = &waProto.AudioMessage{
+			URL:           proto.String(uploaded.URL),
+			DirectPath:    proto.String(uploaded.DirectPath),
+			MediaKey:      uploaded.MediaKey,
+			Mimetype:      proto.String(mimeType),
+			FileEncSHA256: uploaded.FileEncSHA256,
+			FileSHA256:    uploaded.FileSHA256,
+			FileLength:    proto.Uint64(uint64(len(data))),
+		}
+	case "document":
+		msg.DocumentMessage = &waProto.DocumentMessage{
+			URL:           proto.String(uploaded.URL),
+			DirectPath:    proto.String(uploaded.DirectPath),
+			MediaKey:      uploaded.MediaKey,
+			Mimetype:      proto.String(mimeType),
+			FileEncSHA256: uploaded.FileEncSHA256,
+			FileSHA256:    uploaded.FileSHA256,
+			FileLength:    proto.Uint64(uint64(len(data))),
+			FileName:      proto.String(fileName),
+			Title:         proto.String(fileName),
+			Caption:       captionPtr,
+		}
+	}
+
+	return c.sendInteractive(jidStr, msg)
+}
+
+// upload sends media to WhatsApp's servers, releasing the lock before the
+// message itself is sent
+func (c *Client) upload(data []byte, mediaType whatsmeow.MediaType) (whatsmeow.UploadResponse, error) {
+	c.mu.RLock()
+	defer c.mu.RUnlock()
+
+	if !c.connected {
+		return whatsmeow.UploadResponse{}, errNotConnected
+	}
+
+	uploaded, err := c.client.Upload(c.ctx, data, mediaType)
+	if err != nil {
+		c.setLastError(err)
+		return whatsmeow.UploadResponse{}, fmt.Errorf("upload failed: %w", err)
+	}
+
+	return uploaded, nil
+}
@@ -0,0 +1,52 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+
+	"go.mau.fi/whatsmeow"
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"go.mau.fi/whatsmeow/types"
+	"google.golang.org/protobuf/proto"
+)
+
+// errPaymentsUnsupported marks payment messages the server refused, which
+// happens for accounts without WhatsApp payments
+var errPaymentsUnsupported = errors.New("payments not supported for this account")
+
+// PaymentRequest asks the recipient to pay an amount
+type PaymentRequest struct {
+	Amount1000 uint64 `json:"amount_1000"`
+	Currency   string `json:"currency"`
+	Note       string `json:"note"`
+}
+
+// SendPaymentRequest sends a request for payment. Only accounts in regions
+// with WhatsApp payments can send these; elsewhere the server rejects them.
+func (c *Client) SendPaymentRequest(jidStr string, req PaymentRequest) (SendResult, error) {
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return SendResult{}, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	payment := &waProto.RequestPaymentMessage{
+		CurrencyCodeIso4217: proto.String(req.Currency),
+		Amount1000:          proto.Uint64(req.Amount1000),
+		RequestFrom:         proto.String(jid.ToNonAD().String()),
+	}
+	if req.Note != "" {
+		payment.NoteMessage = &waProto.Message{
+			ExtendedTextMessage: &waProto.ExtendedTextMessage{Text: proto.String(req.Note)},
+		}
+	}
+
+	result, err := c.sendInteractive(jidStr, &waProto.Message{RequestPaymentMessage: payment})
+	if errors.Is(err, whatsmeow.ErrServerReturnedError) {
+		err = fmt.Errorf("%w: %v", errPaymentsUnsupported, err)
+	}
+	if err != nil {
+		c.setLastError(err)
+	}
+	return result, err
+}
@@ -0,0 +1,104 @@
+package main
+
+import (
+	"crypto/sha256"
+	"fmt"
+	"time"
+
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"go.mau.fi/whatsmeow/types"
+	"go.mau.fi/whatsmeow/types/events"
+)
+
+// PollVote is a decrypted poll vote with option names resolved
+type PollVote struct {
+	PollID    string    `json:"poll_id"`
+	MessageID string    `json:"message_id"`
+	Chat      types.JID `json:"chat"`
+	Voter     types.JID `json:"voter"`
+	Options   []string  `json:"options"`
+	Timestamp time.Time `json:"timestamp"`
+}
+
+// handlePollVote decrypts a poll update and queues a "poll_vote" event
+func (c *Client) handlePollVote(msg *events.Message) {
+	vote, err := c.DecryptPollVote(msg)
+	if err != nil {
+		c.setLastError(err)
+		return
+	}
+
+	data, err := MarshalTypedEvent("poll_vote", vote)
+	if err != nil {
+		return
+	}
+	c.enqueue(data)
+}
+
+// DecryptPollVote decrypts a pollUpdateMessage and maps the selected option
+// hashes back to names using the original poll from the history store
+func (c *Client) DecryptPollVote(msg *events.Message) (*PollVote, error) {
+	decrypted, err := c.client.DecryptPollVote(c.ctx, msg)
+	if err != nil {
+		return nil, fmt.Errorf("decrypt poll vote failed: %w", err)
+	}
+
+	pollID := msg.Message.GetPollUpdateMessage().GetPollCreationMessageKey().GetID()
+	poll, err := c.history.Message(msg.Info.Chat.String(), pollID)
+	if err != nil {
+		return nil, fmt.Errorf("load poll %s failed: %w", pollID, err)
+	}
+	if poll == nil {
+		return nil, fmt.Errorf("unknown poll %s", pollID)
+	}
+
+	return &PollVote{
+		PollID:    pollID,
+		MessageID: msg.Info.ID,
+		Chat:      msg.Info.Chat,
+		Voter:     msg.Info.Sender,
+		Options:   resolvePollOptions(pollOptions(poll), decrypted.GetSelectedOptions()),
+		Timestamp: msg.Info.Timestamp,
+	}, nil
+}
+
+// resolvePollOptions maps the selected option hashes of a vote back to names
+//
+// Votes carry SHA-256 hashes of the option names; unknown hashes are skipped.
+// The result is never nil so a withdrawn vote marshals as an empty list.
+func resolvePollOptions(names []string, selected [][]byte) []string {
+	byHash := make(map[[32]byte]string, len(names))
+	for _, name := range names {
+		byHash[sha256.Sum256([]byte(name))] = name
+	}
+
+	options := make([]string, 0, len(selected))
+	for _, hash := range selected {
+		var key [32]byte
+		if len(hash) != len(key) {
+			continue
+		}
+		copy(key[:], hash)
+		if name, ok := byHash[key]; ok {
+			options = append(options, name)
+		}
+	}
+	return options
+}
+
+// pollOptions returns the option names of any poll creation message version
+func pollOptions(msg *waProto.Message) []string {
+	poll := msg.GetPollCreationMessage()
+	if poll == nil {
+		poll = msg.GetPollCreationMessageV2()
+	}
+	if poll == nil {
+		poll = msg.GetPollCreationMessageV3()
+	}
+
+	names := make([]string, 0, len(poll.GetOptions()))
+	for _, opt := range poll.GetOptions() {
+		names = append(names, opt.GetOptionName())
+	}
+	return names
+}
@@ -0,0 +1,32 @@
+package main
+
+import (
+	"crypto/sha256"
+	"reflect"
+	"testing"
+)
+
+func optionHash(name string) []byte {
+	sum := sha256.Sum256([]byte(name))
+	return sum[:]
+}
+
+func TestResolvePollOptionsMapsHashesToNames(t *testing.T) {
+	names := []string{"Red", "Green", "Blue"}
+	selected := [][]byte{optionHash("Blue"), optionHash("Purple"), []byte("short"), optionHash("Red")}
+
+	got := resolvePollOptions(names, selected)
+
+	want := []string{"Blue", "Red"}
+	if !reflect.DeepEqual(got, want) {
+		t.Fatalf("resolvePollOptions() = %v, want %v", got, want)
+	}
+}
+
+func TestResolvePollOptionsWithdrawnVoteIsEmpty(t *testing.T) {
+	got := resolvePollOptions([]string{"Red"}, nil)
+
+	if got == nil || len(got) != 0 {
+		t.Fatalf("resolvePollOptions() = %#v, want an empty non-nil slice", got)
+	}
+}
@@ -0,0 +1,89 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+
+	"go.mau.fi/whatsmeow/types"
+)
+
+// PrivacySettings mirrors types.PrivacySettings with stable JSON keys
+type PrivacySettings struct {
+	GroupAdd     types.PrivacySetting `json:"group_add,omitempty"`
+	LastSeen     types.PrivacySetting `json:"last_seen,omitempty"`
+	Status       types.PrivacySetting `json:"status,omitempty"`
+	Profile      types.PrivacySetting `json:"profile,omitempty"`
+	ReadReceipts types.PrivacySetting `json:"read_receipts,omitempty"`
+	Online       types.PrivacySetting `json:"online,omitempty"`
+	CallAdd      types.PrivacySetting `json:"call_add,omitempty"`
+}
+
+// errInvalidPrivacy marks errors caused by an unknown key or value
+var errInvalidPrivacy = errors.New("invalid privacy setting")
+
+var privacySettingTypes = map[string]types.PrivacySettingType{
+	string(types.PrivacySettingTypeGroupAdd):     types.PrivacySettingTypeGroupAdd,
+	string(types.PrivacySettingTypeLastSeen):     types.PrivacySettingTypeLastSeen,
+	string(types.PrivacySettingTypeStatus):       types.PrivacySettingTypeStatus,
+	string(types.PrivacySettingTypeProfile):      types.PrivacySettingTypeProfile,
+	string(types.PrivacySettingTypeReadReceipts): types.PrivacySettingTypeReadReceipts,
+	string(types.PrivacySettingTypeOnline):       types.PrivacySettingTypeOnline,
+	string(types.PrivacySettingTypeCallAdd):      types.PrivacySettingTypeCallAdd,
+}
+
+var privacySettingValues = map[string]types.PrivacySetting{
+	string(types.PrivacySettingAll):              types.PrivacySettingAll,
+	string(types.PrivacySettingContacts):         types.PrivacySettingContacts,
+	string(types.PrivacySettingContactBlacklist): types.PrivacySettingContactBlacklist,
+	string(types.PrivacySettingMatchLastSeen):    types.PrivacySettingMatchLastSeen,
+	string(types.PrivacySettingKnown):            types.PrivacySettingKnown,
+	string(types.PrivacySettingNone):             types.PrivacySettingNone,
+}
+
+func privacyFromTypes(s types.PrivacySettings) PrivacySettings {
+	return PrivacySettings{
+		GroupAdd:     s.GroupAdd,
+		LastSeen:     s.LastSeen,
+		Status:       s.Status,
+		Profile:      s.Profile,
+		ReadReceipts: s.ReadReceipts,
+		Online:       s.Online,
+		CallAdd:      s.CallAdd,
+	}
+}
+
+// PrivacySettings fetches the account's privacy settings from the server
+func (c *Client) PrivacySettings() (PrivacySettings, error) {
+	settings, err := c.client.TryFetchPrivacySettings(c.ctx, true)
+	if err != nil {
+		c.setLastError(err)
+		return PrivacySettings{}, fmt.Errorf("privacy query failed: %w", err)
+	}
+
+	return privacyFromTypes(*settings), nil
+}
+
+// SetPrivacySetting changes one privacy setting. key and value use
+// whatsmeow's wire names (e.g. "readreceipts", "none").
+func (c *Client) SetPrivacySetting(key, value string) error {
+	name, ok := privacySettingTypes[key]
+	if !ok {
+		err := fmt.Errorf("%w: unknown key %q", errInvalidPrivacy, key)
+		c.setLastError(err)
+		return err
+	}
+
+	setting, ok := privacySettingValues[value]
+	if !ok {
+		err := fmt.Errorf("%w: unknown value %q", errInvalidPrivacy, value)
+		c.setLastError(err)
+		return err
+	}
+
+	if _, err := c.client.SetPrivacySetting(c.ctx, name, setting); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("set privacy failed: %w", err)
+	}
+
+	return nil
+}
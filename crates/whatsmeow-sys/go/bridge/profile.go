@@ -0,0 +1,91 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"strings"
+	"unicode/utf8"
+
+	"go.mau.fi/whatsmeow"
+	"go.mau.fi/whatsmeow/appstate"
+	"go.mau.fi/whatsmeow/proto/waSyncAction"
+	"go.mau.fi/whatsmeow/types"
+	"google.golang.org/protobuf/proto"
+)
+
+// maxPushNameLen is the longest display name WhatsApp accepts, in characters
+const maxPushNameLen = 25
+
+// errInvalidPushName marks empty or overlong display names
+var errInvalidPushName = errors.New("invalid push name")
+
+// ProfilePicture locates a user's or group's profile picture
+type ProfilePicture struct {
+	URL  string `json:"url"`
+	ID   string `json:"id"`
+	Type string `json:"type"`
+}
+
+// ProfilePicture fetches the picture URL for a JID. A nil result means the
+// picture is unset or hidden from us by privacy settings.
+func (c *Client) ProfilePicture(jidStr string, full bool) (*ProfilePicture, error) {
+	jid, err := types.ParseJID(jidStr)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("invalid JID: %w", err)
+	}
+
+	info, err := c.client.GetProfilePictureInfo(c.ctx, jid.ToNonAD(), &whatsmeow.GetProfilePictureParams{
+		Preview: !full,
+	})
+	if errors.Is(err, whatsmeow.ErrProfilePictureUnauthorized) || errors.Is(err, whatsmeow.ErrProfilePictureNotSet) {
+		return nil, nil
+	}
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("profile picture query failed: %w", err)
+	}
+	if info == nil {
+		return nil, nil
+	}
+
+	return &ProfilePicture{URL: info.URL, ID: info.ID, Type: info.Type}, nil
+}
+
+// PushName returns the display name recipients see for this account
+func (c *Client) PushName() string {
+	return c.client.Store.PushName
+}
+
+// SetPushName changes the account's display name. The change syncs to the
+// account's other devices and is shown to contacts from the next message.
+func (c *Client) SetPushName(name string) error {
+	if strings.TrimSpace(name) == "" || utf8.RuneCountInString(name) > maxPushNameLen {
+		err := fmt.Errorf("%w: must be 1-%d characters", errInvalidPushName, maxPushNameLen)
+		c.setLastError(err)
+		return err
+	}
+
+	patch := appstate.PatchInfo{
+		Type: appstate.WAPatchCriticalBlock,
+		Mutations: []appstate.MutationInfo{{
+			Index:   []string{appstate.IndexSettingPushName},
+			Version: 1,
+			Value: &waSyncAction.SyncActionValue{
+				PushNameSetting: &waSyncAction.PushNameSetting{Name: proto.String(name)},
+			},
+		}},
+	}
+	if err := c.client.SendAppState(c.ctx, patch); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("set push name failed: %w", err)
+	}
+
+	c.client.Store.PushName = name
+	if err := c.client.Store.Save(c.ctx); err != nil {
+		c.setLastError(err)
+		return fmt.Errorf("saving push name failed: %w", err)
+	}
+
+	return nil
+}
@@ -0,0 +1,24 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"google.golang.org/protobuf/encoding/protojson"
+)
+
+// errInvalidRawMessage marks raw messages that don't decode as waE2E.Message
+var errInvalidRawMessage = errors.New("invalid raw message")
+
+// SendRaw sends a message given in the protobuf JSON mapping of
+// waE2E.Message, for message types the bridge doesn't model yet
+func (c *Client) SendRaw(jidStr string, messageJSON string) (SendResult, error) {
+	var msg waProto.Message
+	if err := protojson.Unmarshal([]byte(messageJSON), &msg); err != nil {
+		c.setLastError(err)
+		return SendResult{}, fmt.Errorf("%w: %v", errInvalidRawMessage, err)
+	}
+
+	return c.sendInteractive(jidStr, &msg)
+}
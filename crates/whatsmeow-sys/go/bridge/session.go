@@ -0,0 +1,121 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+
+	"go.mau.fi/whatsmeow/proto/waAdv"
+	"go.mau.fi/whatsmeow/types"
+	"go.mau.fi/whatsmeow/util/keys"
+	"google.golang.org/protobuf/proto"
+)
+
+// errSessionImport marks imports rejected because of the blob or client state
+var errSessionImport = errors.New("cannot import session")
+
+// SessionBackup holds the device credentials needed to resume a login
+type SessionBackup struct {
+	ID             types.JID     `json:"id"`
+	LID            types.JID     `json:"lid"`
+	RegistrationID uint32        `json:"registration_id"`
+	NoiseKey       *keys.KeyPair `json:"noise_key"`
+	IdentityKey    *keys.KeyPair `json:"identity_key"`
+	SignedPreKey   *keys.PreKey  `json:"signed_pre_key"`
+	AdvSecretKey   []byte        `json:"adv_secret_key"`
+	Account        []byte        `json:"account"`
+	Platform       string        `json:"platform"`
+	BusinessName   string        `json:"business_name"`
+	PushName       string        `json:"push_name"`
+}
+
+// ExportSession serializes the logged-in device's credentials
+func (c *Client) ExportSession() ([]byte, error) {
+	device := c.client.Store
+	if device.ID == nil {
+		err := fmt.Errorf("not logged in")
+		c.setLastError(err)
+		return nil, err
+	}
+
+	account, err := proto.Marshal(device.Account)
+	if err != nil {
+		c.setLastError(err)
+		return nil, fmt.Errorf("failed to encode account: %w", err)
+	}
+
+	return json.Marshal(SessionBackup{
+		ID:             *device.ID,
+		LID:            device.LID,
+		RegistrationID: device.RegistrationID,
+		NoiseKey:       device.NoiseKey,
+		IdentityKey:    device.IdentityKey,
+		SignedPreKey:   device.SignedPreKey,
+		AdvSecretKey:   device.AdvSecretKey,
+		Account:        account,
+		Platform:       device.Platform,
+		BusinessName:   device.BusinessName,
+		PushName:       device.PushName,
+	})
+}
+
+// ImportSession restores exported credentials into this (not yet logged in)
+// client's store. Connect afterwards to resume the session.
+func (c *Client) ImportSession(blob []byte) error {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	fail := func(err error) error {
+		c.lastError = err.Error()
+		return err
+	}
+
+	if c.connected {
+		return fail(fmt.Errorf("%w: disconnect first", errSessionImport))
+	}
+
+	device := c.client.Store
+	if device.ID != nil {
+		return fail(fmt.Errorf("%w: store already holds a session", errSessionImport))
+	}
+
+	var backup SessionBackup
+	if err := json.Unmarshal(blob, &backup); err != nil {
+		return fail(fmt.Errorf("%w: %v", errSessionImport, err))
+	}
+	if backup.ID.IsEmpty() || backup.NoiseKey == nil || backup.IdentityKey == nil || backup.SignedPreKey == nil {
+		return fail(fmt.Errorf("%w: incomplete credentials", errSessionImport))
+	}
+
+	var account waAdv.ADVSignedDeviceIdentity
+	if err := proto.Unmarshal(backup.Account, &account); err != nil {
+		return fail(fmt.Errorf("%w: invalid account: %v", errSessionImport, err))
+	}
+
+	id := backup.ID
+	device.ID = &id
+	device.LID = backup.LID
+	device.RegistrationID = backup.RegistrationID
+	device.NoiseKey = backup.NoiseKey
+	device.IdentityKey = backup.IdentityKey
+	device.SignedPreKey = backup.SignedPreKey
+	device.AdvSecretKey = backup.AdvSecretKey
+	device.Account = &account
+	device.Platform = backup.Platform
+	device.BusinessName = backup.BusinessName
+	device.PushName = backup.PushName
+
+	if err := device.Save(c.ctx); err != nil {
+		device.ID = nil
+		return fail(fmt.Errorf("failed to save session: %w", err))
+	}
+
+	return nil
+}
+
+// zeroBytes wipes a buffer that held credentials
+func zeroBytes(b []byte) {
+	for i := range b {
+		b[i] = 0
+	}
+}
@@ -0,0 +1,24 @@
+package main
+
+import (
+	waProto "go.mau.fi/whatsmeow/proto/waE2E"
+	"google.golang.org/protobuf/proto"
+)
+
+// TemplateMessage references a pre-approved business template
+type TemplateMessage struct {
+	Namespace string   `json:"namespace"`
+	Name      string   `json:"name"`
+	Params    []string `json:"params"`
+}
+
+// SendTemplate sends a highly structured (template) message
+func (c *Client) SendTemplate(jidStr string, template TemplateMessage) (SendResult, error) {
+	return c.sendInteractive(jidStr, &waProto.Message{
+		HighlyStructuredMessage: &waProto.HighlyStructuredMessage{
+			Namespace:   proto.String(template.Namespace),
+			ElementName: proto.String(template.Name),
+			Params:      template.Params,
+		},
+	})
+}
@@ -5,7 +5,7 @@
 
 #![allow(non_camel_case_types)]
 
-use libc::{c_char, c_int, c_void};
+use libc::{c_char, c_int, c_longlong, c_void};
 
 /// Opaque handle to a WhatsApp client instance
 pub type ClientHandle = *mut c_void;
@@ -23,12 +23,24 @@ pub mod error_codes {
     pub const WM_ERR_DISCONNECTED: c_int = -3;
     pub const WM_ERR_INVALID_HANDLE: c_int = -4;
     pub const WM_ERR_BUFFER_TOO_SMALL: c_int = -5;
+    pub const WM_ERR_INVALID_ARGUMENT: c_int = -6;
+    pub const WM_ERR_UNSUPPORTED: c_int = -7;
 }
 
 unsafe extern "C" {
     /// Initialize a new WhatsApp client with custom device name
     pub fn wm_client_new(db_path: *const c_char, device_name: *const c_char) -> ClientHandle;
 
+    /// Initialize a client with JSON-encoded store options (e.g. {"wal_mode": true})
+    pub fn wm_client_new_ex(
+        db_path: *const c_char,
+        device_name: *const c_char,
+        options_json: *const c_char,
+    ) -> ClientHandle;
+
+    /// Override the advertised WhatsApp Web version (process-wide, before connecting)
+    pub fn wm_set_client_version(major: c_int, minor: c_int, patch: c_int) -> WmResult;
+
     /// Connect the client to WhatsApp
     pub fn wm_client_connect(handle: ClientHandle) -> WmResult;
 
@@ -41,14 +53,26 @@ unsafe extern "C" {
     /// Poll for next event (non-blocking)
     pub fn wm_poll_event(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
 
-    /// Send a text message
+    /// Send a text message; writes the send result JSON
     pub fn wm_send_message(
         handle: ClientHandle,
         jid: *const c_char,
         text: *const c_char,
-    ) -> WmResult;
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a text message with JSON-encoded send options; writes the send result JSON
+    pub fn wm_send_message_ex(
+        handle: ClientHandle,
+        jid: *const c_char,
+        text: *const c_char,
+        options_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
 
-    /// Send an image message
+    /// Send an image message; writes the send result JSON
     pub fn wm_send_image(
         handle: ClientHandle,
         jid: *const c_char,
@@ -56,6 +80,288 @@ unsafe extern "C" {
         data_len: c_int,
         mime_type: *const c_char,
         caption: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send an image with an optional JPEG thumbnail (null/0 for none); writes the send result JSON
+    pub fn wm_send_image_ex(
+        handle: ClientHandle,
+        jid: *const c_char,
+        data: *const c_char,
+        data_len: c_int,
+        mime_type: *const c_char,
+        caption: *const c_char,
+        thumbnail: *const c_char,
+        thumbnail_len: c_int,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Download an image from an http(s) URL and send it (empty MIME type to
+    /// detect it, null/0 for no thumbnail); writes the send result JSON
+    pub fn wm_send_image_url(
+        handle: ClientHandle,
+        jid: *const c_char,
+        url: *const c_char,
+        mime_type: *const c_char,
+        caption: *const c_char,
+        thumbnail: *const c_char,
+        thumbnail_len: c_int,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send images (JSON array of {data, mime_type}) as an album; writes the message IDs
+    pub fn wm_send_album(
+        handle: ClientHandle,
+        jid: *const c_char,
+        items_json: *const c_char,
+        caption: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a WebP sticker (animated is 0 or 1)
+    pub fn wm_send_sticker(
+        handle: ClientHandle,
+        jid: *const c_char,
+        data: *const c_char,
+        data_len: c_int,
+        width: c_int,
+        height: c_int,
+        animated: c_int,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a video, audio or document (kind is "video", "audio" or "document");
+    /// file_name is used for documents only. Writes the send result JSON
+    pub fn wm_send_media(
+        handle: ClientHandle,
+        jid: *const c_char,
+        kind: *const c_char,
+        data: *const c_char,
+        data_len: c_int,
+        mime_type: *const c_char,
+        file_name: *const c_char,
+        caption: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send reply buttons (JSON {body, buttons: [{id, title}]}); writes the send result JSON
+    pub fn wm_send_buttons(
+        handle: ClientHandle,
+        jid: *const c_char,
+        buttons_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a list menu (JSON {body, button_text, sections}); writes the send result JSON
+    pub fn wm_send_list(
+        handle: ClientHandle,
+        jid: *const c_char,
+        list_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a business template (JSON {namespace, name, params}); writes the send result JSON
+    pub fn wm_send_template(
+        handle: ClientHandle,
+        jid: *const c_char,
+        template_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a payment request (JSON {amount_1000, currency, note}); writes the send result
+    /// JSON, or returns WM_ERR_UNSUPPORTED if the account can't use payments
+    pub fn wm_send_payment_request(
+        handle: ClientHandle,
+        jid: *const c_char,
+        request_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Send a message given as protobuf JSON of waE2E.Message; writes the send result JSON
+    pub fn wm_send_raw(
+        handle: ClientHandle,
+        jid: *const c_char,
+        message_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Decline an incoming call offer
+    pub fn wm_reject_call(
+        handle: ClientHandle,
+        from: *const c_char,
+        call_id: *const c_char,
+    ) -> WmResult;
+
+    /// Send read receipts (ids_json is a JSON array of message IDs)
+    pub fn wm_mark_read(
+        handle: ClientHandle,
+        chat: *const c_char,
+        sender: *const c_char,
+        ids_json: *const c_char,
+    ) -> WmResult;
+
+    /// Send "played" receipts for voice notes (ids_json is a JSON array of message IDs)
+    pub fn wm_mark_played(
+        handle: ClientHandle,
+        chat: *const c_char,
+        sender: *const c_char,
+        ids_json: *const c_char,
+    ) -> WmResult;
+
+    /// Send a chat presence ("composing"/"paused", media "" or "audio")
+    pub fn wm_send_chat_presence(
+        handle: ClientHandle,
+        jid: *const c_char,
+        state: *const c_char,
+        media: *const c_char,
+    ) -> WmResult;
+
+    /// Look up a contact in the local store; writes contact JSON
+    pub fn wm_get_contact(
+        handle: ClientHandle,
+        jid: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Map a LID to its phone number JID; writes `{"lid", "phone"}` JSON
+    pub fn wm_resolve_lid(
+        handle: ClientHandle,
+        lid: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Fetch a profile picture URL (`full` != 0 for full resolution); writes
+    /// JSON, or nothing if the picture is unset or hidden
+    pub fn wm_get_profile_picture(
+        handle: ClientHandle,
+        jid: *const c_char,
+        full: c_int,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Write the logged-in device JID as plain text, or nothing before pairing
+    pub fn wm_get_own_jid(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Estimated WhatsApp server time in unix milliseconds, or 0 until a
+    /// live message has been received
+    pub fn wm_server_time(handle: ClientHandle) -> c_longlong;
+
+    /// Write this account's display name as plain text (empty if unset)
+    pub fn wm_get_push_name(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Change this account's display name (1-25 characters)
+    pub fn wm_set_push_name(handle: ClientHandle, name: *const c_char) -> WmResult;
+
+    /// Check which phone numbers (JSON array) are registered; writes a JSON array
+    pub fn wm_is_on_whatsapp(
+        handle: ClientHandle,
+        phones_json: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Subscribe to presence updates for a contact (lasts for the current connection)
+    pub fn wm_subscribe_presence(handle: ClientHandle, jid: *const c_char) -> WmResult;
+
+    /// Query stored messages for a chat as a JSON array (before_id may be null)
+    pub fn wm_query_messages(
+        handle: ClientHandle,
+        chat: *const c_char,
+        limit: c_int,
+        before_id: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Star or unstar a stored message (starred is 0 or 1)
+    pub fn wm_star_message(
+        handle: ClientHandle,
+        chat: *const c_char,
+        message_id: *const c_char,
+        starred: c_int,
+    ) -> WmResult;
+
+    /// Get the starred stored messages in a chat as a JSON array
+    pub fn wm_get_starred(
+        handle: ClientHandle,
+        chat: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Fully re-fetch an app state collection (e.g. "critical_unblock_low", "regular_high")
+    pub fn wm_resync_app_state(handle: ClientHandle, name: *const c_char) -> WmResult;
+
+    /// Flag a chat as unread on all devices
+    pub fn wm_mark_chat_unread(handle: ClientHandle, jid: *const c_char) -> WmResult;
+
+    /// Mute (`mute` != 0) a chat for `duration_secs` (0 = until unmuted), or unmute it
+    pub fn wm_mute_chat(
+        handle: ClientHandle,
+        jid: *const c_char,
+        mute: c_int,
+        duration_secs: c_longlong,
+    ) -> WmResult;
+
+    /// Delete a chat's messages from the local history store
+    pub fn wm_clear_chat(handle: ClientHandle, jid: *const c_char) -> WmResult;
+
+    /// Export the logged-in device's credentials (returns bytes written)
+    pub fn wm_export_session(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Restore exported credentials into a client that is not logged in or connected
+    pub fn wm_import_session(
+        handle: ClientHandle,
+        data: *const c_char,
+        data_len: c_int,
+    ) -> WmResult;
+
+    /// Get per-chat unread message counts as a JSON object (chat JID -> count)
+    pub fn wm_get_unread_counts(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Get message, chat and contact counts as a JSON object
+    pub fn wm_get_store_stats(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Get linked devices as a JSON array (returns bytes written)
+    pub fn wm_get_linked_devices(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Unlink a device from the account
+    pub fn wm_remove_device(handle: ClientHandle, jid: *const c_char) -> WmResult;
+
+    /// List joined groups as a JSON array of group metadata
+    pub fn wm_get_joined_groups(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int;
+
+    /// Resolve a group invite code to group metadata JSON without joining
+    pub fn wm_get_group_info_from_link(
+        handle: ClientHandle,
+        code: *const c_char,
+        buf: *mut c_char,
+        buf_len: c_int,
+    ) -> c_int;
+
+    /// Fetch privacy settings as JSON
+    pub fn wm_get_privacy_settings(handle: ClientHandle, buf: *mut c_char, buf_len: c_int)
+    -> c_int;
+
+    /// Change a single privacy setting (e.g. key "readreceipts", value "none")
+    pub fn wm_set_privacy_setting(
+        handle: ClientHandle,
+        key: *const c_char,
+        value: *const c_char,
     ) -> WmResult;
 
     /// Get last error message
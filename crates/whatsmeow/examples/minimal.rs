@@ -0,0 +1,40 @@
+//! Echo bot using only the core API, buildable with `--no-default-features`
+
+use futures::StreamExt;
+use whatsmeow::{Event, Jid, WhatsApp};
+
+#[tokio::main]
+async fn main() -> whatsmeow::Result<()> {
+    let client = WhatsApp::connect("storage/session.db").build().await?;
+    let mut events = client.events();
+
+    let pump = client.clone();
+    tokio::spawn(async move {
+        if let Err(e) = pump.run().await {
+            eprintln!("Event loop error: {}", e);
+        }
+    });
+
+    loop {
+        tokio::select! {
+            Some(event) = events.next() => match event {
+                Event::Qr(qr) => {
+                    if let Some(code) = qr.code() {
+                        println!("Scan this code with WhatsApp: {}", code);
+                    }
+                }
+                Event::Message(msg) if !msg.info.is_from_me => {
+                    let chat = Jid::new(msg.info.chat.as_str());
+                    if let Err(e) = client.send(chat, msg.text()) {
+                        eprintln!("Reply failed: {}", e);
+                    }
+                }
+                _ => {}
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    client.disconnect();
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Image albums
+
+use serde::{Serialize, Serializer};
+
+use crate::error::{Error, Result};
+use crate::events::MediaSource;
+
+/// Fewest images WhatsApp groups into an album
+pub const MIN_ALBUM_SIZE: usize = 2;
+
+/// Most images WhatsApp accepts in one album
+pub const MAX_ALBUM_SIZE: usize = 30;
+
+/// One loaded album image, as sent to the bridge
+#[derive(Serialize)]
+pub(crate) struct AlbumItem {
+    #[serde(serialize_with = "base64")]
+    data: Vec<u8>,
+    mime_type: String,
+}
+
+/// Load every source up front so nothing is sent if any image is unusable
+pub(crate) fn load_album(images: &[MediaSource]) -> Result<Vec<AlbumItem>> {
+    if !(MIN_ALBUM_SIZE..=MAX_ALBUM_SIZE).contains(&images.len()) {
+        return Err(Error::Validation(format!(
+            "Album must have {} to {} images, got {}",
+            MIN_ALBUM_SIZE,
+            MAX_ALBUM_SIZE,
+            images.len()
+        )));
+    }
+
+    images
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            let data = source
+                .load()
+                .map_err(|e| Error::Send(format!("Failed to load album image {}: {}", i, e)))?;
+            let mime_type = MediaSource::detect_mime_from_signature(&data);
+            if !mime_type.starts_with("image/") {
+                return Err(Error::Validation(format!(
+                    "Album item {} is not an image ({})",
+                    i, mime_type
+                )));
+            }
+            Ok(AlbumItem { data, mime_type })
+        })
+        .collect()
+}
+
+fn base64<S: Serializer>(data: &[u8], s: S) -> std::result::Result<S::Ok, S::Error> {
+    use base64::Engine;
+    s.serialize_str(&base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn images(count: usize) -> Vec<MediaSource> {
+        (0..count)
+            .map(|_| MediaSource::bytes(PNG.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn albums_outside_the_size_limits_are_rejected() {
+        for count in [0, 1, MAX_ALBUM_SIZE + 1] {
+            assert!(
+                matches!(load_album(&images(count)), Err(Error::Validation(_))),
+                "{count} images accepted"
+            );
+        }
+        assert_eq!(load_album(&images(MIN_ALBUM_SIZE)).unwrap().len(), 2);
+        assert_eq!(load_album(&images(MAX_ALBUM_SIZE)).unwrap().len(), 30);
+    }
+
+    #[test]
+    fn one_unloadable_image_fails_the_whole_album() {
+        let mut sources = images(3);
+        sources[1] = MediaSource::file("/nonexistent/album-image.png");
+
+        let err = load_album(&sources).err().unwrap();
+
+        assert!(err.to_string().contains("album image 1"), "{err}");
+    }
+
+    #[test]
+    fn non_images_are_rejected() {
+        let mut sources = images(2);
+        sources[1] = MediaSource::bytes(b"plain text".to_vec());
+
+        assert!(matches!(load_album(&sources), Err(Error::Validation(_))));
+    }
+}
@@ -3,6 +3,17 @@
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use serde::Serialize;
+
+/// Point-in-time copy of [`TrackedAllocator`] counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryStats {
+    pub current_bytes: usize,
+    pub peak_bytes: usize,
+    pub outstanding_allocations: usize,
+    pub total_bytes_allocated: usize,
+}
+
 /// Custom allocator that tracks memory allocations for FFI operations.
 #[derive(Default)]
 pub struct TrackedAllocator {
@@ -65,6 +76,16 @@ impl TrackedAllocator {
             .saturating_sub(self.deallocation_count.load(Ordering::Relaxed))
     }
 
+    /// Snapshot of all counters
+    pub fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            current_bytes: self.current_bytes(),
+            peak_bytes: self.peak_bytes(),
+            outstanding_allocations: self.outstanding_allocations(),
+            total_bytes_allocated: self.total_bytes_allocated(),
+        }
+    }
+
     /// Print memory statistics
     pub fn print_stats(&self) {
         println!("📊 Memory Statistics:");
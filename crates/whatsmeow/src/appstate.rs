@@ -0,0 +1,112 @@
+//! App state (contacts, chat settings) sync collections
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Jid;
+use crate::timestamp::parse_rfc3339;
+
+/// Named app state collection synced between linked devices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppStateCollection {
+    /// Push name and block list
+    CriticalBlock,
+    /// Contact names
+    CriticalUnblockLow,
+    /// Miscellaneous account settings
+    Regular,
+    /// Pinned and starred state, labels
+    RegularLow,
+    /// Mute, archive, read state and deletions
+    RegularHigh,
+}
+
+impl AppStateCollection {
+    /// Collections holding contact names and push names
+    pub const CONTACTS: [Self; 2] = [Self::CriticalBlock, Self::CriticalUnblockLow];
+
+    /// Collections holding per-chat settings
+    pub const CHATS: [Self; 3] = [Self::Regular, Self::RegularLow, Self::RegularHigh];
+
+    /// Wire name used by the bridge
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CriticalBlock => "critical_block",
+            Self::CriticalUnblockLow => "critical_unblock_low",
+            Self::Regular => "regular",
+            Self::RegularLow => "regular_low",
+            Self::RegularHigh => "regular_high",
+        }
+    }
+}
+
+/// An app state collection finished a full sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppStateSyncedEvent {
+    #[serde(rename = "Name")]
+    pub collection: AppStateCollection,
+}
+
+/// A chat was deleted on another of this account's devices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatDeletedEvent {
+    #[serde(rename = "JID")]
+    pub chat: Jid,
+    /// When the chat was deleted (RFC 3339)
+    #[serde(rename = "Timestamp", default)]
+    pub timestamp: String,
+    /// Replayed by a full app state sync rather than deleted just now
+    #[serde(rename = "FromFullSync", default)]
+    pub from_full_sync: bool,
+}
+
+impl ChatDeletedEvent {
+    /// Parse the deletion time
+    pub fn timestamp_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi;
+
+    #[test]
+    fn wire_names_match_serde() {
+        for collection in AppStateCollection::CONTACTS
+            .into_iter()
+            .chain(AppStateCollection::CHATS)
+        {
+            assert_eq!(
+                serde_json::to_value(collection).unwrap(),
+                collection.as_str()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn resyncs_name_their_collections() {
+        let (client, mock) = mock_ffi::client("appstate-resync").await;
+
+        client.resync_contacts().unwrap();
+        client.resync_chats().unwrap();
+        client
+            .resync_app_state(AppStateCollection::RegularHigh)
+            .unwrap();
+
+        assert_eq!(
+            mock.calls("wm_resync_app_state"),
+            [
+                ["critical_block"],
+                ["critical_unblock_low"],
+                ["regular"],
+                ["regular_low"],
+                ["regular_high"],
+                ["regular_high"],
+            ]
+        );
+    }
+}
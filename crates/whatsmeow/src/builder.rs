@@ -3,18 +3,33 @@
 use std::future::Future;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::appstate::ChatDeletedEvent;
 use crate::client::WhatsApp;
-use crate::error::Result;
-use crate::events::{MessageEvent, QrEvent};
-use crate::ffi::FfiClient;
+use crate::config::{ClientConfig, StoreOptions};
+use crate::dispatch::EventOrder;
+use crate::error::{Error, Result};
+use crate::events::{
+    EphemeralSettingEvent, Event, Jid, MessageEditedEvent, MessageEvent, MessageType, QrEvent,
+};
+use crate::ffi::{self, FfiClient};
+use crate::handlers::Handlers;
 use crate::inner::InnerClient;
+use crate::interactive::InteractiveResponseEvent;
+use crate::session::SessionBlob;
 
 /// Builder for configuring a WhatsApp client
 pub struct WhatsAppBuilder {
     db_path: String,
     device_name: String,
-    inner: Option<Arc<InnerClient>>,
+    client_version: Option<(u32, u32, u32)>,
+    record_ffi_latencies: bool,
+    build_timeout: Option<Duration>,
+    session: Option<SessionBlob>,
+    config: ClientConfig,
+    store: StoreOptions,
+    handlers: Handlers,
 }
 
 impl WhatsAppBuilder {
@@ -22,7 +37,13 @@ impl WhatsAppBuilder {
         Self {
             db_path: db_path.as_ref().to_string_lossy().into_owned(),
             device_name: "WhatsApp-RS".to_string(),
-            inner: None,
+            client_version: None,
+            record_ffi_latencies: false,
+            build_timeout: None,
+            session: None,
+            config: ClientConfig::default(),
+            store: StoreOptions::default(),
+            handlers: Handlers::new(),
         }
     }
 
@@ -32,66 +53,482 @@ impl WhatsAppBuilder {
         self
     }
 
-    fn ensure_inner(&mut self) -> Result<&Arc<InnerClient>> {
-        if self.inner.is_none() {
-            let ffi = FfiClient::new(&self.db_path, &self.device_name)?;
-            self.inner = Some(Arc::new(InnerClient::new(ffi)));
-        }
-        Ok(self.inner.as_ref().unwrap())
+    /// Override the WhatsApp Web version advertised to the server
+    ///
+    /// Defaults to the version baked into the bridge. WhatsApp rejects
+    /// clients that fall too far behind, so this lets you bump it without
+    /// rebuilding. The setting is process-wide.
+    pub fn client_version(mut self, major: u32, minor: u32, patch: u32) -> Self {
+        self.client_version = Some((major, minor, patch));
+        self
+    }
+
+    /// Aggregate how long each bridge call takes, for
+    /// [`WhatsApp::ffi_latencies`]
+    ///
+    /// Off by default since every call, event polls included, then takes a
+    /// lock to record its time. Only this client's calls are recorded.
+    pub fn record_ffi_latencies(mut self, enabled: bool) -> Self {
+        self.record_ffi_latencies = enabled;
+        self
+    }
+
+    /// Fail [`build`](Self::build) with [`Error::Init`] if setup and connect
+    /// take longer than `timeout`
+    ///
+    /// A timed-out attempt keeps running in the background and its client is
+    /// discarded (and disconnected) once it finishes.
+    pub fn build_timeout(mut self, timeout: Duration) -> Self {
+        self.build_timeout = Some(timeout);
+        self
+    }
+
+    /// Open the session database in write-ahead logging (WAL) mode
+    ///
+    /// WAL lets the event loop and history writes proceed while other
+    /// queries read, which helps busy accounts. The trade-offs: sqlite keeps
+    /// `-wal` and `-shm` files next to the database (copy all three when
+    /// backing up), the database cannot live on a network filesystem, and
+    /// the last few commits may be lost on power failure since the bridge
+    /// pairs WAL with `synchronous=NORMAL`. A process crash loses nothing.
+    /// Defaults to sqlite's rollback journal.
+    pub fn wal_mode(mut self, enabled: bool) -> Self {
+        self.store.wal_mode = enabled;
+        self
+    }
+
+    /// Create the database's parent directory if it is missing (default `true`)
+    ///
+    /// Turn off where a missing directory means a misconfigured path or a
+    /// read-only deployment; [`build`](Self::build) then fails with
+    /// [`Error::Init`] instead of creating it.
+    pub fn create_db_dir(mut self, create: bool) -> Self {
+        self.store.create_db_dir = create;
+        self
+    }
+
+    /// Log in with credentials from [`WhatsApp::export_session`] instead of pairing
+    ///
+    /// Only applies to a fresh session database; [`build`](Self::build)
+    /// fails with [`Error::Validation`] if the database already holds a login.
+    pub fn restore_session(mut self, blob: SessionBlob) -> Self {
+        self.session = Some(blob);
+        self
+    }
+
+    /// Automatically mark incoming messages as read before dispatching them
+    ///
+    /// Messages sent by this account are never marked.
+    pub fn auto_read(mut self, enabled: bool) -> Self {
+        self.config.auto_read = enabled;
+        self
+    }
+
+    /// Only auto-mark messages matching a predicate (implies `auto_read(true)`)
+    pub fn auto_read_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&MessageEvent) -> bool + Send + Sync + 'static,
+    {
+        self.config.auto_read = true;
+        self.config.auto_read_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Drop events for which `filter` returns false
+    ///
+    /// Filtered events never reach handlers or [`EventStream`](crate::EventStream)s,
+    /// which relieves the broadcast channel of high-volume types such as
+    /// presence or receipts. Internal bookkeeping (contact names, auto-read,
+    /// presence re-subscription) still sees every event.
+    pub fn event_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Event) -> bool + Send + Sync + 'static,
+    {
+        self.config.event_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Add a middleware that sees every event before handlers and streams
+    ///
+    /// It may pass the event through, rewrite it (e.g. map LID senders to
+    /// phone JIDs, or fill in contact names), or return `None` to drop it.
+    /// Middlewares run in the order they were added, each receiving the
+    /// previous one's output. They run after [`event_filter`](Self::event_filter),
+    /// so filtered events never reach them, and before
+    /// [`coalesce_presence`](Self::coalesce_presence) and [`WhatsApp::pause`],
+    /// so they see every presence update and run even while paused. Like the
+    /// filter, it does not affect internal bookkeeping, and it delays every
+    /// later event while it runs.
+    pub fn before_dispatch<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Event>> + Send + 'static,
+    {
+        self.config
+            .before_dispatch
+            .push(Arc::new(move |event| Box::pin(f(event))));
+        self
+    }
+
+    /// Deliver at most one presence update per contact per `window`
+    ///
+    /// Presence flaps during active chats; with this set, the first update
+    /// from a contact opens a window, later ones replace it, and only the
+    /// latest state is dispatched when the window closes. Updates are
+    /// therefore delayed by up to `window` and may arrive after events that
+    /// followed them. Applies after [`before_dispatch`](Self::before_dispatch).
+    pub fn coalesce_presence(mut self, window: Duration) -> Self {
+        self.config.coalesce_presence = Some(window);
+        self
+    }
+
+    /// Whether messages sent by this account reach handlers and streams
+    /// (default `true`)
+    ///
+    /// These are messages typed on the phone or another linked device. Pass
+    /// `false` so `on_message` bots need no `is_from_me` guard against
+    /// answering themselves. Stays on by default because some bots, like the
+    /// stream example's `!echo`, react to commands typed on the account
+    /// itself. Contact names and auto-read still see every message.
+    pub fn dispatch_own_messages(mut self, dispatch: bool) -> Self {
+        self.config.skip_own_messages = !dispatch;
+        self
+    }
+
+    /// Add a middleware that sees every outgoing message before it is sent
+    ///
+    /// It may return the recipient and message unchanged, rewrite either
+    /// (e.g. append a signature), or return `None` to block the send, which
+    /// then fails with [`Error::SendBlocked`]. Middlewares run in the order
+    /// they were added, each receiving the previous one's output. Applies to
+    /// [`send`](WhatsApp::send), [`send_with`](WhatsApp::send_with),
+    /// [`send_async`](WhatsApp::send_async) and
+    /// [`send_batch`](WhatsApp::send_batch), where it runs per recipient.
+    ///
+    /// Async sends await it. The blocking [`send`](WhatsApp::send),
+    /// [`send_with`](WhatsApp::send_with) and
+    /// [`send_album`](WhatsApp::send_album) drive it to completion on the
+    /// calling thread instead, so a middleware awaiting Tokio timers or I/O
+    /// hangs them on a current-thread runtime, where the blocked thread is
+    /// the one that would drive those; use [`send_async`](WhatsApp::send_async)
+    /// there.
+    pub fn before_send<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(Jid, MessageType) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<(Jid, MessageType)>> + Send + 'static,
+    {
+        self.config
+            .before_send
+            .push(Arc::new(move |jid, msg| Box::pin(f(jid, msg))));
+        self
+    }
+
+    /// Run event handlers on a fixed pool of `n` workers
+    ///
+    /// By default every handler call gets its own task. With a pool, at most
+    /// `n` handlers run at once and the event loop waits when all workers
+    /// are busy and the queue is full, bounding concurrency and memory.
+    pub fn dispatch_workers(mut self, n: usize) -> Self {
+        self.config.dispatch_workers = Some(n);
+        self
+    }
+
+    /// Choose whether handlers or event streams are given each event first
+    ///
+    /// By default handlers are scheduled before the event is published to
+    /// streams. Both sides see events in arrival order either way.
+    pub fn event_order(mut self, order: EventOrder) -> Self {
+        self.config.event_order = order;
+        self
+    }
+
+    /// Country code used by [`WhatsApp::parse_phone`] for numbers written
+    /// without `+` or `00`, e.g. `"20"` for Egypt
+    pub fn default_country_code(mut self, code: impl Into<String>) -> Self {
+        self.config.default_country_code = Some(code.into().trim_start_matches('+').to_string());
+        self
+    }
+
+    /// Work on at most `n` recipients at once in
+    /// [`WhatsApp::send_batch`] (default 8)
+    ///
+    /// Bridge calls still go out one at a time, as every call shares the
+    /// client's handle. Concurrency overlaps the per-recipient work around
+    /// them (middleware, preparation, waiting in the send queue) so the
+    /// handle never idles between recipients.
+    pub fn batch_concurrency(mut self, n: usize) -> Self {
+        self.config.batch_concurrency = Some(n.max(1));
+        self
+    }
+
+    /// Decline every incoming voice and video call
+    ///
+    /// Callers passed to [`allow_calls_from`](Self::allow_calls_from) still
+    /// ring through. [`Event::CallOffer`] is delivered either way.
+    pub fn auto_reject_calls(mut self, enabled: bool) -> Self {
+        self.config.auto_reject_calls = enabled;
+        self
+    }
+
+    /// Exempt these contacts from [`auto_reject_calls`](Self::auto_reject_calls)
+    pub fn allow_calls_from<I>(mut self, jids: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Jid>,
+    {
+        self.config
+            .call_allowlist
+            .extend(jids.into_iter().map(|jid| jid.into().without_device()));
+        self
+    }
+
+    /// Remember delivered message IDs in `path` across restarts
+    ///
+    /// Messages WhatsApp redelivers are always dropped before reaching
+    /// handlers, but by default the record of what was delivered starts
+    /// empty on each run, so redeliveries right after a restart get through.
+    /// The file holds the most recent few thousand message keys and is
+    /// rewritten periodically and on disconnect.
+    pub fn persist_dedup(mut self, path: impl AsRef<Path>) -> Self {
+        self.config.dedup_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Check that user recipients are on WhatsApp before every send
+    ///
+    /// Sends to unregistered numbers then fail with
+    /// [`Error::RecipientNotOnWhatsApp`](crate::Error::RecipientNotOnWhatsApp)
+    /// instead of a generic error. Costs one extra server query per send,
+    /// except to recipients found registered within the last hour.
+    pub fn verify_recipients(mut self, enabled: bool) -> Self {
+        self.config.verify_recipients = enabled;
+        self
+    }
+
+    /// Subscribe to presence updates for these contacts once connected
+    ///
+    /// Subscriptions are re-issued after every reconnect.
+    pub fn subscribe_presence<I>(mut self, jids: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Jid>,
+    {
+        self.config
+            .presence_subscriptions
+            .extend(jids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Randomize each reconnect delay by up to `±fraction` (default `0.2`)
+    ///
+    /// After an unexpected disconnect the client retries with exponential
+    /// backoff. Jitter keeps many clients (e.g. under a
+    /// [`WhatsAppManager`](crate::WhatsAppManager)) that dropped at the same
+    /// moment from hammering the server in lockstep. `fraction` is clamped
+    /// to `0.0..=1.0`; `0.0` disables jitter.
+    pub fn reconnect_jitter(mut self, fraction: f64) -> Self {
+        self.config.reconnect.jitter = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Start reconnect backoff at `base` instead of seconds, so tests needn't wait
+    #[cfg(test)]
+    pub(crate) fn reconnect_base(mut self, base: Duration) -> Self {
+        self.config.reconnect.base = base;
+        self
+    }
+
+    /// Keep the bridge's JSON for each event, available through
+    /// [`WhatsApp::events_with_raw`]
+    ///
+    /// Costs a second parse of every event; leave off unless debugging how
+    /// fields are mapped.
+    pub fn retain_raw_json(mut self, retain: bool) -> Self {
+        self.config.retain_raw_json = retain;
+        self
+    }
+
+    /// Force at least `interval` between event loop iterations
+    ///
+    /// Debugging aid for reproducing ordering and timing issues: events are
+    /// polled one at a time at this pace even when more are queued. Off by
+    /// default; never enable in production.
+    #[doc(hidden)]
+    pub fn poll_throttle(mut self, interval: Duration) -> Self {
+        self.config.poll_throttle = Some(interval);
+        self
     }
 
     /// Register an async QR code handler
-    pub fn on_qr<F, Fut>(mut self, f: F) -> Self
+    pub fn on_qr<F, Fut>(self, f: F) -> Self
     where
         F: Fn(QrEvent) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = ()> + Send + 'static,
     {
-        if let Ok(inner) = self.ensure_inner() {
-            inner.handlers.register_qr(f);
-        }
+        self.handlers.register_qr(f);
         self
     }
 
     /// Register an async message handler
-    pub fn on_message<F, Fut>(mut self, f: F) -> Self
+    ///
+    /// Edits go to [`on_message_edited`](Self::on_message_edited) instead,
+    /// and system notices only arrive as [`Event::System`].
+    pub fn on_message<F, Fut>(self, f: F) -> Self
     where
         F: Fn(MessageEvent) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = ()> + Send + 'static,
     {
-        if let Ok(inner) = self.ensure_inner() {
-            inner.handlers.register_message(f);
-        }
+        self.handlers.register_message(f);
+        self
+    }
+
+    /// Register an async message handler for direct (one-to-one) chats only
+    ///
+    /// Group messages and status/broadcast updates are skipped.
+    pub fn on_message_dm<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(MessageEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_message_where(
+            |msg| {
+                let chat = Jid::new(msg.info.chat.as_str());
+                chat.is_user() || chat.is_lid()
+            },
+            f,
+        );
+        self
+    }
+
+    /// Register an async message handler for group chats only
+    pub fn on_message_group<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(MessageEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers
+            .register_message_where(MessageEvent::is_group, f);
+        self
+    }
+
+    /// Register an async handler for edits of previously sent messages
+    ///
+    /// Edits are delivered here instead of to [`on_message`](Self::on_message).
+    pub fn on_message_edited<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(MessageEditedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_message_edited(f);
+        self
+    }
+
+    /// Register an async handler for disappearing-messages setting changes
+    pub fn on_ephemeral_setting_changed<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(EphemeralSettingEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_ephemeral_setting(f);
+        self
+    }
+
+    /// Register an async handler for taps on reply buttons and picks from list menus
+    ///
+    /// Responses are delivered here instead of to [`on_message`](Self::on_message).
+    pub fn on_interactive_response<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(InteractiveResponseEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_interactive_response(f);
+        self
+    }
+
+    /// Register an async handler for chats deleted on another device
+    ///
+    /// Use it to drop per-chat state kept by the bot. Deletions replayed by a
+    /// full app state sync arrive too, flagged with `from_full_sync`.
+    pub fn on_chat_deleted<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(ChatDeletedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_chat_deleted(f);
         self
     }
 
     /// Register an async connected handler
-    pub fn on_connected<F, Fut>(mut self, f: F) -> Self
+    pub fn on_connected<F, Fut>(self, f: F) -> Self
     where
         F: Fn(()) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = ()> + Send + 'static,
     {
-        if let Ok(inner) = self.ensure_inner() {
-            inner.handlers.register_connected(f);
-        }
+        self.handlers.register_connected(f);
+        self
+    }
+
+    /// Register an async handler run after each successful reconnection
+    ///
+    /// Unlike [`on_connected`](Self::on_connected) it skips the initial
+    /// connect, so it is the place to restore per-connection state such as
+    /// presence or status that a dropped connection lost.
+    pub fn on_reconnected<F, Fut>(self, f: F) -> Self
+    where
+        F: Fn(()) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.register_reconnected(f);
         self
     }
 
     /// Register an async disconnected handler
-    pub fn on_disconnected<F, Fut>(mut self, f: F) -> Self
+    pub fn on_disconnected<F, Fut>(self, f: F) -> Self
     where
         F: Fn(()) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = ()> + Send + 'static,
     {
-        if let Ok(inner) = self.ensure_inner() {
-            inner.handlers.register_disconnected(f);
-        }
+        self.handlers.register_disconnected(f);
         self
     }
 
     /// Build the client without starting event loop
-    pub async fn build(mut self) -> Result<WhatsApp> {
-        let inner = self.ensure_inner()?.clone();
-        inner.connect().await?;
+    pub async fn build(self) -> Result<WhatsApp> {
+        let limit = self.build_timeout;
+
+        // Store setup and connect block inside the FFI, so run them off the
+        // async runtime where a timeout can abandon them
+        let task = tokio::task::spawn_blocking(move || self.build_blocking());
+
+        let joined = match limit {
+            Some(limit) => tokio::time::timeout(limit, task)
+                .await
+                .map_err(|_| Error::Init("build timed out".into()))?,
+            None => task.await,
+        };
+
+        joined.map_err(|e| Error::Init(format!("build task failed: {}", e)))?
+    }
+
+    fn build_blocking(mut self) -> Result<WhatsApp> {
+        if let Some((major, minor, patch)) = self.client_version {
+            ffi::set_client_version(major, minor, patch)?;
+        }
+
+        let ffi = FfiClient::new(&self.db_path, &self.device_name, &self.store)?;
+        let inner = Arc::new(InnerClient::new(ffi, self.handlers));
+        if self.record_ffi_latencies {
+            inner.ffi.record_latencies();
+        }
+        if let Some(blob) = self.session.take() {
+            inner.import_session(&blob)?;
+        }
+        inner.configure(self.config);
+        inner.connect()?;
         Ok(WhatsApp::from_inner(inner))
     }
 
@@ -101,3 +538,123 @@ impl WhatsAppBuilder {
         client.run().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi;
+
+    #[tokio::test]
+    async fn the_client_version_reaches_the_bridge() {
+        WhatsApp::connect(mock_ffi::db_path("client-version"))
+            .client_version(2, 3000, 1015901307)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(mock_ffi::client_version(), Some((2, 3000, 1015901307)));
+    }
+
+    #[tokio::test]
+    async fn a_slow_connect_times_the_build_out() {
+        let path = mock_ffi::db_path("build-timeout");
+        mock_ffi::before_open(&path, |mock| {
+            mock.delay("wm_client_connect", Duration::from_millis(500));
+        });
+        let started = std::time::Instant::now();
+
+        let result = WhatsApp::connect(path)
+            .build_timeout(Duration::from_millis(50))
+            .build()
+            .await;
+
+        assert!(matches!(result, Err(Error::Init(ref msg)) if msg == "build timed out"));
+        assert!(started.elapsed() < Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn the_wal_option_reaches_the_bridge_constructor() {
+        let wal = mock_ffi::db_path("wal-on");
+        let journal = mock_ffi::db_path("wal-off");
+
+        WhatsApp::connect(&wal)
+            .wal_mode(true)
+            .build()
+            .await
+            .unwrap();
+        WhatsApp::connect(&journal).build().await.unwrap();
+
+        assert_eq!(
+            mock_ffi::opened(&wal).unwrap().options,
+            serde_json::json!({ "wal_mode": true })
+        );
+        assert_eq!(
+            mock_ffi::opened(&journal).unwrap().options,
+            serde_json::json!({ "wal_mode": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn dm_and_group_handlers_only_see_their_own_chats() {
+        let dms = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let groups = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let (dm_seen, group_seen) = (dms.clone(), groups.clone());
+
+        let client = WhatsApp::connect(mock_ffi::db_path("chat-kind-handlers"))
+            .on_message_dm(move |msg| {
+                dm_seen.lock().push(msg.info.id.to_string());
+                async {}
+            })
+            .on_message_group(move |msg| {
+                group_seen.lock().push(msg.info.id.to_string());
+                async {}
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = mock_ffi::Mock::of(&client);
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("DM", "1@s.whatsapp.net", "1@s.whatsapp.net", "hi"),
+        );
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("GROUP", "123-456@g.us", "1@s.whatsapp.net", "hi all"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while dms.lock().is_empty() || groups.lock().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("handlers not called");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(*dms.lock(), ["DM"]);
+        assert_eq!(*groups.lock(), ["GROUP"]);
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn a_missing_db_dir_is_created_only_when_allowed() {
+        let dir = mock_ffi::db_path("db-dir").with_extension("");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let refused = WhatsApp::connect(dir.join("refused/session.db"))
+            .create_db_dir(false)
+            .build()
+            .await;
+        assert!(matches!(refused, Err(Error::Init(ref msg)) if msg.contains("does not exist")));
+        assert!(!dir.join("refused").exists());
+
+        WhatsApp::connect(dir.join("created/session.db"))
+            .build()
+            .await
+            .unwrap();
+        assert!(dir.join("created").is_dir());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,96 @@
+//! Voice and video calls
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timestamp::parse_rfc3339;
+
+/// Someone is calling this account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallOfferEvent {
+    /// Device the call comes from
+    #[serde(rename = "From")]
+    pub from: String,
+    /// Pass to [`WhatsApp::reject_call`](crate::WhatsApp::reject_call)
+    #[serde(rename = "CallID")]
+    pub call_id: String,
+    /// User who started the call (differs from `from` for group calls)
+    #[serde(rename = "CallCreator", default)]
+    pub call_creator: String,
+    #[serde(rename = "Timestamp", default)]
+    pub timestamp: String,
+    #[serde(rename = "RemotePlatform", default)]
+    pub remote_platform: String,
+}
+
+impl CallOfferEvent {
+    /// When the call was offered, if the timestamp parses
+    pub fn timestamp_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::{Value, json};
+
+    use crate::mock_ffi::{self, Mock};
+    use crate::{Event, WhatsApp};
+
+    fn offer(from: &str, call_id: &str) -> Value {
+        json!({
+            "From": format!("{from}:1@s.whatsapp.net"),
+            "CallID": call_id,
+            "CallCreator": format!("{from}@s.whatsapp.net"),
+            "Timestamp": "2024-01-01T00:00:00Z",
+        })
+    }
+
+    async fn offers_seen(client: &WhatsApp) -> Mock {
+        let mock = Mock::of(client);
+        let mut events = client.events();
+        mock.push_event("call_offer", offer("15550001111", "C1"));
+        mock.push_event("call_offer", offer("15550002222", "C2"));
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+        for _ in 0..2 {
+            assert!(matches!(
+                mock_ffi::next_event(&mut events).await,
+                Event::CallOffer(_)
+            ));
+        }
+        mock
+    }
+
+    #[tokio::test]
+    async fn offers_are_rejected_unless_the_caller_is_allowed() {
+        let client = WhatsApp::connect(mock_ffi::db_path("auto-reject-calls"))
+            .auto_reject_calls(true)
+            .allow_calls_from(["15550002222@s.whatsapp.net"])
+            .build()
+            .await
+            .unwrap();
+
+        let mock = offers_seen(&client).await;
+
+        let rejected = mock.wait_for_calls("wm_reject_call", 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(rejected, [vec!["15550001111:1@s.whatsapp.net", "C1"]]);
+        assert_eq!(mock.calls("wm_reject_call").len(), 1);
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn offers_ring_through_by_default() {
+        let (client, _) = mock_ffi::client("calls-ring-through").await;
+
+        let mock = offers_seen(&client).await;
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(mock.calls("wm_reject_call").is_empty());
+        client.disconnect();
+    }
+}
@@ -1,18 +1,63 @@
 //! Public WhatsApp client interface
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use futures::StreamExt;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+use crate::album::load_album;
+use crate::appstate::AppStateCollection;
 use crate::builder::WhatsAppBuilder;
-use crate::error::Result;
-use crate::events::{Jid, MessageType};
+use crate::calls::CallOfferEvent;
+use crate::clock::ClockOffset;
+use crate::contact_book::ContactBook;
+use crate::contacts::ContactInfo;
+use crate::devices::LinkedDevice;
+use crate::diagnostics::Diagnostics;
+use crate::embedded;
+use crate::error::{Error, Result};
+use crate::events::{Event, Jid, MediaSource, MessageId, MessageType, PresenceEvent};
+use crate::ffi;
+use crate::groups::{GroupMetadata, invite_code};
+use crate::handlers::HandlerStats;
+use crate::history::StoredMessage;
 use crate::inner::InnerClient;
-use crate::stream::EventStream;
+use crate::latency::LatencyStats;
+use crate::lid::LidMapping;
+use crate::metrics::Metrics;
+use crate::options::SendOptions;
+use crate::outgoing::Outgoing;
+use crate::phone::PhoneNumber;
+use crate::privacy::{PrivacyKey, PrivacySettings, PrivacyValue};
+use crate::profile::{PictureQuality, ProfilePicture, validate_push_name};
+use crate::sent::SentMessage;
+use crate::session::SessionBlob;
+use crate::store_stats::{self, StoreStats};
+use crate::stream::{EnvelopeStream, EventStream};
+use crate::typing::{ChatPresence, TypingGuard};
+
+/// Pause between app state changes in bulk operations like
+/// [`WhatsApp::mute_all_groups`]
+const BULK_CHANGE_SPACING: Duration = Duration::from_millis(250);
+
+/// How long [`WhatsApp::query_presence`] waits for an answer
+pub const PRESENCE_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// WhatsApp client for sending and receiving messages
-#[derive(Clone)]
+///
+/// Clones share the same connection. Dropping the last clone stops the
+/// event loop and disconnects, so an explicit [`WhatsApp::disconnect`] is optional.
+/// A clone busy in [`run`](Self::run) doesn't count, so dropping every
+/// other clone also stops a loop spawned on its own clone. Clones captured
+/// by handlers or kept in long-lived tasks do count.
 pub struct WhatsApp {
     pub(crate) inner: Arc<InnerClient>,
+    /// Whether this value keeps the client open (internal copies don't)
+    counted: bool,
 }
 
 impl WhatsApp {
@@ -22,7 +67,19 @@ impl WhatsApp {
     }
 
     pub(crate) fn from_inner(inner: Arc<InnerClient>) -> Self {
-        Self { inner }
+        inner.acquire_handle();
+        Self {
+            inner,
+            counted: true,
+        }
+    }
+
+    /// Copy for the crate's own tasks, which shouldn't keep the client open
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            counted: false,
+        }
     }
 
     /// Get an async stream of events
@@ -30,16 +87,36 @@ impl WhatsApp {
         self.inner.events()
     }
 
+    /// Get an async stream of events paired with the bridge's JSON for each
+    ///
+    /// A debugging aid for field-mapping issues. Only fed when the client is
+    /// built with [`retain_raw_json`](crate::WhatsAppBuilder::retain_raw_json);
+    /// otherwise the stream never yields.
+    pub fn events_with_raw(&self) -> EnvelopeStream {
+        self.inner.events_with_raw()
+    }
+
     /// Run the client event loop
     pub async fn run(&self) -> Result<()> {
-        self.inner.run().await
+        self.run_with_cancel(CancellationToken::new()).await
+    }
+
+    /// Run the event loop until `token` is cancelled
+    ///
+    /// Cancelling only stops the loop; the connection stays open, so `run`
+    /// can be called again later. Use [`WhatsApp::disconnect`] to close it.
+    pub async fn run_with_cancel(&self, token: CancellationToken) -> Result<()> {
+        let _running = self.counted.then(|| RunningHandle::new(&self.inner));
+        self.inner.run(token).await
     }
 
     /// Send a message to a JID
     ///
+    /// Returns the message ID and server timestamp.
+    ///
     /// # Examples
     /// ```rust,no_run
-    /// use whatsmeow::{Jid, MessageType};
+    /// use whatsmeow::{Jid, MessageType, Recipient};
     ///
     /// // Send with string (auto-converted)
     /// client.send("1234567890@s.whatsapp.net", "Hello!")?;
@@ -50,6 +127,9 @@ impl WhatsApp {
     /// // Send to a group
     /// client.send(Jid::group("123456789"), MessageType::Text("Hello group!".into()))?;
     ///
+    /// // Or name the kind of recipient
+    /// client.send(Recipient::Group("123456789".into()), "Hello group!")?;
+    ///
     /// // Send an image from file path
     /// client.send(Jid::user("1234567890"), MessageType::image(MediaSource::file("photo.jpg"), "image/jpeg"))?;
     ///
@@ -57,40 +137,262 @@ impl WhatsApp {
     /// let data = std::fs::read("photo.jpg")?;
     /// client.send(Jid::user("1234567890"), MessageType::image(data, "image/jpeg"))?;
     /// ```
-    pub fn send(&self, to: impl Into<Jid>, message: impl Into<MessageType>) -> Result<()> {
-        let jid: Jid = to.into();
+    pub fn send(&self, to: impl Into<Jid>, message: impl Into<MessageType>) -> Result<SentMessage> {
+        self.send_with(to, message, SendOptions::default())
+    }
+
+    /// Send a file, choosing image, video, audio or document from its contents
+    ///
+    /// The type is detected from the file signature rather than the
+    /// extension; unrecognised files are sent as a document carrying the
+    /// file's name. See [`MessageType::file`].
+    pub fn send_file(&self, to: impl Into<Jid>, path: impl AsRef<Path>) -> Result<SentMessage> {
+        self.send(to, MessageType::file(path)?)
+    }
+
+    /// Send a message with extra options (e.g. a link preview)
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use whatsmeow::{Jid, LinkPreview, SendOptions};
+    ///
+    /// # fn example(client: &whatsmeow::WhatsApp) -> whatsmeow::Result<()> {
+    /// let preview = LinkPreview::new("https://www.rust-lang.org", "Rust")
+    ///     .description("A language empowering everyone");
+    /// client.send_with(
+    ///     Jid::user("1234567890"),
+    ///     "Check out https://www.rust-lang.org",
+    ///     SendOptions::new().link_preview(preview),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_with(
+        &self,
+        to: impl Into<Jid>,
+        message: impl Into<MessageType>,
+        options: SendOptions,
+    ) -> Result<SentMessage> {
+        let (jid, msg) = self.before_send_blocking(to.into(), message.into())?;
+        options.validate()?;
+
+        // All loading happens here, before the FFI handle is locked
+        let outgoing = Outgoing::prepare(&msg, &options)?;
+        self.send_prepared(jid, &outgoing)
+    }
+
+    /// Send one message to many recipients
+    ///
+    /// Media is loaded and validated once, then up to
+    /// [`batch_concurrency`](crate::WhatsAppBuilder::batch_concurrency)
+    /// recipients are worked on at a time. Results are returned in the order of
+    /// `recipients`; one recipient failing does not stop the others. Fails
+    /// as a whole only if the message itself is invalid. With
+    /// [`before_send`](crate::WhatsAppBuilder::before_send) middleware, each
+    /// recipient's message is run through it and prepared separately.
+    pub async fn send_batch<I>(
+        &self,
+        recipients: I,
+        message: impl Into<MessageType>,
+        options: SendOptions,
+    ) -> Result<Vec<Result<SentMessage>>>
+    where
+        I: IntoIterator,
+        I::Item: Into<Jid>,
+    {
         let msg: MessageType = message.into();
+        options.validate()?;
+        let priority = options.priority;
 
-        match msg {
-            MessageType::Text(text) => self.inner.send_message(jid.as_str(), &text),
-            MessageType::Image {
-                source,
-                mime_type,
-                caption,
-            } => {
-                // Resolve the media source to bytes
-                let data = match source.load() {
-                    Ok(data) => data,
-                    Err(e) => {
-                        return Err(crate::error::Error::Send(format!(
-                            "Failed to load media: {}",
-                            e
-                        )));
-                    }
-                };
+        // Middleware may change the message per recipient
+        let shared = if self.inner.config().before_send.is_empty() {
+            let (msg, options) = (msg.clone(), options.clone());
+            let prepared = tokio::task::spawn_blocking(move || Outgoing::prepare(&msg, &options))
+                .await
+                .map_err(|e| Error::Send(format!("send task failed: {}", e)))??;
+            Some(Arc::new(prepared))
+        } else {
+            None
+        };
+
+        let limit = self.inner.config().batch_limit();
+        let permits = Arc::new(Semaphore::new(limit));
 
-                // Auto-detect MIME type from file signature if not provided
-                let detected_mime = mime_type.unwrap_or_else(|| {
-                    crate::events::MediaSource::detect_mime_from_signature(&data)
-                });
+        let tasks: Vec<_> = recipients
+            .into_iter()
+            .map(|to| {
+                let client = self.share();
+                let shared = shared.clone();
+                let (msg, options) = (msg.clone(), options.clone());
+                let permits = permits.clone();
+                let jid: Jid = to.into();
+                tokio::spawn(async move {
+                    let _permit = permits.acquire_owned().await;
+                    let (jid, outgoing) = match shared {
+                        Some(outgoing) => (jid, outgoing),
+                        None => {
+                            let (jid, msg) = client.before_send(jid, msg).await?;
+                            let prepared = tokio::task::spawn_blocking(move || {
+                                Outgoing::prepare(&msg, &options)
+                            })
+                            .await
+                            .map_err(|e| Error::Send(format!("send task failed: {}", e)))??;
+                            (jid, Arc::new(prepared))
+                        }
+                    };
+                    let _turn = client.inner.send_queue.acquire(priority).await;
+                    client.send_prepared_async(jid, outgoing).await
+                })
+            })
+            .collect();
 
-                self.inner
-                    .send_image(jid.as_str(), &data, &detected_mime, caption.as_deref())
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            results.push(
+                task.await
+                    .map_err(|e| Error::Send(format!("send task failed: {}", e)))
+                    .and_then(|r| r),
+            );
+        }
+        Ok(results)
+    }
+
+    /// Decline an incoming call from [`Event::CallOffer`](crate::Event::CallOffer)
+    pub fn reject_call(&self, call: &CallOfferEvent) -> Result<()> {
+        self.inner.reject_call(&call.from, &call.call_id)
+    }
+
+    /// Send a message the crate doesn't model yet
+    ///
+    /// `message` is the protobuf JSON mapping of WhatsApp's `waE2E.Message`,
+    /// e.g. `{"conversation": "hi"}`. It is passed through unchecked apart
+    /// from being a JSON object; the bridge rejects fields that don't exist
+    /// with [`Error::Validation`]. Prefer [`send`](Self::send) where a
+    /// [`MessageType`] covers the message.
+    pub fn send_raw(&self, to: impl Into<Jid>, message: serde_json::Value) -> Result<SentMessage> {
+        if !message.is_object() {
+            return Err(Error::Validation(
+                "Raw message must be a JSON object".into(),
+            ));
+        }
+        self.send_prepared(to.into(), &Outgoing::Raw(message.to_string()))
+    }
+
+    /// Run the [`before_send`](crate::WhatsAppBuilder::before_send) chain
+    async fn before_send(&self, jid: Jid, msg: MessageType) -> Result<(Jid, MessageType)> {
+        // Cloned so middleware calling back into the client never runs
+        // under the config lock
+        let chain = self.inner.config().before_send.clone();
+        let mut current = (jid, msg);
+        for middleware in chain {
+            let jid = current.0.clone();
+            current = middleware(current.0, current.1)
+                .await
+                .ok_or(Error::SendBlocked { jid })?;
+        }
+        Ok(current)
+    }
+
+    /// [`before_send`](Self::before_send) for the blocking send methods,
+    /// run to completion on the calling thread
+    fn before_send_blocking(&self, jid: Jid, msg: MessageType) -> Result<(Jid, MessageType)> {
+        futures::executor::block_on(self.before_send(jid, msg))
+    }
+
+    /// Whether a send to `jid` must first check it is on WhatsApp
+    ///
+    /// Recipients found registered recently are trusted without asking again.
+    fn needs_verification(&self, jid: &Jid) -> bool {
+        self.inner.config().verify_recipients
+            && jid.is_user()
+            && !self.inner.recently_verified(&jid.without_device())
+    }
+
+    /// Fail with [`Error::RecipientNotOnWhatsApp`] if `jid` needs verifying and isn't registered
+    fn verify_recipient(&self, jid: &Jid) -> Result<()> {
+        if self.needs_verification(jid) && !self.is_on_whatsapp(jid.clone())? {
+            return Err(Error::RecipientNotOnWhatsApp { jid: jid.clone() });
+        }
+        Ok(())
+    }
+
+    /// Verify the recipient if configured, then deliver a prepared message
+    ///
+    /// Delivering is the only step that holds the FFI handle.
+    fn send_prepared(&self, jid: Jid, outgoing: &Outgoing) -> Result<SentMessage> {
+        self.verify_recipient(&jid)?;
+        let data = self.inner.send(jid.as_str(), outgoing)?;
+        SentMessage::from_ffi(jid, &data)
+    }
+
+    /// [`send_prepared`](Self::send_prepared) for async sends, waiting its
+    /// turn for the handle without blocking the runtime
+    async fn send_prepared_async(&self, jid: Jid, outgoing: Arc<Outgoing>) -> Result<SentMessage> {
+        if self.needs_verification(&jid) {
+            let (client, target) = (self.share(), jid.clone());
+            let registered = tokio::task::spawn_blocking(move || client.is_on_whatsapp(target))
+                .await
+                .map_err(|e| Error::Send(format!("send task failed: {}", e)))??;
+            if !registered {
+                return Err(Error::RecipientNotOnWhatsApp { jid });
             }
         }
+
+        let to = jid.to_string();
+        let data = self
+            .inner
+            .call_async(move |ffi| outgoing.deliver(ffi, &to))
+            .await?;
+        SentMessage::from_ffi(jid, &data)
     }
 
-    /// Disconnect from WhatsApp
+    /// Send a message without blocking the async runtime
+    ///
+    /// Media loading, thumbnailing and the bridge call run on Tokio's
+    /// blocking pool. Behaves like [`send_with`](Self::send_with), and
+    /// additionally honours [`SendOptions::simulate_typing`]: the message is
+    /// prepared first, so invalid messages fail without any indicator, then
+    /// "typing..." is shown and cleared before the message goes out.
+    /// Waiting async sends go out by [`SendOptions::priority`].
+    pub async fn send_async(
+        &self,
+        to: impl Into<Jid>,
+        message: impl Into<MessageType>,
+        options: SendOptions,
+    ) -> Result<SentMessage> {
+        let (jid, msg) = self.before_send(to.into(), message.into()).await?;
+        let typing = options.simulate_typing;
+        let priority = options.priority;
+
+        let outgoing = tokio::task::spawn_blocking(move || {
+            options.validate()?;
+            Outgoing::prepare(&msg, &options)
+        })
+        .await
+        .map_err(|e| Error::Send(format!("send task failed: {}", e)))??;
+
+        // Cleared before sending, and by the guard's drop if this future is
+        // cancelled mid-wait, so a failed send never leaves it showing
+        if let Some(duration) = typing {
+            self.typing(jid.clone(), duration).finished().await;
+        }
+
+        let _turn = self.inner.send_queue.acquire(priority).await;
+        self.send_prepared_async(jid, Arc::new(outgoing)).await
+    }
+
+    /// Connect again after a connection loss the client won't recover from
+    /// by itself, such as [`Event::StreamReplaced`](crate::Event::StreamReplaced)
+    ///
+    /// Ordinary drops are retried automatically.
+    pub async fn reconnect(&self) -> Result<()> {
+        let inner = self.inner.clone();
+        tokio::task::spawn_blocking(move || inner.connect())
+            .await
+            .map_err(|e| Error::Connection(format!("reconnect task failed: {}", e)))?
+    }
+
+    /// Disconnect from WhatsApp (safe to call more than once)
     pub fn disconnect(&self) {
         self.inner.disconnect();
     }
@@ -99,4 +401,1382 @@ impl WhatsApp {
     pub fn is_connected(&self) -> bool {
         self.inner.is_connected()
     }
+
+    /// Stop delivering events to handlers and streams without disconnecting
+    ///
+    /// The event loop keeps draining the bridge while paused, and contact
+    /// names, auto-read and reconnects keep working. Events that pass the
+    /// event filter are buffered (up to 1024, oldest dropped first) and
+    /// delivered in order on [`resume`](Self::resume).
+    pub fn pause(&self) {
+        self.inner.set_paused(true);
+    }
+
+    /// Resume delivery, starting with the events buffered while paused
+    pub fn resume(&self) {
+        self.inner.set_paused(false);
+    }
+
+    /// Check if event delivery is paused
+    pub fn is_paused(&self) -> bool {
+        self.inner.is_paused()
+    }
+
+    /// Latency percentiles per bridge function (e.g. `wm_send_message`,
+    /// `wm_poll_event`, `wm_client_connect`)
+    ///
+    /// Shows whether sends or event polling are the bottleneck. Empty unless
+    /// this client was built with
+    /// [`record_ffi_latencies`](crate::WhatsAppBuilder::record_ffi_latencies).
+    /// Covers only this client's calls, including those made through clones
+    /// of it and across reconnects; other clients in the process keep their
+    /// own.
+    pub fn ffi_latencies(&self) -> HashMap<String, LatencyStats> {
+        self.inner.ffi_latencies()
+    }
+
+    /// Snapshot of runtime metrics
+    pub fn metrics(&self) -> Metrics {
+        self.inner.metrics()
+    }
+
+    /// Logged-in device JID, `None` before pairing
+    pub fn own_jid(&self) -> Result<Option<Jid>> {
+        let data = self.inner.own_jid()?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let jid = String::from_utf8(data)
+            .map_err(|_| Error::Validation("Own JID is not valid UTF-8".into()))?;
+        Ok(Some(Jid::new(jid)))
+    }
+
+    /// WhatsApp's current time, `None` until a live message has arrived
+    ///
+    /// Estimated from the server timestamp of the latest message delivered
+    /// while online, so it is only accurate to about a second.
+    pub fn server_time(&self) -> Result<Option<SystemTime>> {
+        Ok(self
+            .inner
+            .server_time()?
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis as u64)))
+    }
+
+    /// How far the local clock is from WhatsApp's, `None` until known
+    ///
+    /// A skewed clock makes outgoing timestamps and disappearing-message
+    /// timers look wrong to the other side.
+    pub fn clock_offset(&self) -> Result<Option<ClockOffset>> {
+        let Some(server) = self.inner.server_time()? else {
+            return Ok(None);
+        };
+        let local = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        Ok(Some(ClockOffset::from_millis(local - server)))
+    }
+
+    /// The display name contacts see for this account
+    ///
+    /// Not to be confused with [`device_name`](crate::WhatsAppBuilder::device_name), which only
+    /// labels this linked device. Returns `None` before pairing.
+    pub fn push_name(&self) -> Result<Option<String>> {
+        let data = self.inner.push_name()?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let name = String::from_utf8(data)
+            .map_err(|_| Error::Validation("Push name is not valid UTF-8".into()))?;
+        Ok(Some(name))
+    }
+
+    /// Change the display name contacts see for this account
+    ///
+    /// Must be 1 to [`MAX_PUSH_NAME_LEN`](crate::MAX_PUSH_NAME_LEN)
+    /// characters. The name syncs to the account's other devices.
+    pub fn set_push_name(&self, name: &str) -> Result<()> {
+        validate_push_name(name)?;
+        self.inner.set_push_name(name)
+    }
+
+    /// Collect connection state, metrics and environment for a bug report
+    ///
+    /// Never fails; parts that can't be determined are left empty.
+    pub fn diagnostics(&self) -> Diagnostics {
+        Diagnostics {
+            version: env!("CARGO_PKG_VERSION"),
+            library_path: embedded::library_path(),
+            connected: self.is_connected(),
+            paused: self.is_paused(),
+            own_jid: self.own_jid().ok().flatten(),
+            clock_offset: self.clock_offset().ok().flatten(),
+            metrics: self.metrics(),
+            handlers: self.handler_stats(),
+            memory: ffi::memory_stats(),
+        }
+    }
+
+    /// Number of callbacks registered per event type
+    ///
+    /// Handy for checking that builder `on_*` registrations took effect.
+    pub fn handler_stats(&self) -> HandlerStats {
+        self.inner.handlers.stats()
+    }
+
+    /// Send 2–30 images grouped as an album
+    ///
+    /// All sources are loaded and checked before anything is sent. The
+    /// caption is attached to the first image. Returns the image message IDs.
+    ///
+    /// Goes through [`before_send`](crate::WhatsAppBuilder::before_send) as a
+    /// [`MessageType::Album`] and recipient verification like any other
+    /// send. If middleware turns the album into another message, that
+    /// message is sent instead and its ID returned.
+    pub fn send_album(
+        &self,
+        to: impl Into<Jid>,
+        images: Vec<MediaSource>,
+        caption: Option<String>,
+    ) -> Result<Vec<MessageId>> {
+        let album = MessageType::Album { images, caption };
+        let (jid, msg) = self.before_send_blocking(to.into(), album)?;
+        let MessageType::Album { images, caption } = msg else {
+            let outgoing = Outgoing::prepare(&msg, &SendOptions::default())?;
+            return Ok(vec![self.send_prepared(jid, &outgoing)?.id]);
+        };
+        let items = load_album(&images)?;
+        self.verify_recipient(&jid)?;
+
+        let data = self.inner.send_album(
+            jid.as_str(),
+            &serde_json::to_string(&items)?,
+            caption.as_deref(),
+        )?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Send read receipts for messages in a chat
+    ///
+    /// `sender` is required for group chats and may be empty for direct chats.
+    pub fn mark_read(
+        &self,
+        chat: impl Into<Jid>,
+        sender: impl Into<Jid>,
+        ids: &[impl AsRef<str>],
+    ) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<&str> = ids.iter().map(|id| id.as_ref()).collect();
+        self.inner
+            .mark_read(chat.into().as_str(), sender.into().as_str(), &ids)
+    }
+
+    /// Send "played" receipts for voice notes in a chat
+    ///
+    /// Complements [`mark_read`](Self::mark_read): WhatsApp shows a voice
+    /// note as listened to only after a played receipt. `sender` is required
+    /// for group chats and may be empty for direct chats.
+    pub fn mark_played(
+        &self,
+        chat: impl Into<Jid>,
+        sender: impl Into<Jid>,
+        ids: &[impl AsRef<str>],
+    ) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<&str> = ids.iter().map(|id| id.as_ref()).collect();
+        self.inner
+            .mark_played(chat.into().as_str(), sender.into().as_str(), &ids)
+    }
+
+    /// Flag a chat as unread on all of the account's devices
+    pub fn mark_unread(&self, chat: impl Into<Jid>) -> Result<()> {
+        self.inner.mark_chat_unread(chat.into().as_str())
+    }
+
+    /// Silence notifications for a chat on all of the account's devices
+    ///
+    /// `None` mutes until [`unmute_chat`](Self::unmute_chat) is called.
+    pub fn mute_chat(&self, chat: impl Into<Jid>, duration: Option<Duration>) -> Result<()> {
+        let secs = match duration {
+            Some(d) if d.as_secs() == 0 => {
+                return Err(Error::Validation(
+                    "Mute duration must be at least one second".into(),
+                ));
+            }
+            Some(d) => i64::try_from(d.as_secs()).unwrap_or(i64::MAX),
+            None => 0,
+        };
+        self.inner.mute_chat(chat.into().as_str(), true, secs)
+    }
+
+    /// Restore notifications for a muted chat
+    pub fn unmute_chat(&self, chat: impl Into<Jid>) -> Result<()> {
+        self.inner.mute_chat(chat.into().as_str(), false, 0)
+    }
+
+    /// Mute every group this account is a member of
+    ///
+    /// Groups are muted one at a time, spaced out to stay clear of
+    /// WhatsApp's rate limits, so this takes a while for accounts in many
+    /// groups. Returns each group with the outcome of its mute; fails as a
+    /// whole only if the group list can't be fetched.
+    pub async fn mute_all_groups(
+        &self,
+        duration: Option<Duration>,
+    ) -> Result<Vec<(Jid, Result<()>)>> {
+        let client = self.share();
+        let groups = tokio::task::spawn_blocking(move || client.joined_groups())
+            .await
+            .map_err(|e| Error::Connection(format!("group query task failed: {}", e)))??;
+
+        let mut results = Vec::with_capacity(groups.len());
+        for (i, group) in groups.into_iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(BULK_CHANGE_SPACING).await;
+            }
+            let client = self.share();
+            let jid = group.jid;
+            let chat = jid.clone();
+            let result = tokio::task::spawn_blocking(move || client.mute_chat(chat, duration))
+                .await
+                .unwrap_or_else(|e| Err(Error::Connection(format!("mute task failed: {}", e))));
+            results.push((jid, result));
+        }
+        Ok(results)
+    }
+
+    /// Permanently delete a chat's messages from the local history store
+    ///
+    /// This cannot be undone: [`messages`](Self::messages), [`starred`](Self::starred)
+    /// and [`unread_counts`](Self::unread_counts) forget the chat. The chat on
+    /// the phone and other devices is untouched. `confirm` must be `true`,
+    /// otherwise nothing is deleted and [`Error::Validation`] is returned.
+    pub fn clear_chat(&self, chat: impl Into<Jid>, confirm: bool) -> Result<()> {
+        if !confirm {
+            return Err(Error::Validation(
+                "clear_chat deletes history permanently; pass confirm = true".into(),
+            ));
+        }
+        self.inner.clear_chat(chat.into().as_str())
+    }
+
+    /// Show or clear the typing/recording indicator in a chat
+    pub fn set_chat_presence(&self, to: impl Into<Jid>, presence: ChatPresence) -> Result<()> {
+        self.inner.send_chat_presence(to.into().as_str(), presence)
+    }
+
+    /// Show "typing..." in a chat for `duration`, then clear it
+    ///
+    /// Returns immediately. Dropping or cancelling the guard clears the
+    /// indicator early; await [`TypingGuard::finished`] to wait it out.
+    ///
+    /// ```
+    /// # async fn example(client: &whatsmeow::WhatsApp) -> whatsmeow::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let to = whatsmeow::Jid::user("1234567890");
+    /// client.typing(to.clone(), Duration::from_secs(2)).finished().await;
+    /// client.send(to, "Hello!")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typing(&self, to: impl Into<Jid>, duration: Duration) -> TypingGuard {
+        TypingGuard::start(self.inner.clone(), to.into(), duration)
+    }
+
+    /// Parse a phone number, completing national numbers with the
+    /// builder's [`default_country_code`](crate::WhatsAppBuilder::default_country_code)
+    ///
+    /// Without a default country code this is [`PhoneNumber::parse`].
+    pub fn parse_phone(&self, s: &str) -> Result<PhoneNumber> {
+        let phone = match &self.inner.config().default_country_code {
+            Some(code) => PhoneNumber::parse_with_country(s, code)?,
+            None => PhoneNumber::parse(s)?,
+        };
+        Ok(phone)
+    }
+
+    /// Check whether a user JID or phone number has a WhatsApp account
+    pub fn is_on_whatsapp(&self, jid: impl Into<Jid>) -> Result<bool> {
+        let jid = jid.into().without_device();
+        let user = jid.as_str().split('@').next().unwrap_or_default();
+        let phone = format!("+{}", user.trim_start_matches('+'));
+
+        let data = self.inner.is_on_whatsapp(&[phone.as_str()])?;
+        if data.is_empty() {
+            return Ok(false);
+        }
+
+        let statuses: Vec<RegistrationStatus> = serde_json::from_slice(&data)?;
+        let registered = statuses.iter().any(|s| s.is_in);
+        if registered {
+            self.inner.mark_verified(jid.clone());
+        }
+        self.inner.contact_book.set_on_whatsapp(jid, registered);
+        Ok(registered)
+    }
+
+    /// Subscribe to presence updates for a contact
+    ///
+    /// Updates arrive as [`Event::Presence`](crate::Event::Presence). The
+    /// subscription only lasts for the current connection; use
+    /// [`WhatsAppBuilder::subscribe_presence`](crate::WhatsAppBuilder::subscribe_presence)
+    /// to have it re-issued on reconnect.
+    pub fn subscribe_presence(&self, jid: impl Into<Jid>) -> Result<()> {
+        self.inner.subscribe_presence(jid.into().as_str())
+    }
+
+    /// Check once whether a contact is online
+    ///
+    /// Subscribes to the contact's presence and waits up to
+    /// [`PRESENCE_QUERY_TIMEOUT`] for their next update. Contacts hiding
+    /// their online status never answer, which fails with
+    /// [`Error::PresenceTimeout`]. Requires the event loop to be running.
+    pub async fn query_presence(&self, jid: impl Into<Jid>) -> Result<PresenceEvent> {
+        let jid = jid.into().without_device();
+
+        // Listen before subscribing so an immediate answer isn't missed
+        let mut events = self.events();
+        let client = self.share();
+        let target = jid.clone();
+        tokio::task::spawn_blocking(move || client.subscribe_presence(target))
+            .await
+            .map_err(|e| Error::Connection(format!("presence task failed: {}", e)))??;
+
+        let wait = async {
+            while let Some(event) = events.next().await {
+                if let Event::Presence(presence) = event
+                    && Jid::new(presence.from.as_str()).without_device() == jid
+                {
+                    return Ok(presence);
+                }
+            }
+            Err(Error::Disconnected)
+        };
+
+        tokio::time::timeout(PRESENCE_QUERY_TIMEOUT, wait)
+            .await
+            .unwrap_or_else(|_| Err(Error::PresenceTimeout { jid: jid.clone() }))
+    }
+
+    /// Read messages for a chat from the local history store
+    ///
+    /// Returns up to `limit` messages, oldest first. To page backwards, pass
+    /// the `id` of the first (oldest) returned message as `before`.
+    pub fn messages(
+        &self,
+        chat: impl Into<Jid>,
+        limit: usize,
+        before: Option<&str>,
+    ) -> Result<Vec<StoredMessage>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let limit = i32::try_from(limit).unwrap_or(i32::MAX);
+        let data = self
+            .inner
+            .query_messages(chat.into().as_str(), limit, before)?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Star or unstar a message, syncing the flag to the account's other devices
+    ///
+    /// The message must be in the local history store; unknown IDs fail with
+    /// [`Error::Validation`].
+    pub fn star(&self, chat: impl Into<Jid>, id: impl AsRef<str>, starred: bool) -> Result<()> {
+        self.inner
+            .star_message(chat.into().as_str(), id.as_ref(), starred)
+    }
+
+    /// Starred messages in a chat from the local history store, oldest first
+    ///
+    /// Includes messages starred on other devices once their app state has synced.
+    pub fn starred(&self, chat: impl Into<Jid>) -> Result<Vec<StoredMessage>> {
+        let data = self.inner.starred(chat.into().as_str())?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Re-fetch contact and push names from the phone
+    ///
+    /// Fresh sessions sometimes link before the phone shares its contacts,
+    /// leaving [`get_contact`](Self::get_contact) empty. Each collection
+    /// reports [`Event::AppStateSynced`](crate::Event::AppStateSynced) once applied.
+    pub fn resync_contacts(&self) -> Result<()> {
+        AppStateCollection::CONTACTS
+            .iter()
+            .try_for_each(|&c| self.resync_app_state(c))
+    }
+
+    /// Re-fetch chat settings (mute, pin, archive, read state) from the phone
+    pub fn resync_chats(&self) -> Result<()> {
+        AppStateCollection::CHATS
+            .iter()
+            .try_for_each(|&c| self.resync_app_state(c))
+    }
+
+    /// Fully re-fetch a single app state collection
+    pub fn resync_app_state(&self, collection: AppStateCollection) -> Result<()> {
+        self.inner.resync_app_state(collection)
+    }
+
+    /// Export this device's credentials for backup or migration
+    ///
+    /// Restore them on another machine with
+    /// [`WhatsAppBuilder::restore_session`](crate::WhatsAppBuilder::restore_session).
+    /// Only the login credentials are included; message history, contacts and
+    /// encryption sessions with other users are rebuilt after reconnecting.
+    pub fn export_session(&self) -> Result<SessionBlob> {
+        self.inner.export_session()
+    }
+
+    /// Count unread incoming messages per chat from the local history store
+    ///
+    /// Chats with nothing unread are omitted. [`mark_read`](Self::mark_read)
+    /// resets a chat's count up to the marked messages, as does reading or
+    /// replying from another linked device.
+    pub fn unread_counts(&self) -> Result<HashMap<Jid, u32>> {
+        let data = self.inner.unread_counts()?;
+        if data.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut counts: HashMap<Jid, u32> = serde_json::from_slice(&data)?;
+        counts.retain(|_, count| *count > 0);
+        Ok(counts)
+    }
+
+    /// Count what the session database holds and measure it on disk
+    ///
+    /// For monitoring growth from history sync. Counting contacts reads the
+    /// whole contact table, so poll this every few minutes, not per message.
+    pub fn store_stats(&self) -> Result<StoreStats> {
+        let (data, db_path) = self.inner.store_stats()?;
+        let mut stats: StoreStats = if data.is_empty() {
+            StoreStats::default()
+        } else {
+            serde_json::from_slice(&data)?
+        };
+        stats.db_size = store_stats::db_size(&db_path)?;
+        Ok(stats)
+    }
+
+    /// List all devices linked to this account (phone and companions)
+    pub fn linked_devices(&self) -> Result<Vec<LinkedDevice>> {
+        let data = self.inner.linked_devices()?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Unlink a device from this account
+    ///
+    /// Linked companions can only unlink themselves (this logs the session out);
+    /// other devices must be removed from the primary phone.
+    pub fn unlink_device(&self, jid: impl Into<Jid>) -> Result<()> {
+        self.inner.remove_device(jid.into().as_str())
+    }
+
+    /// Groups this account is a member of
+    pub fn joined_groups(&self) -> Result<Vec<GroupMetadata>> {
+        let data = self.inner.joined_groups()?;
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Look up a group from its invite link without joining
+    ///
+    /// Accepts `https://chat.whatsapp.com/<code>` links or the bare code.
+    pub fn preview_group(&self, link: &str) -> Result<GroupMetadata> {
+        let code = invite_code(link)?;
+        let data = self.inner.group_info_from_link(code)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Fetch the account's privacy settings from the server
+    pub fn privacy_settings(&self) -> Result<PrivacySettings> {
+        let data = self.inner.privacy_settings()?;
+        if data.is_empty() {
+            return Ok(PrivacySettings::default());
+        }
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Change a privacy setting
+    ///
+    /// Not every value is valid for every key (e.g. read receipts only accept
+    /// `All` or `None`); the server rejects unsupported combinations.
+    pub fn set_privacy(&self, key: PrivacyKey, value: PrivacyValue) -> Result<()> {
+        self.inner.set_privacy_setting(key.as_str(), value.as_str())
+    }
+
+    /// Look up a contact in the local contact store
+    pub fn get_contact(&self, jid: impl Into<Jid>) -> Result<Option<ContactInfo>> {
+        let jid = jid.into();
+        let data = self.inner.get_contact(jid.as_str())?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let info: ContactInfo = serde_json::from_slice(&data)?;
+        if !info.found {
+            return Ok(None);
+        }
+        self.inner.contact_book.merge_info(jid, &info);
+        Ok(Some(info))
+    }
+
+    /// Fetch the profile picture of a user or group
+    ///
+    /// [`PictureQuality::Preview`] returns a small thumbnail that is quicker
+    /// to download. Returns `None` if the picture is unset or hidden by the
+    /// owner's privacy settings.
+    pub fn profile_picture(
+        &self,
+        jid: impl Into<Jid>,
+        quality: PictureQuality,
+    ) -> Result<Option<ProfilePicture>> {
+        let data = self
+            .inner
+            .profile_picture(jid.into().as_str(), quality == PictureQuality::Full)?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&data)?))
+    }
+
+    /// Map a LID to the phone number JID it stands for
+    ///
+    /// Answered from the local store, which learns mappings from incoming
+    /// messages and history sync; returns `None` when none is known yet.
+    /// Fails with [`Error::Validation`] if `lid` is not an `@lid` JID.
+    pub fn resolve_lid(&self, lid: impl Into<Jid>) -> Result<Option<Jid>> {
+        let lid = lid.into();
+        if !lid.is_lid() {
+            return Err(Error::Validation(format!("Not a LID: {}", lid.as_str())));
+        }
+
+        let data = self.inner.resolve_lid(lid.as_str())?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+        let mapping: LidMapping = serde_json::from_slice(&data)?;
+        Ok((!mapping.phone.is_empty()).then(|| Jid::new(mapping.phone)))
+    }
+
+    /// Best human-readable name for a JID
+    ///
+    /// Tries, in order: names seen on recent messages, the contact store,
+    /// then the bare phone number / user part of the JID. Names found in the
+    /// contact store are cached.
+    pub fn display_name(&self, jid: impl Into<Jid>) -> String {
+        let jid = jid.into().without_device();
+
+        if let Some(name) = self.inner.contacts.get(&jid) {
+            return name;
+        }
+
+        match self.get_contact(jid.clone()) {
+            Ok(Some(info)) => {
+                if let Some(name) = info.best_name() {
+                    self.inner.contacts.insert(jid, name.to_string());
+                    return name.to_string();
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::debug!(error = %e, %jid, "Contact lookup failed"),
+        }
+
+        jid.as_str()
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Every contact seen so far, searchable by name
+    ///
+    /// Filled from incoming messages' push names,
+    /// [`get_contact`](Self::get_contact) lookups and
+    /// [`is_on_whatsapp`](Self::is_on_whatsapp) checks.
+    pub fn contact_book(&self) -> &ContactBook {
+        &self.inner.contact_book
+    }
+
+    /// Get the last push name seen for a JID on incoming messages
+    ///
+    /// Useful for senders whose later messages arrive without a push name.
+    pub fn cached_name(&self, jid: impl Into<Jid>) -> Option<String> {
+        self.inner.contacts.get(&jid.into())
+    }
+}
+
+/// Entry of the bridge's registration query response
+#[derive(serde::Deserialize)]
+struct RegistrationStatus {
+    is_in: bool,
+}
+
+impl Clone for WhatsApp {
+    fn clone(&self) -> Self {
+        Self::from_inner(self.inner.clone())
+    }
+}
+
+impl Drop for WhatsApp {
+    fn drop(&mut self) {
+        if self.counted {
+            self.inner.release_handle();
+        }
+    }
+}
+
+/// Takes a clone out of the handle count while it runs the event loop
+struct RunningHandle<'a>(&'a InnerClient);
+
+impl<'a> RunningHandle<'a> {
+    fn new(inner: &'a InnerClient) -> Self {
+        inner.set_handle_running(true);
+        Self(inner)
+    }
+}
+
+impl Drop for RunningHandle<'_> {
+    fn drop(&mut self) {
+        self.0.set_handle_running(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Instant;
+
+    use super::*;
+    use crate::mock_ffi::{self, Mock, Reply};
+
+    #[tokio::test]
+    async fn sending_to_an_unregistered_number_fails() {
+        let client = WhatsApp::connect(mock_ffi::db_path("verify-unregistered"))
+            .verify_recipients(true)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.reply(
+            "wm_is_on_whatsapp",
+            Reply::ok(br#"[{"is_in":false}]"#.to_vec()),
+        );
+
+        let result = client
+            .send_async("15550001111@s.whatsapp.net", "hi", SendOptions::default())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::RecipientNotOnWhatsApp { jid }) if jid.as_str() == "15550001111@s.whatsapp.net"
+        ));
+        assert!(mock.calls("wm_send_message").is_empty());
+    }
+
+    #[tokio::test]
+    async fn registered_recipients_are_only_checked_once() {
+        let client = WhatsApp::connect(mock_ffi::db_path("verify-cached"))
+            .verify_recipients(true)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.reply(
+            "wm_is_on_whatsapp",
+            Reply::ok(br#"[{"is_in":true}]"#.to_vec()),
+        );
+
+        client.send("15550001111@s.whatsapp.net", "one").unwrap();
+        client
+            .send_async("15550001111@s.whatsapp.net", "two", SendOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(mock.calls("wm_is_on_whatsapp").len(), 1);
+        assert_eq!(mock.calls("wm_send_message").len(), 2);
+    }
+
+    fn album() -> Vec<MediaSource> {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        vec![MediaSource::bytes(png.clone()), MediaSource::bytes(png)]
+    }
+
+    #[tokio::test]
+    async fn albums_go_through_before_send() {
+        let client = WhatsApp::connect(mock_ffi::db_path("album-before-send"))
+            .before_send(|_, msg| async { Some((Jid::from("2@s.whatsapp.net"), msg)) })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+
+        let ids = client
+            .send_album("1@s.whatsapp.net", album(), Some("trip".into()))
+            .unwrap();
+
+        assert_eq!(ids.len(), 2);
+        let calls = mock.calls("wm_send_album");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0][0], "2@s.whatsapp.net");
+        assert_eq!(calls[0][2], "trip");
+    }
+
+    #[tokio::test]
+    async fn blocked_albums_are_not_sent() {
+        let client = WhatsApp::connect(mock_ffi::db_path("album-blocked"))
+            .before_send(|_, _| async { None })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+
+        let result = client.send_album("1@s.whatsapp.net", album(), None);
+
+        assert!(matches!(result, Err(Error::SendBlocked { .. })));
+        assert!(mock.calls("wm_send_album").is_empty());
+    }
+
+    #[tokio::test]
+    async fn albums_to_unregistered_numbers_are_not_sent() {
+        let client = WhatsApp::connect(mock_ffi::db_path("album-unregistered"))
+            .verify_recipients(true)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+
+        let result = client.send_album("15550001111@s.whatsapp.net", album(), None);
+
+        assert!(matches!(result, Err(Error::RecipientNotOnWhatsApp { .. })));
+        assert!(mock.calls("wm_send_album").is_empty());
+    }
+
+    #[tokio::test]
+    async fn an_album_with_a_missing_image_sends_nothing() {
+        let (client, mock) = mock_ffi::client("album-missing-image").await;
+        let mut images = album();
+        images.push(MediaSource::file("/nonexistent/album-image.png"));
+
+        assert!(client.send_album("1@s.whatsapp.net", images, None).is_err());
+        assert!(mock.calls("wm_send_album").is_empty());
+    }
+
+    #[tokio::test]
+    async fn albums_are_rejected_by_single_message_sends() {
+        let (client, mock) = mock_ffi::client("album-single-send").await;
+        let album = MessageType::Album {
+            images: album(),
+            caption: None,
+        };
+
+        assert!(matches!(
+            client.send("1@s.whatsapp.net", album),
+            Err(Error::Validation(_))
+        ));
+        assert!(
+            mock.call_names()
+                .iter()
+                .all(|name| !name.starts_with("wm_send"))
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_the_last_clone_stops_a_spawned_run_loop() {
+        let (client, mock) = mock_ffi::client("drop-last-clone").await;
+        let pump = client.clone();
+        let task = tokio::spawn(async move { pump.run().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        drop(client);
+
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("run loop kept going after the last clone was dropped")
+            .unwrap()
+            .unwrap();
+        assert!(!mock.calls("wm_client_disconnect").is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelling_the_token_stops_run_but_keeps_the_connection() {
+        let (client, mock) = mock_ffi::client("run-with-cancel").await;
+        let token = CancellationToken::new();
+        let pump = client.clone();
+        let cancel = token.clone();
+        let task = tokio::spawn(async move { pump.run_with_cancel(cancel).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!task.is_finished());
+
+        token.cancel();
+
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("run loop ignored the cancelled token")
+            .unwrap()
+            .unwrap();
+        assert!(mock.calls("wm_client_disconnect").is_empty());
+    }
+
+    #[tokio::test]
+    async fn internal_copies_do_not_stop_the_run_loop() {
+        let (client, _mock) = mock_ffi::client("internal-copies").await;
+        let pump = client.clone();
+        let task = tokio::spawn(async move { pump.run().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        client
+            .send_async("1@s.whatsapp.net", "hi", SendOptions::default())
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!task.is_finished());
+
+        client.disconnect();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_async_sends_leave_the_runtime_responsive() {
+        let (client, mock) = mock_ffi::client("concurrent-async-sends").await;
+        mock.delay("wm_send_message", Duration::from_millis(20));
+
+        let sends: Vec<_> = (0..10)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .send_async("1@s.whatsapp.net", format!("{i}"), SendOptions::default())
+                        .await
+                })
+            })
+            .collect();
+
+        // Ten 20ms calls queue for ~200ms; waiting on them must not stall timers
+        let started = Instant::now();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        let mut ids = HashSet::new();
+        for send in sends {
+            ids.insert(send.await.unwrap().unwrap().id);
+        }
+        assert_eq!(ids.len(), 10);
+        assert_eq!(mock.calls("wm_send_message").len(), 10);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn batch_results_follow_input_order_under_the_cap() {
+        let client = WhatsApp::connect(mock_ffi::db_path("batch-order"))
+            .batch_concurrency(4)
+            .build()
+            .await
+            .unwrap();
+        let mock = mock_ffi::Mock::of(&client);
+        mock.delay("wm_send_message", Duration::from_millis(1));
+
+        let ffi = client.inner.ffi.clone();
+        let sampling = CancellationToken::new();
+        let sampler = tokio::spawn({
+            let sampling = sampling.clone();
+            async move {
+                let mut peak = 0;
+                while !sampling.is_cancelled() {
+                    peak = peak.max(ffi.queue_depth());
+                    tokio::task::yield_now().await;
+                }
+                peak
+            }
+        });
+
+        let recipients: Vec<Jid> = (0..100)
+            .map(|i| Jid::new(format!("{}@s.whatsapp.net", 1000 + i)))
+            .collect();
+        let results = client
+            .send_batch(recipients.clone(), "hello", SendOptions::default())
+            .await
+            .unwrap();
+        sampling.cancel();
+
+        let sent_to: Vec<Jid> = results.into_iter().map(|r| r.unwrap().to).collect();
+        assert_eq!(sent_to, recipients);
+        assert_eq!(mock.calls("wm_send_message").len(), 100);
+        assert!(sampler.await.unwrap() <= 4);
+    }
+
+    #[tokio::test]
+    async fn before_send_rewrites_the_message_on_every_send_path() {
+        let client = WhatsApp::connect(mock_ffi::db_path("before-send-rewrite"))
+            .before_send(|jid, msg| async move {
+                match msg {
+                    MessageType::Text(text) => {
+                        Some((jid, MessageType::Text(format!("{text} [bot]"))))
+                    }
+                    other => Some((jid, other)),
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = mock_ffi::Mock::of(&client);
+
+        client.send("1@s.whatsapp.net", "hi").unwrap();
+        client
+            .send_async("1@s.whatsapp.net", "hey", SendOptions::default())
+            .await
+            .unwrap();
+
+        let texts: Vec<String> = mock
+            .calls("wm_send_message")
+            .into_iter()
+            .map(|args| args[1].clone())
+            .collect();
+        assert_eq!(texts, ["hi [bot]", "hey [bot]"]);
+    }
+
+    #[tokio::test]
+    async fn async_sends_await_before_send() {
+        let client = WhatsApp::connect(mock_ffi::db_path("before-send-await"))
+            .before_send(|jid, msg| async move {
+                // Stands in for a lookup such as an opt-out list
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                (jid.as_str() != "2@s.whatsapp.net").then_some((jid, msg))
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = mock_ffi::Mock::of(&client);
+
+        client
+            .send_async("1@s.whatsapp.net", "hi", SendOptions::default())
+            .await
+            .unwrap();
+        let results = client
+            .send_batch(
+                ["2@s.whatsapp.net", "3@s.whatsapp.net"],
+                "hey",
+                SendOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(results[0], Err(Error::SendBlocked { .. })));
+        assert!(results[1].is_ok());
+        let sent_to: Vec<String> = mock
+            .calls("wm_send_message")
+            .into_iter()
+            .map(|args| args[0].clone())
+            .collect();
+        assert_eq!(sent_to, ["1@s.whatsapp.net", "3@s.whatsapp.net"]);
+    }
+
+    #[tokio::test]
+    async fn before_send_returning_none_blocks_the_send() {
+        let client = WhatsApp::connect(mock_ffi::db_path("before-send-block"))
+            .before_send(|jid, msg| async move { (!jid.is_group()).then_some((jid, msg)) })
+            .build()
+            .await
+            .unwrap();
+        let mock = mock_ffi::Mock::of(&client);
+
+        let blocked = client.send(Jid::group("123"), "hi");
+        assert!(matches!(blocked, Err(Error::SendBlocked { jid }) if jid == Jid::group("123")));
+        let blocked = client
+            .send_async(Jid::group("123"), "hi", SendOptions::default())
+            .await;
+        assert!(matches!(blocked, Err(Error::SendBlocked { .. })));
+        assert!(mock.calls("wm_send_message").is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_async_send_reports_the_bridge_error() {
+        let (client, mock) = mock_ffi::client("failed-async-send").await;
+        mock.reply(
+            "wm_send_message",
+            Reply::err(whatsmeow_sys::error_codes::WM_ERR_CONNECT),
+        );
+
+        let result = client
+            .send_async("1@s.whatsapp.net", "hi", SendOptions::default())
+            .await;
+        assert!(matches!(result, Err(Error::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn display_name_prefers_the_cache_then_the_store_then_the_number() {
+        let (client, mock) = mock_ffi::client("display-name-order").await;
+        client
+            .inner
+            .contacts
+            .insert("15550001111@s.whatsapp.net".into(), "Alice".into());
+        mock.reply(
+            "wm_get_contact",
+            Reply::ok(br#"{"found":true,"push_name":"B","full_name":"Bob"}"#.to_vec()),
+        );
+        mock.reply("wm_get_contact", Reply::ok(br#"{"found":false}"#.to_vec()));
+        mock.reply(
+            "wm_get_contact",
+            Reply::err(whatsmeow_sys::error_codes::WM_ERR_UNSUPPORTED),
+        );
+
+        assert_eq!(client.display_name("15550001111:2@s.whatsapp.net"), "Alice");
+        assert_eq!(client.display_name("15550002222@s.whatsapp.net"), "Bob");
+        // Cached after the store lookup
+        assert_eq!(client.display_name("15550002222@s.whatsapp.net"), "Bob");
+        assert_eq!(
+            client.display_name("15550003333@s.whatsapp.net"),
+            "15550003333"
+        );
+        assert_eq!(
+            client.display_name("15550004444@s.whatsapp.net"),
+            "15550004444"
+        );
+
+        assert_eq!(
+            mock.calls("wm_get_contact"),
+            [
+                vec!["15550002222@s.whatsapp.net"],
+                vec!["15550003333@s.whatsapp.net"],
+                vec!["15550004444@s.whatsapp.net"],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unread_counts_are_keyed_by_chat_and_skip_read_chats() {
+        let (client, mock) = mock_ffi::client("unread-counts").await;
+        mock.reply(
+            "wm_get_unread_counts",
+            Reply::ok(
+                br#"{"15550001111@s.whatsapp.net":3,"123-456@g.us":12,"15550002222@s.whatsapp.net":0}"#
+                    .to_vec(),
+            ),
+        );
+
+        let counts = client.unread_counts().unwrap();
+
+        assert_eq!(
+            counts,
+            HashMap::from([
+                (Jid::from("15550001111@s.whatsapp.net"), 3),
+                (Jid::from("123-456@g.us"), 12),
+            ])
+        );
+        // An empty reply means nothing is unread
+        assert!(client.unread_counts().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn mark_unread_and_clear_chat_pass_the_chat_jid() {
+        let (client, mock) = mock_ffi::client("unread-and-clear").await;
+
+        client.mark_unread("15550001111@s.whatsapp.net").unwrap();
+        client.clear_chat("123-456@g.us", true).unwrap();
+
+        assert_eq!(
+            mock.calls("wm_mark_chat_unread"),
+            [vec!["15550001111@s.whatsapp.net"]]
+        );
+        assert_eq!(mock.calls("wm_clear_chat"), [vec!["123-456@g.us"]]);
+    }
+
+    #[tokio::test]
+    async fn clock_offset_compares_the_server_time_with_the_local_clock() {
+        let (client, mock) = mock_ffi::client("clock-offset").await;
+        assert_eq!(client.clock_offset().unwrap(), None);
+
+        // Server an hour behind the local clock
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        mock.set_server_time(now - 3_600_000);
+
+        let offset = client.clock_offset().unwrap().unwrap();
+        assert!(offset.is_local_ahead());
+        assert!(
+            (offset.as_millis() - 3_600_000).abs() < 1_000,
+            "{:?}",
+            offset
+        );
+        assert!(offset.magnitude() >= Duration::from_secs(3_599));
+    }
+
+    #[tokio::test]
+    async fn url_images_are_fetched_by_the_bridge() {
+        let (client, mock) = mock_ffi::client("image-url").await;
+
+        client
+            .send(
+                "1@s.whatsapp.net",
+                MessageType::image_auto_with_caption(
+                    crate::MediaSource::url("https://example.com/cat.jpg"),
+                    "cat",
+                ),
+            )
+            .unwrap();
+
+        let calls = mock.calls("wm_send_image_url");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0][..4],
+            ["1@s.whatsapp.net", "https://example.com/cat.jpg", "", "cat"]
+        );
+        assert!(mock.calls("wm_send_image_ex").is_empty());
+    }
+
+    #[tokio::test]
+    async fn non_http_image_urls_are_rejected() {
+        let (client, mock) = mock_ffi::client("image-url-scheme").await;
+
+        let result = client.send(
+            "1@s.whatsapp.net",
+            MessageType::image_auto(crate::MediaSource::url("ftp://example.com/cat.jpg")),
+        );
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(mock.calls("wm_send_image_url").is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_send_timestamp_reaches_the_bridge_in_unix_seconds() {
+        let (client, mock) = mock_ffi::client("send-timestamp").await;
+        let at = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+
+        client
+            .send_with("1@s.whatsapp.net", "hi", SendOptions::new().timestamp(at))
+            .unwrap();
+
+        let calls = mock.calls("wm_send_message_ex");
+        assert_eq!(calls.len(), 1);
+        let options: serde_json::Value = serde_json::from_str(&calls[0][2]).unwrap();
+        assert_eq!(options, serde_json::json!({ "timestamp": 1_704_067_200 }));
+    }
+
+    #[tokio::test]
+    async fn a_timestamp_far_in_the_future_is_rejected() {
+        let (client, mock) = mock_ffi::client("send-timestamp-future").await;
+        let at = SystemTime::now() + crate::MAX_TIMESTAMP_LEAD + Duration::from_secs(60);
+
+        let result = client.send_with("1@s.whatsapp.net", "hi", SendOptions::new().timestamp(at));
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(mock.calls("wm_send_message_ex").is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_caller_chosen_message_id_is_sent_and_returned() {
+        let (client, mock) = mock_ffi::client("send-message-id").await;
+        let id = "3EB0A1B2C3D4E5F6A7B8C9";
+
+        let sent = client
+            .send_with("1@s.whatsapp.net", "hi", SendOptions::new().message_id(id))
+            .unwrap();
+
+        assert_eq!(sent.id.as_str(), id);
+        let calls = mock.calls("wm_send_message_ex");
+        let options: serde_json::Value = serde_json::from_str(&calls[0][2]).unwrap();
+        assert_eq!(options, serde_json::json!({ "message_id": id }));
+    }
+
+    #[tokio::test]
+    async fn malformed_or_misplaced_message_ids_are_rejected() {
+        let (client, mock) = mock_ffi::client("send-message-id-invalid").await;
+
+        for id in ["3eb0a1b2c3d4e5f6a7b8c9", "3EB0", "3EB0A1B2C3D4E5F6A7B8C9G"] {
+            let result =
+                client.send_with("1@s.whatsapp.net", "hi", SendOptions::new().message_id(id));
+            assert!(matches!(result, Err(Error::Validation(_))), "{id}");
+        }
+        let image =
+            MessageType::image_auto(crate::MediaSource::bytes(b"\xFF\xD8\xFF\xE0".to_vec()));
+        let result = client.send_with(
+            "1@s.whatsapp.net",
+            image,
+            SendOptions::new().message_id("3EB0A1B2C3D4E5F6A7B8C9"),
+        );
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(
+            !mock
+                .call_names()
+                .iter()
+                .any(|name| name.starts_with("wm_send"))
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_bridge_calls_give_latency_percentiles() {
+        let client = WhatsApp::connect(mock_ffi::db_path("ffi-latencies"))
+            .record_ffi_latencies(true)
+            .build()
+            .await
+            .unwrap();
+        let (other, _) = mock_ffi::client("ffi-latencies-other").await;
+        let mock = Mock::of(&client);
+        mock.delay("wm_get_linked_devices", Duration::from_millis(20));
+
+        for _ in 0..5 {
+            client.linked_devices().unwrap();
+        }
+        other.linked_devices().unwrap();
+
+        let stats = client.ffi_latencies()["wm_get_linked_devices"];
+        assert_eq!(stats.calls, 5);
+        // Recording is per client and off unless asked for
+        assert!(other.ffi_latencies().is_empty());
+        assert!(stats.max >= Duration::from_millis(20));
+        assert!(stats.p50 >= Duration::from_millis(16), "{:?}", stats);
+        assert!(stats.p50 <= stats.p99 && stats.p99 <= stats.max);
+    }
+
+    #[tokio::test]
+    async fn mark_played_passes_the_ids_through() {
+        let (client, mock) = mock_ffi::client("mark-played").await;
+
+        client
+            .mark_played("123-456@g.us", "1@s.whatsapp.net", &["V1", "V2"])
+            .unwrap();
+
+        assert_eq!(
+            mock.calls("wm_mark_played"),
+            [vec!["123-456@g.us", "1@s.whatsapp.net", r#"["V1","V2"]"#]]
+        );
+    }
+
+    #[tokio::test]
+    async fn mark_played_with_no_ids_skips_the_bridge() {
+        let (client, mock) = mock_ffi::client("mark-played-empty").await;
+
+        client
+            .mark_played("1@s.whatsapp.net", "", &[] as &[&str])
+            .unwrap();
+
+        assert!(mock.calls("wm_mark_played").is_empty());
+    }
+
+    #[tokio::test]
+    async fn clear_chat_without_confirmation_deletes_nothing() {
+        let (client, mock) = mock_ffi::client("clear-unconfirmed").await;
+
+        assert!(matches!(
+            client.clear_chat("123-456@g.us", false),
+            Err(Error::Validation(_))
+        ));
+        assert!(mock.calls("wm_clear_chat").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn a_slow_media_load_does_not_hold_up_a_text_send() {
+        let (client, mock) = mock_ffi::client("slow-media-load").await;
+        // Reading a FIFO blocks until something writes to it
+        let fifo =
+            std::env::temp_dir().join(format!("whatsmeow-slow-media-{}", std::process::id()));
+        let _ = std::fs::remove_file(&fifo);
+        let made = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(made.success());
+
+        let media = client.clone();
+        let source = MediaSource::file(&fifo);
+        let image = tokio::task::spawn_blocking(move || {
+            media.send("1@s.whatsapp.net", MessageType::image(source, "image/png"))
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let started = Instant::now();
+        client.send("2@s.whatsapp.net", "hi").unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert!(!image.is_finished());
+        assert!(mock.calls("wm_send_image_ex").is_empty());
+
+        std::fs::write(&fifo, b"\x89PNG\r\n\x1a\n").unwrap();
+        image.await.unwrap().unwrap();
+        std::fs::remove_file(&fifo).unwrap();
+        assert_eq!(mock.calls("wm_send_image_ex").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn raw_message_json_is_passed_to_the_bridge() {
+        let (client, mock) = mock_ffi::client("send-raw").await;
+        let message = serde_json::json!({ "conversation": "hi" });
+
+        let sent = client
+            .send_raw("15550001111@s.whatsapp.net", message.clone())
+            .unwrap();
+
+        let calls = mock.calls("wm_send_raw");
+        assert_eq!(calls[0][0], "15550001111@s.whatsapp.net");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&calls[0][1]).unwrap(),
+            message
+        );
+        assert_eq!(sent.to, Jid::from("15550001111@s.whatsapp.net"));
+    }
+
+    #[tokio::test]
+    async fn raw_messages_must_be_objects() {
+        let (client, mock) = mock_ffi::client("send-raw-invalid").await;
+
+        for message in [serde_json::json!("hi"), serde_json::json!([1, 2])] {
+            assert!(matches!(
+                client.send_raw("15550001111@s.whatsapp.net", message),
+                Err(Error::Validation(_))
+            ));
+        }
+        assert!(mock.calls("wm_send_raw").is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_file_picks_the_message_type_from_the_contents() {
+        let (client, mock) = mock_ffi::client("send-file").await;
+        let dir = mock_ffi::db_path("send-file").with_extension("d");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Named misleadingly: only the signature counts
+        let jpeg = dir.join("photo.bin");
+        std::fs::write(&jpeg, b"\xFF\xD8\xFF\xE0\0\x10JFIF\0").unwrap();
+        let blob = dir.join("blob.jpg");
+        std::fs::write(
+            &blob,
+            [0x13, 0x37, 0x00, 0xA5, 0x5A, 0xC3, 0x3C, 0x99, 0x42],
+        )
+        .unwrap();
+
+        client.send_file("1@s.whatsapp.net", &jpeg).unwrap();
+        client.send_file("1@s.whatsapp.net", &blob).unwrap();
+
+        let images = mock.calls("wm_send_image_ex");
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0][2], "image/jpeg");
+        let media = mock.calls("wm_send_media");
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0][1], "document");
+        assert_eq!(media[0][3], "application/octet-stream");
+        assert_eq!(media[0][4], "blob.jpg");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
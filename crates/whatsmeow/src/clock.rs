@@ -0,0 +1,36 @@
+//! Local clock skew relative to WhatsApp's servers
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Difference between the local clock and WhatsApp's
+///
+/// Positive when the local clock is ahead. See
+/// [`WhatsApp::clock_offset`](crate::WhatsApp::clock_offset).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ClockOffset {
+    millis: i64,
+}
+
+impl ClockOffset {
+    pub(crate) fn from_millis(millis: i64) -> Self {
+        Self { millis }
+    }
+
+    /// Signed offset in milliseconds, positive when the local clock is ahead
+    pub fn as_millis(&self) -> i64 {
+        self.millis
+    }
+
+    /// Size of the offset regardless of direction
+    pub fn magnitude(&self) -> Duration {
+        Duration::from_millis(self.millis.unsigned_abs())
+    }
+
+    /// Whether the local clock runs ahead of the server
+    pub fn is_local_ahead(&self) -> bool {
+        self.millis > 0
+    }
+}
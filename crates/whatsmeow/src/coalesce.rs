@@ -0,0 +1,107 @@
+//! Debouncing of bursty presence updates
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::events::Event;
+
+/// Holds presence updates back so each JID yields at most one per window
+///
+/// The first update for a JID opens its window; later ones within it
+/// replace the held update, and the latest is released when it closes.
+pub(crate) struct PresenceCoalescer {
+    window: Duration,
+    pending: HashMap<String, Pending>,
+}
+
+struct Pending {
+    due: Instant,
+    event: Event,
+    raw: Option<Value>,
+}
+
+impl PresenceCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Take a presence update, or hand any other event straight back
+    pub fn offer(&mut self, event: Event, raw: Option<Value>) -> Option<(Event, Option<Value>)> {
+        let Event::Presence(presence) = &event else {
+            return Some((event, raw));
+        };
+        let due = Instant::now() + self.window;
+        self.pending
+            .entry(presence.from.clone())
+            .and_modify(|held| {
+                held.event = event.clone();
+                held.raw = raw.clone();
+            })
+            .or_insert(Pending { due, event, raw });
+        None
+    }
+
+    /// Remove and return the updates whose window has closed, oldest first
+    pub fn take_due(&mut self, now: Instant) -> Vec<(Event, Option<Value>)> {
+        let closed: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, held)| held.due <= now)
+            .map(|(jid, _)| jid.clone())
+            .collect();
+        let mut due: Vec<Pending> = closed
+            .iter()
+            .filter_map(|jid| self.pending.remove(jid))
+            .collect();
+        due.sort_by_key(|held| held.due);
+        due.into_iter().map(|held| (held.event, held.raw)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::WhatsApp;
+    use crate::events::Event;
+    use crate::mock_ffi::{self, Mock};
+
+    #[tokio::test]
+    async fn burst_of_updates_is_dispatched_once() {
+        let client = WhatsApp::connect(mock_ffi::db_path("coalesce-burst"))
+            .coalesce_presence(std::time::Duration::from_millis(50))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        for unavailable in [false, true, true] {
+            mock.push_event(
+                "presence",
+                json!({ "From": "1@s.whatsapp.net", "Unavailable": unavailable, "LastSeen": "" }),
+            );
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        mock.push_event("connected", Value::Null);
+
+        let Event::Presence(presence) = mock_ffi::next_event(&mut events).await else {
+            panic!("expected the coalesced presence update");
+        };
+        assert!(presence.unavailable);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+
+        client.disconnect();
+        run.await.unwrap().unwrap();
+    }
+}
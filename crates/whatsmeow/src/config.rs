@@ -0,0 +1,143 @@
+//! Runtime behaviour configured through the builder
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::calls::CallOfferEvent;
+use crate::dispatch::EventOrder;
+use crate::events::{Event, Jid, MessageEvent, MessageType};
+use crate::handlers::BoxFuture;
+use crate::reconnect::Backoff;
+
+/// Recipients [`send_batch`](crate::WhatsApp::send_batch) works on at once unless configured
+const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+/// Predicate deciding whether an incoming message is acted on
+pub(crate) type MessagePredicate = Arc<dyn Fn(&MessageEvent) -> bool + Send + Sync + 'static>;
+
+/// Predicate deciding whether an event is delivered to handlers and streams
+pub(crate) type EventPredicate = Arc<dyn Fn(&Event) -> bool + Send + Sync + 'static>;
+
+/// Outgoing middleware: rewrites a message and its recipient, or blocks it with `None`
+pub(crate) type SendMiddleware = Arc<
+    dyn Fn(Jid, MessageType) -> BoxFuture<'static, Option<(Jid, MessageType)>>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+/// Incoming middleware: rewrites an event before dispatch, or drops it with `None`
+pub(crate) type DispatchMiddleware =
+    Arc<dyn Fn(Event) -> BoxFuture<'static, Option<Event>> + Send + Sync + 'static>;
+
+/// Options collected by [`WhatsAppBuilder`](crate::WhatsAppBuilder) and applied on build
+#[derive(Clone, Default)]
+pub(crate) struct ClientConfig {
+    /// Send read receipts for incoming messages before dispatch
+    pub auto_read: bool,
+    /// Optional filter limiting which messages are auto-marked read
+    pub auto_read_filter: Option<MessagePredicate>,
+    /// Check user recipients are registered before sending
+    pub verify_recipients: bool,
+    /// Run in order on every message before it is prepared and sent
+    pub before_send: Vec<SendMiddleware>,
+    /// Drops events before they reach handlers and the broadcast bus
+    pub event_filter: Option<EventPredicate>,
+    /// Drop messages sent by this account before they reach handlers and streams
+    pub skip_own_messages: bool,
+    /// Run in order on every delivered event before handlers and streams see it
+    pub before_dispatch: Vec<DispatchMiddleware>,
+    /// Deliver at most one presence update per JID per window
+    pub coalesce_presence: Option<Duration>,
+    /// Run handlers on this many pooled workers instead of one task per call
+    pub dispatch_workers: Option<usize>,
+    /// Whether handlers or streams are given each event first
+    pub event_order: EventOrder,
+    /// Recipients worked on at once during [`send_batch`](crate::WhatsApp::send_batch)
+    pub batch_concurrency: Option<usize>,
+    /// Decline incoming calls automatically
+    pub auto_reject_calls: bool,
+    /// Callers exempt from `auto_reject_calls`
+    pub call_allowlist: Vec<Jid>,
+    /// Sidecar file keeping the seen-message set across restarts
+    pub dedup_path: Option<PathBuf>,
+    /// Country code completing national numbers in [`parse_phone`](crate::WhatsApp::parse_phone)
+    pub default_country_code: Option<String>,
+    /// Contacts whose presence is subscribed to after every connect
+    pub presence_subscriptions: Vec<Jid>,
+    /// Delays between attempts after an unexpected disconnect
+    pub reconnect: Backoff,
+    /// Keep each event's source JSON for [`events_with_raw`](crate::WhatsApp::events_with_raw)
+    pub retain_raw_json: bool,
+    /// Minimum spacing between event loop iterations (debugging aid)
+    pub poll_throttle: Option<Duration>,
+}
+
+impl ClientConfig {
+    /// Configured batch concurrency, or the default
+    pub fn batch_limit(&self) -> usize {
+        self.batch_concurrency.unwrap_or(DEFAULT_BATCH_CONCURRENCY)
+    }
+
+    /// Whether an event passes the user's event filter
+    pub fn should_deliver(&self, event: &Event) -> bool {
+        if self.skip_own_messages
+            && let Event::Message(msg) = event
+            && msg.info.is_from_me
+        {
+            return false;
+        }
+        self.event_filter.as_ref().is_none_or(|f| f(event))
+    }
+
+    /// Run an event through the `before_dispatch` chain
+    pub async fn before_dispatch(&self, event: Event) -> Option<Event> {
+        let mut event = event;
+        for middleware in &self.before_dispatch {
+            event = middleware(event).await?;
+        }
+        Some(event)
+    }
+
+    /// Whether an incoming call should be auto-rejected
+    pub fn should_reject_call(&self, call: &CallOfferEvent) -> bool {
+        if !self.auto_reject_calls {
+            return false;
+        }
+        let from = Jid::new(call.from.as_str()).without_device();
+        let creator = Jid::new(call.call_creator.as_str()).without_device();
+        !self
+            .call_allowlist
+            .iter()
+            .any(|jid| *jid == from || *jid == creator)
+    }
+
+    /// Whether an incoming message should be auto-marked read
+    pub fn should_auto_read(&self, msg: &MessageEvent) -> bool {
+        self.auto_read
+            && !msg.info.is_from_me
+            && self.auto_read_filter.as_ref().is_none_or(|f| f(msg))
+    }
+}
+
+/// How the bridge opens the session database, passed to `wm_client_new_ex`
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct StoreOptions {
+    /// Use sqlite's write-ahead log instead of the rollback journal
+    pub wal_mode: bool,
+    /// Create a missing parent directory (handled on the Rust side)
+    #[serde(skip)]
+    pub create_db_dir: bool,
+}
+
+impl Default for StoreOptions {
+    fn default() -> Self {
+        Self {
+            wal_mode: false,
+            create_db_dir: true,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Aggregated view of every contact this client has learned about
+
+use std::collections::HashMap;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::contacts::ContactInfo;
+use crate::events::{Jid, MessageEvent};
+
+/// What is known about one contact, merged across sources
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KnownContact {
+    pub jid: Jid,
+    /// Names from the contact store and the latest push name seen
+    pub info: ContactInfo,
+    /// Result of the last [`WhatsApp::is_on_whatsapp`](crate::WhatsApp::is_on_whatsapp)
+    /// check, `None` if never checked
+    pub on_whatsapp: Option<bool>,
+}
+
+impl KnownContact {
+    fn new(jid: Jid) -> Self {
+        Self {
+            jid,
+            info: ContactInfo::default(),
+            on_whatsapp: None,
+        }
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        [
+            &self.info.full_name,
+            &self.info.first_name,
+            &self.info.push_name,
+            &self.info.business_name,
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+    }
+}
+
+/// Every contact seen on messages, contact lookups and registration checks
+///
+/// Unlike the bounded name cache behind
+/// [`cached_name`](crate::WhatsApp::cached_name), nothing is evicted; it
+/// lasts as long as the client. Get it from
+/// [`WhatsApp::contact_book`](crate::WhatsApp::contact_book).
+#[derive(Default)]
+pub struct ContactBook {
+    contacts: RwLock<HashMap<Jid, KnownContact>>,
+}
+
+impl ContactBook {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Everything known about a contact (device suffixes are ignored)
+    pub fn lookup(&self, jid: &Jid) -> Option<KnownContact> {
+        self.contacts.read().get(&jid.without_device()).cloned()
+    }
+
+    /// Contacts whose name contains `query`, ignoring case, ordered by JID
+    pub fn search(&self, query: &str) -> Vec<KnownContact> {
+        let query = query.to_lowercase();
+        let mut found: Vec<_> = self
+            .contacts
+            .read()
+            .values()
+            .filter(|c| c.names().any(|n| n.to_lowercase().contains(&query)))
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.jid.as_str().cmp(b.jid.as_str()));
+        found
+    }
+
+    /// All known contacts, ordered by JID
+    pub fn all(&self) -> Vec<KnownContact> {
+        let mut all: Vec<_> = self.contacts.read().values().cloned().collect();
+        all.sort_by(|a, b| a.jid.as_str().cmp(b.jid.as_str()));
+        all
+    }
+
+    pub fn len(&self) -> usize {
+        self.contacts.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contacts.read().is_empty()
+    }
+
+    /// Record the sender's push name from an incoming message
+    pub(crate) fn observe(&self, msg: &MessageEvent) {
+        if msg.info.push_name.is_empty() || msg.info.is_from_me {
+            return;
+        }
+        let push_name = msg.info.push_name.clone();
+        self.update(Jid::new(&msg.info.sender), |c| {
+            c.info.push_name = Some(push_name);
+        });
+    }
+
+    /// Merge a contact store lookup; present names overwrite older ones
+    pub(crate) fn merge_info(&self, jid: Jid, info: &ContactInfo) {
+        self.update(jid, |c| {
+            c.info.found |= info.found;
+            for (slot, name) in [
+                (&mut c.info.first_name, &info.first_name),
+                (&mut c.info.full_name, &info.full_name),
+                (&mut c.info.push_name, &info.push_name),
+                (&mut c.info.business_name, &info.business_name),
+            ] {
+                if name.is_some() {
+                    slot.clone_from(name);
+                }
+            }
+        });
+    }
+
+    /// Record whether a number has a WhatsApp account
+    pub(crate) fn set_on_whatsapp(&self, jid: Jid, registered: bool) {
+        self.update(jid, |c| c.on_whatsapp = Some(registered));
+    }
+
+    fn update(&self, jid: Jid, f: impl FnOnce(&mut KnownContact)) {
+        let key = jid.without_device();
+        let mut contacts = self.contacts.write();
+        f(contacts
+            .entry(key.clone())
+            .or_insert_with(|| KnownContact::new(key)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi;
+
+    fn message(sender: &str, push_name: &str) -> MessageEvent {
+        let mut data = mock_ffi::text_message("M1", sender, sender, "hi");
+        data["Info"]["PushName"] = push_name.into();
+        serde_json::from_value(data).unwrap()
+    }
+
+    #[test]
+    fn sources_merge_into_one_searchable_contact() {
+        let book = ContactBook::new();
+        let alice = Jid::from("1@s.whatsapp.net");
+
+        book.observe(&message("1:5@s.whatsapp.net", "Ally"));
+        book.merge_info(
+            alice.clone(),
+            &ContactInfo {
+                found: true,
+                full_name: Some("Alice Smith".into()),
+                ..ContactInfo::default()
+            },
+        );
+        book.set_on_whatsapp(alice.clone(), true);
+        book.observe(&message("2@s.whatsapp.net", "Bob"));
+
+        assert_eq!(book.len(), 2);
+        let known = book.lookup(&Jid::from("1:7@s.whatsapp.net")).unwrap();
+        assert_eq!(known.jid, alice);
+        assert_eq!(known.info.push_name.as_deref(), Some("Ally"));
+        assert_eq!(known.info.full_name.as_deref(), Some("Alice Smith"));
+        assert!(known.info.found);
+        assert_eq!(known.on_whatsapp, Some(true));
+
+        let found =
+            |query: &str| -> Vec<Jid> { book.search(query).into_iter().map(|c| c.jid).collect() };
+        assert_eq!(found("smith"), found("ALLY"));
+        assert_eq!(found("smith").len(), 1);
+        assert_eq!(found("smith")[0], alice);
+        assert!(book.search("carol").is_empty());
+    }
+
+    #[test]
+    fn a_lookup_without_names_keeps_the_known_ones() {
+        let book = ContactBook::new();
+        let jid = Jid::from("1@s.whatsapp.net");
+
+        book.observe(&message("1@s.whatsapp.net", "Ally"));
+        book.merge_info(jid.clone(), &ContactInfo::default());
+
+        assert_eq!(
+            book.lookup(&jid).unwrap().info.push_name.as_deref(),
+            Some("Ally")
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! In-memory contact name cache
+
+use std::collections::{HashMap, VecDeque};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{Jid, MessageEvent};
+
+/// Default maximum number of cached contacts
+const DEFAULT_CONTACT_CACHE_CAPACITY: usize = 4096;
+
+/// Contact details from the local contact store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactInfo {
+    /// Whether the store knows this contact at all
+    pub found: bool,
+    pub first_name: Option<String>,
+    /// Name from the phone's address book
+    pub full_name: Option<String>,
+    /// Name the contact set for themselves
+    pub push_name: Option<String>,
+    /// Verified business name
+    pub business_name: Option<String>,
+}
+
+impl ContactInfo {
+    /// Best available name: address book, then push name, then business name
+    pub fn best_name(&self) -> Option<&str> {
+        [
+            &self.full_name,
+            &self.first_name,
+            &self.push_name,
+            &self.business_name,
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .find(|name| !name.is_empty())
+    }
+}
+
+/// Bounded JID → push name cache populated from incoming messages
+pub(crate) struct ContactCache {
+    inner: Mutex<CacheState>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct CacheState {
+    names: HashMap<Jid, String>,
+    order: VecDeque<Jid>,
+}
+
+impl ContactCache {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(CacheState::default()),
+            capacity: DEFAULT_CONTACT_CACHE_CAPACITY,
+        }
+    }
+
+    /// Record the push name carried by a message, if any
+    pub fn observe(&self, msg: &MessageEvent) {
+        if msg.info.push_name.is_empty() || msg.info.is_from_me {
+            return;
+        }
+        self.insert(Jid::new(&msg.info.sender), msg.info.push_name.clone());
+    }
+
+    /// Insert or refresh a name, evicting the oldest entry when full
+    pub fn insert(&self, jid: Jid, name: String) {
+        let key = jid.without_device();
+        let mut state = self.inner.lock();
+
+        if let Some(existing) = state.names.get_mut(&key) {
+            *existing = name;
+            return;
+        }
+
+        while state.names.len() >= self.capacity {
+            match state.order.pop_front() {
+                Some(oldest) => {
+                    state.names.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        state.order.push_back(key.clone());
+        state.names.insert(key, name);
+    }
+
+    /// Look up the last known name for a JID
+    pub fn get(&self, jid: &Jid) -> Option<String> {
+        self.inner.lock().names.get(&jid.without_device()).cloned()
+    }
+}
+
+impl Default for ContactCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi;
+
+    fn message(id: &str, sender: &str, push_name: &str) -> MessageEvent {
+        let mut data = mock_ffi::text_message(id, sender, sender, "hi");
+        data["Info"]["PushName"] = push_name.into();
+        serde_json::from_value(data).unwrap()
+    }
+
+    #[test]
+    fn a_name_survives_later_messages_without_one() {
+        let cache = ContactCache::new();
+
+        cache.observe(&message("M1", "1@s.whatsapp.net", "Alice"));
+        cache.observe(&message("M2", "1@s.whatsapp.net", ""));
+
+        assert_eq!(
+            cache.get(&Jid::from("1:3@s.whatsapp.net")).as_deref(),
+            Some("Alice")
+        );
+    }
+
+    #[test]
+    fn the_oldest_name_is_evicted_when_full() {
+        let cache = ContactCache {
+            inner: Mutex::new(CacheState::default()),
+            capacity: 2,
+        };
+
+        for (jid, name) in [
+            ("1@s.whatsapp.net", "A"),
+            ("2@s.whatsapp.net", "B"),
+            ("3@s.whatsapp.net", "C"),
+        ] {
+            cache.insert(Jid::from(jid), name.into());
+        }
+
+        assert_eq!(cache.get(&Jid::from("1@s.whatsapp.net")), None);
+        assert_eq!(
+            cache.get(&Jid::from("3@s.whatsapp.net")).as_deref(),
+            Some("C")
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Duplicate message suppression
+
+use std::collections::{HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+
+use crate::events::MessageInfo;
+
+/// Message keys remembered (and persisted) at most
+const SEEN_CAPACITY: usize = 4096;
+
+/// New keys recorded between saves of the sidecar file
+const SAVE_INTERVAL: usize = 256;
+
+/// Bounded set of recently delivered messages
+///
+/// WhatsApp redelivers messages, notably right after a reconnect. With a
+/// sidecar file the set survives restarts, one `chat/id` key per line,
+/// oldest first.
+pub(crate) struct SeenMessages {
+    inner: Mutex<SeenState>,
+}
+
+#[derive(Default)]
+struct SeenState {
+    keys: HashSet<String>,
+    order: VecDeque<String>,
+    path: Option<PathBuf>,
+    unsaved: usize,
+}
+
+impl SeenMessages {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(SeenState::default()),
+        }
+    }
+
+    /// Load keys from `path` (if it exists) and save back to it from now on
+    pub fn restore(&self, path: &Path) -> std::io::Result<()> {
+        let mut state = self.inner.lock();
+        state.path = Some(path.to_path_buf());
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        for key in contents.lines().filter(|line| !line.is_empty()) {
+            state.remember(key.to_owned());
+        }
+        state.unsaved = 0;
+        Ok(())
+    }
+
+    /// Record a message, returning false if it was already seen
+    pub fn insert(&self, info: &MessageInfo) -> bool {
+        let key = format!("{}/{}", info.chat, info.id);
+        let mut state = self.inner.lock();
+        if !state.remember(key) {
+            return false;
+        }
+
+        state.unsaved += 1;
+        if state.unsaved >= SAVE_INTERVAL
+            && let Err(e) = state.save()
+        {
+            tracing::warn!(error = %e, "Failed to save seen messages");
+        }
+        true
+    }
+
+    /// Write pending keys to the sidecar file, if one is configured
+    pub fn flush(&self) {
+        let mut state = self.inner.lock();
+        if state.unsaved > 0
+            && let Err(e) = state.save()
+        {
+            tracing::warn!(error = %e, "Failed to save seen messages");
+        }
+    }
+}
+
+impl SeenState {
+    /// Insert a key, evicting the oldest when full
+    fn remember(&mut self, key: String) -> bool {
+        if self.keys.contains(&key) {
+            return false;
+        }
+        if self.order.len() == SEEN_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.keys.remove(&oldest);
+        }
+        self.keys.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+
+    /// Replace the sidecar file atomically so a crash never leaves it torn
+    fn save(&mut self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let tmp = path.with_extension("tmp");
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+        for key in &self.order {
+            writeln!(file, "{}", key)?;
+        }
+        file.into_inner()?.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        self.unsaved = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageEvent;
+    use crate::mock_ffi;
+
+    fn info(id: &str) -> MessageInfo {
+        let data = mock_ffi::text_message(id, "1@s.whatsapp.net", "1@s.whatsapp.net", "hi");
+        serde_json::from_value::<MessageEvent>(data).unwrap().info
+    }
+
+    #[test]
+    fn messages_seen_before_a_restart_stay_deduplicated() {
+        let path = mock_ffi::db_path("seen-restart").with_extension("seen");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let before = SeenMessages::new();
+        before.restore(&path).unwrap();
+        assert!(before.insert(&info("M1")));
+        assert!(!before.insert(&info("M1")));
+        before.flush();
+
+        let after = SeenMessages::new();
+        after.restore(&path).unwrap();
+
+        assert!(!after.insert(&info("M1")));
+        assert!(after.insert(&info("M2")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn the_oldest_keys_are_evicted_when_full() {
+        let seen = SeenMessages::new();
+        for i in 0..=SEEN_CAPACITY {
+            assert!(seen.insert(&info(&format!("M{i}"))));
+        }
+
+        assert!(seen.insert(&info("M0")));
+        assert!(!seen.insert(&info(&format!("M{SEEN_CAPACITY}"))));
+    }
+}
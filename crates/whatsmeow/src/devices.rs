@@ -0,0 +1,78 @@
+//! Linked device types
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::Jid;
+
+/// A device linked to the logged-in account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkedDevice {
+    /// Full device JID (e.g. "1234567890:3@s.whatsapp.net")
+    pub jid: Jid,
+    /// Device index (0 is the primary phone)
+    pub device: u16,
+    /// Whether this is the primary phone
+    pub is_primary: bool,
+    /// Whether this is the device this client is running as
+    pub is_self: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    #[tokio::test]
+    async fn linked_devices_are_parsed_from_the_bridge() {
+        let (client, mock) = mock_ffi::client("linked-devices").await;
+        mock.reply(
+            "wm_get_linked_devices",
+            Reply::ok(
+                br#"[
+                    {"jid":"1@s.whatsapp.net","device":0,"is_primary":true,"is_self":false},
+                    {"jid":"1:3@s.whatsapp.net","device":3,"is_primary":false,"is_self":true}
+                ]"#
+                .to_vec(),
+            ),
+        );
+
+        let devices = client.linked_devices().unwrap();
+
+        assert_eq!(
+            devices,
+            [
+                LinkedDevice {
+                    jid: Jid::from("1@s.whatsapp.net"),
+                    device: 0,
+                    is_primary: true,
+                    is_self: false,
+                },
+                LinkedDevice {
+                    jid: Jid::from("1:3@s.whatsapp.net"),
+                    device: 3,
+                    is_primary: false,
+                    is_self: true,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn no_linked_devices_is_an_empty_list() {
+        let (client, _mock) = mock_ffi::client("linked-devices-empty").await;
+
+        assert!(client.linked_devices().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unlink_passes_the_device_jid_through() {
+        let (client, mock) = mock_ffi::client("unlink-device").await;
+
+        client.unlink_device("1:3@s.whatsapp.net").unwrap();
+
+        assert_eq!(
+            mock.calls("wm_remove_device"),
+            [vec!["1:3@s.whatsapp.net".to_string()]]
+        );
+    }
+}
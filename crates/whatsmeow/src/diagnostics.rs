@@ -0,0 +1,102 @@
+//! Diagnostics snapshot for bug reports
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::allocator::MemoryStats;
+use crate::clock::ClockOffset;
+use crate::events::Jid;
+use crate::handlers::HandlerStats;
+use crate::metrics::Metrics;
+
+/// Everything useful to attach to a bug report, in one serializable value
+///
+/// ```no_run
+/// # fn demo(client: &whatsmeow::WhatsApp) {
+/// let report = serde_json::to_string_pretty(&client.diagnostics()).unwrap();
+/// println!("{report}");
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    /// Version of this crate
+    pub version: &'static str,
+    /// Extracted bridge library, when built with `embed-dll`
+    pub library_path: Option<PathBuf>,
+    pub connected: bool,
+    pub paused: bool,
+    /// Logged-in device, `None` before pairing or if the lookup failed
+    pub own_jid: Option<Jid>,
+    /// Local clock minus WhatsApp's, `None` until a live message arrives
+    pub clock_offset: Option<ClockOffset>,
+    pub metrics: Metrics,
+    pub handlers: HandlerStats,
+    /// Rust-side heap usage (all zero without the `track-allocations` feature)
+    pub memory: MemoryStats,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::Value;
+
+    use crate::mock_ffi::{self, Mock, Reply};
+    use crate::{Event, WhatsApp};
+
+    #[tokio::test]
+    async fn the_snapshot_reflects_a_reconnect_sequence() {
+        let client = WhatsApp::connect(mock_ffi::db_path("diagnostics"))
+            .reconnect_base(Duration::from_millis(10))
+            .on_message(|_| async {})
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.reply(
+            "wm_get_own_jid",
+            Reply::ok(b"15550001111:3@s.whatsapp.net".to_vec()),
+        );
+        let mut events = client.events();
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        mock.push_event("connected", Value::Null);
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "1@s.whatsapp.net", "hi"),
+        );
+        mock.push_event("disconnected", Value::Null);
+        mock.wait_for_calls("wm_client_connect", 2).await;
+        mock.push_event("connected", Value::Null);
+        for _ in 0..3 {
+            mock_ffi::next_event(&mut events).await;
+        }
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        for _ in 0..100 {
+            if client.metrics().reconnects == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let report = client.diagnostics();
+
+        assert!(report.connected);
+        assert!(!report.paused);
+        assert_eq!(
+            report.own_jid.as_ref().map(|jid| jid.as_str()),
+            Some("15550001111:3@s.whatsapp.net")
+        );
+        assert_eq!(report.metrics.events_received, 4);
+        assert_eq!(report.metrics.reconnects, 1);
+        assert_eq!(report.handlers.message, 1);
+        assert_eq!(report.version, env!("CARGO_PKG_VERSION"));
+        assert!(serde_json::to_value(&report).unwrap()["metrics"].is_object());
+        client.disconnect();
+    }
+}
@@ -0,0 +1,140 @@
+//! Scheduling of handler tasks
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::handlers::BoxFuture;
+
+type Job = BoxFuture<'static, ()>;
+
+/// Whether callback handlers or event streams are given an event first
+///
+/// Each side always sees events in arrival order. Handlers are only
+/// *started* in order, though: they run as separate tasks, so a slow
+/// handler for one event can finish after a fast one for the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventOrder {
+    /// Schedule handlers, then publish to streams (the default)
+    #[default]
+    HandlersFirst,
+    /// Publish to streams, then schedule handlers
+    StreamsFirst,
+}
+
+/// Runs handler tasks either as free tokio tasks or on a fixed worker pool
+pub(crate) enum Dispatcher {
+    /// One `tokio::spawn` per handler call (unbounded)
+    Spawn,
+    /// Bounded queue drained by a fixed number of workers
+    Pool(mpsc::Sender<Job>),
+}
+
+impl Dispatcher {
+    /// Create a dispatcher, starting `workers` pool tasks if set
+    pub fn new(workers: Option<usize>) -> Self {
+        let Some(workers) = workers else {
+            return Self::Spawn;
+        };
+        let workers = workers.max(1);
+
+        let (tx, rx) = mpsc::channel::<Job>(workers);
+        let rx = Arc::new(Mutex::new(rx));
+
+        for _ in 0..workers {
+            let rx = rx.clone();
+            tokio::spawn(async move {
+                loop {
+                    let job = { rx.lock().await.recv().await };
+                    let Some(job) = job else { break };
+
+                    // Keep the worker alive if a handler panics
+                    if AssertUnwindSafe(job).catch_unwind().await.is_err() {
+                        tracing::error!("Event handler panicked");
+                    }
+                }
+            });
+        }
+
+        Self::Pool(tx)
+    }
+
+    /// Schedule jobs; waits for queue space when the pool is saturated
+    pub async fn dispatch(&self, jobs: Vec<Job>) {
+        match self {
+            Self::Spawn => {
+                for job in jobs {
+                    tokio::spawn(job);
+                }
+            }
+            Self::Pool(tx) => {
+                for job in jobs {
+                    if tx.send(job).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn the_pool_runs_exactly_its_worker_count_at_once() {
+        let dispatcher = Dispatcher::new(Some(3));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        let jobs = (0..12)
+            .map(|_| {
+                let (active, peak, done) = (active.clone(), peak.clone(), done.clone());
+                Box::pin(async move {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    done.fetch_add(1, Ordering::SeqCst);
+                }) as Job
+            })
+            .collect();
+        dispatcher.dispatch(jobs).await;
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while done.load(Ordering::SeqCst) < 12 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn a_panicking_job_leaves_the_worker_running() {
+        let dispatcher = Dispatcher::new(Some(1));
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        dispatcher
+            .dispatch(vec![
+                Box::pin(async { panic!("handler failed") }),
+                Box::pin(async move {
+                    let _ = tx.send(());
+                }),
+            ])
+            .await;
+
+        tokio::time::timeout(Duration::from_secs(1), rx)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -86,6 +86,18 @@ mod inner {
 #[cfg(feature = "embed-dll")]
 pub use inner::ensure_dll_extracted;
 
+/// Where the bridge library was extracted to, if embedded
+#[cfg(feature = "embed-dll")]
+pub(crate) fn library_path() -> Option<std::path::PathBuf> {
+    Some(inner::get_dll_path().clone())
+}
+
+/// The bridge is loaded from the system path when not embedded
+#[cfg(not(feature = "embed-dll"))]
+pub(crate) fn library_path() -> Option<std::path::PathBuf> {
+    None
+}
+
 /// No-op when embed-dll feature is disabled
 #[cfg(not(feature = "embed-dll"))]
 pub fn ensure_dll_extracted() {
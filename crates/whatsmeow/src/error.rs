@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::events::Jid;
+
 /// Main error type for WhatsApp operations
 #[derive(Debug, Error)]
 pub enum Error {
@@ -26,9 +28,56 @@ pub enum Error {
     #[error("Send failed: {0}")]
     Send(String),
 
+    #[error("Validation failed: {0}")]
+    Validation(String),
+
+    #[error(transparent)]
+    InvalidJid(#[from] JidError),
+
+    #[error(transparent)]
+    InvalidPhone(#[from] PhoneError),
+
+    #[error("Send to {jid} blocked by a before_send middleware")]
+    SendBlocked { jid: Jid },
+
+    #[error("{jid} is not on WhatsApp")]
+    RecipientNotOnWhatsApp { jid: Jid },
+
+    #[error("No presence update from {jid} in time")]
+    PresenceTimeout { jid: Jid },
+
+    #[error("Failed to fetch {url}: {reason}")]
+    Fetch { url: String, reason: String },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+/// A string that could not be parsed as a JID
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JidError {
+    #[error("Invalid phone number: {0:?}")]
+    InvalidPhone(String),
+
+    #[error("Malformed JID: {0:?}")]
+    Malformed(String),
+}
+
+/// A string that could not be parsed as a [`PhoneNumber`](crate::PhoneNumber)
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PhoneError {
+    #[error("Phone number is empty")]
+    Empty,
+
+    #[error("Phone number contains {0:?}")]
+    InvalidCharacter(char),
+
+    #[error("Phone number {0:?} is too short")]
+    TooShort(String),
+
+    #[error("Phone number {0:?} is longer than 15 digits")]
+    TooLong(String),
+}
+
 /// Convenient Result type alias
 pub type Result<T> = std::result::Result<T, Error>;
@@ -3,26 +3,49 @@
 use tokio::sync::broadcast;
 
 use crate::events::Event;
-use crate::stream::EventStream;
+use crate::stream::{EnvelopeStream, EventEnvelope, EventStream};
 
 const EVENT_CHANNEL_CAPACITY: usize = 256;
 
+/// Fan-out of delivered events to every [`EventStream`]
+///
+/// Owned by the client for its whole life, independently of the bridge
+/// handle. The bus survives disconnects, reconnects and handle reopens, so
+/// subscribers are never cut off by a dropped connection; a stream only
+/// ends once the client itself is dropped.
 pub(crate) struct EventBus {
     tx: broadcast::Sender<Event>,
+    /// Only fed when raw JSON is retained
+    raw_tx: broadcast::Sender<EventEnvelope>,
 }
 
 impl EventBus {
     pub fn new() -> Self {
         let (tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
-        Self { tx }
+        let (raw_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { tx, raw_tx }
     }
 
     pub fn emit(&self, event: Event) {
         let _ = self.tx.send(event);
     }
 
+    /// Publish an event with its source JSON, skipping the clone if nobody listens
+    pub fn emit_raw(&self, event: &Event, raw: serde_json::Value) {
+        if self.raw_tx.receiver_count() > 0 {
+            let _ = self.raw_tx.send(EventEnvelope {
+                event: event.clone(),
+                raw,
+            });
+        }
+    }
+
     pub fn subscribe(&self) -> EventStream {
-        EventStream::new(self.tx.subscribe())
+        EventStream::new(&self.tx)
+    }
+
+    pub fn subscribe_raw(&self) -> EnvelopeStream {
+        EnvelopeStream::new(&self.raw_tx)
     }
 }
 
@@ -36,6 +59,45 @@ impl Clone for EventBus {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
+            raw_tx: self.raw_tx.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::events::Event;
+    use crate::mock_ffi;
+
+    #[tokio::test]
+    async fn stream_keeps_receiving_across_disconnect_and_reconnect() {
+        let (client, mock) = mock_ffi::client("bus-survives-reconnect").await;
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        mock.push_event("connected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        mock.push_event("disconnected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Disconnected
+        ));
+
+        client.reconnect().await.unwrap();
+        mock.push_event("connected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        assert!(!events.is_closed());
+
+        client.disconnect();
+        run.await.unwrap().unwrap();
+    }
+}
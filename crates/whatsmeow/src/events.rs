@@ -2,7 +2,23 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Borrow;
 use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::appstate::{AppStateSyncedEvent, ChatDeletedEvent};
+use crate::calls::CallOfferEvent;
+use crate::error::JidError;
+use crate::history_sync::HistoryChunk;
+use crate::interactive::{Button, InteractiveResponseEvent, ListSection};
+use crate::parsed::ParsedMessage;
+#[cfg(feature = "payments")]
+use crate::payments::PaymentEvent;
+use crate::phone::PhoneNumber;
+use crate::system::SystemEvent;
+use crate::template::check_params;
+use crate::timestamp::parse_rfc3339;
 
 /// WhatsApp JID (Jabber ID) - identifies users, groups, and broadcasts
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -15,6 +31,9 @@ impl Jid {
     }
 
     /// Create a user JID from a phone number (adds @s.whatsapp.net)
+    ///
+    /// The number is not checked; pass a [`PhoneNumber`](crate::PhoneNumber)
+    /// (or use `str::parse`) to reject malformed input.
     pub fn user(phone: impl AsRef<str>) -> Self {
         let phone = phone.as_ref().trim_start_matches('+');
         Self(format!("{}@s.whatsapp.net", phone))
@@ -25,6 +44,11 @@ impl Jid {
         Self(format!("{}@g.us", group_id.as_ref()))
     }
 
+    /// Create a broadcast list JID (adds @broadcast)
+    pub fn broadcast(list_id: impl AsRef<str>) -> Self {
+        Self(format!("{}@broadcast", list_id.as_ref()))
+    }
+
     /// Get the raw JID string
     pub fn as_str(&self) -> &str {
         &self.0
@@ -39,6 +63,111 @@ impl Jid {
     pub fn is_user(&self) -> bool {
         self.0.ends_with("@s.whatsapp.net")
     }
+
+    /// Check if this is a LID (server-assigned identity) JID
+    pub fn is_lid(&self) -> bool {
+        self.0.ends_with("@lid")
+    }
+
+    /// Strip the device suffix (e.g. "123:5@s.whatsapp.net" -> "123@s.whatsapp.net")
+    pub fn without_device(&self) -> Jid {
+        match self.0.split_once('@') {
+            Some((user, server)) => {
+                let user = user.split_once(':').map_or(user, |(u, _)| u);
+                Jid(format!("{}@{}", user, server))
+            }
+            None => self.clone(),
+        }
+    }
+
+    /// Parse a comma- or whitespace-separated list of JIDs and/or phone numbers
+    ///
+    /// Empty entries are skipped; the first invalid entry fails the whole list.
+    ///
+    /// ```
+    /// use whatsmeow::Jid;
+    ///
+    /// let jids = Jid::parse_list("+1234567890, 120363@g.us").unwrap();
+    /// assert_eq!(jids, [Jid::user("1234567890"), Jid::group("120363")]);
+    /// ```
+    pub fn parse_list(s: &str) -> Result<Vec<Jid>, JidError> {
+        s.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromStr for Jid {
+    type Err = JidError;
+
+    /// Parse a full JID, or a bare phone number routed through [`Jid::user`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        match s.split_once('@') {
+            Some((user, server)) => {
+                if user.is_empty()
+                    || server.is_empty()
+                    || server.contains('@')
+                    || s.chars().any(char::is_whitespace)
+                {
+                    return Err(JidError::Malformed(s.to_string()));
+                }
+                Ok(Jid::new(s))
+            }
+            None => PhoneNumber::parse(s)
+                .map(Jid::from)
+                .map_err(|_| JidError::InvalidPhone(s.to_string())),
+        }
+    }
+}
+
+/// WhatsApp message ID
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MessageId(String);
+
+impl MessageId {
+    /// Wrap a raw message ID
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Get the raw ID string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<String> for MessageId {
+    fn from(s: String) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<&str> for MessageId {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl AsRef<str> for MessageId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Look up `MessageId`-keyed maps and sets by `&str`
+impl Borrow<str> for MessageId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
 }
 
 impl fmt::Display for Jid {
@@ -90,7 +219,11 @@ impl MediaSource {
         MediaSource::LocalPath { path: path.into() }
     }
 
-    /// Create from a URL
+    /// Create from an http(s) URL
+    ///
+    /// Images from a URL are downloaded by the bridge (up to 16 MiB), so the
+    /// bytes never pass through this process. Other message types can't load
+    /// URLs yet and fail to send.
     pub fn url(url: impl Into<String>) -> Self {
         MediaSource::RemoteUrl { url: url.into() }
     }
@@ -218,7 +351,72 @@ pub enum MessageType {
         /// Optional caption
         caption: Option<String>,
     },
-    // Future: Video, Document, Audio, Location, Contact, etc.
+    /// Sticker message (static or animated WebP)
+    Sticker {
+        /// Sticker source (must be WebP data)
+        source: MediaSource,
+    },
+    /// Text with up to [`MAX_BUTTONS`](crate::MAX_BUTTONS) quick-reply buttons
+    Buttons { body: String, buttons: Vec<Button> },
+    /// Text with a button opening a single-select list menu
+    List {
+        body: String,
+        /// Label of the button that opens the menu
+        button_text: String,
+        sections: Vec<ListSection>,
+    },
+    /// Pre-approved business template, filled with positional parameters
+    Template {
+        namespace: String,
+        name: String,
+        params: Vec<String>,
+    },
+    /// Video message
+    Video {
+        /// Video source (file, URL, base64, or raw bytes)
+        source: MediaSource,
+        /// MIME type (auto-detected if None)
+        mime_type: Option<String>,
+        /// Optional caption
+        caption: Option<String>,
+    },
+    /// Audio message, shown as a playable clip
+    Audio {
+        /// Audio source (file, URL, base64, or raw bytes)
+        source: MediaSource,
+        /// MIME type (auto-detected if None)
+        mime_type: Option<String>,
+    },
+    /// Document message, shown as a downloadable file
+    Document {
+        /// Document source (file, URL, base64, or raw bytes)
+        source: MediaSource,
+        /// MIME type (auto-detected if None)
+        mime_type: Option<String>,
+        /// File name shown to the recipient
+        file_name: String,
+        /// Optional caption
+        caption: Option<String>,
+    },
+    /// 2–30 images grouped as one album, see [`WhatsApp::send_album`](crate::WhatsApp::send_album)
+    ///
+    /// Only `send_album` sends this; other send methods reject it.
+    Album {
+        images: Vec<MediaSource>,
+        /// Attached to the first image
+        caption: Option<String>,
+    },
+    /// Request for payment (experimental, see the `payments` feature)
+    #[cfg(feature = "payments")]
+    PaymentRequest {
+        /// Amount in thousandths of the currency unit (12.50 is `12_500`)
+        amount_1000: u64,
+        /// ISO 4217 currency code, e.g. `"INR"`
+        currency: String,
+        /// Optional note shown with the request
+        note: Option<String>,
+    },
+    // Future: Location, Contact, etc.
 }
 
 impl MessageType {
@@ -270,6 +468,192 @@ impl MessageType {
         }
     }
 
+    /// Create a sticker message from WebP data
+    pub fn sticker(source: impl Into<MediaSource>) -> Self {
+        MessageType::Sticker {
+            source: source.into(),
+        }
+    }
+
+    /// Create a video message with auto-detected MIME type
+    pub fn video(source: impl Into<MediaSource>) -> Self {
+        MessageType::Video {
+            source: source.into(),
+            mime_type: None,
+            caption: None,
+        }
+    }
+
+    /// Create an audio message with auto-detected MIME type
+    pub fn audio(source: impl Into<MediaSource>) -> Self {
+        MessageType::Audio {
+            source: source.into(),
+            mime_type: None,
+        }
+    }
+
+    /// Create a document message with auto-detected MIME type
+    pub fn document(source: impl Into<MediaSource>, file_name: impl Into<String>) -> Self {
+        MessageType::Document {
+            source: source.into(),
+            mime_type: None,
+            file_name: file_name.into(),
+            caption: None,
+        }
+    }
+
+    /// Read a file and pick the message type from its contents
+    ///
+    /// Images, videos and audio are recognised by their file signature;
+    /// anything else is sent as a document named after the file.
+    pub fn file(path: impl AsRef<std::path::Path>) -> Result<Self, crate::Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| crate::Error::Send(format!("Failed to load media: {}", e)))?;
+        let mime = MediaSource::detect_mime_from_signature(&data);
+        let source = MediaSource::Bytes { data };
+
+        Ok(match mime.split('/').next() {
+            Some("image") => MessageType::Image {
+                source,
+                mime_type: Some(mime),
+                caption: None,
+            },
+            Some("video") => MessageType::Video {
+                source,
+                mime_type: Some(mime),
+                caption: None,
+            },
+            Some("audio") => MessageType::Audio {
+                source,
+                mime_type: Some(mime),
+            },
+            _ => MessageType::Document {
+                source,
+                mime_type: Some(mime),
+                file_name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "file".into()),
+                caption: None,
+            },
+        })
+    }
+
+    /// Create a payment request for `amount_1000` thousandths of `currency`
+    #[cfg(feature = "payments")]
+    pub fn payment_request(amount_1000: u64, currency: impl Into<String>) -> Self {
+        MessageType::PaymentRequest {
+            amount_1000,
+            currency: currency.into(),
+            note: None,
+        }
+    }
+
+    /// Create a message with quick-reply buttons
+    pub fn buttons(body: impl Into<String>, buttons: Vec<Button>) -> Self {
+        MessageType::Buttons {
+            body: body.into(),
+            buttons,
+        }
+    }
+
+    /// Create a message with a list menu opened by `button_text`
+    pub fn list(
+        body: impl Into<String>,
+        button_text: impl Into<String>,
+        sections: Vec<ListSection>,
+    ) -> Self {
+        MessageType::List {
+            body: body.into(),
+            button_text: button_text.into(),
+            sections,
+        }
+    }
+
+    /// Create a template message
+    pub fn template(
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        params: Vec<String>,
+    ) -> Self {
+        MessageType::Template {
+            namespace: namespace.into(),
+            name: name.into(),
+            params,
+        }
+    }
+
+    /// Create a template message, checking `params` against the approved
+    /// `body`'s `{{1}}`, `{{2}}`, ... placeholders
+    pub fn template_for_body(
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        body: &str,
+        params: Vec<String>,
+    ) -> Result<Self, crate::Error> {
+        check_params(body, &params)?;
+        Ok(Self::template(namespace, name, params))
+    }
+
+    /// Load the media bytes and resolve MIME type and caption
+    ///
+    /// The MIME type is detected from the file signature when not given
+    /// explicitly. Fails for messages without media or if loading fails.
+    pub fn resolve_media(&self) -> Result<(Vec<u8>, String, Option<String>), crate::Error> {
+        let load = |source: &MediaSource| {
+            source
+                .load()
+                .map_err(|e| crate::Error::Send(format!("Failed to load media: {}", e)))
+        };
+
+        match self {
+            MessageType::Text(_)
+            | MessageType::Buttons { .. }
+            | MessageType::List { .. }
+            | MessageType::Template { .. } => {
+                Err(crate::Error::Validation("Message type has no media".into()))
+            }
+            MessageType::Album { .. } => Err(crate::Error::Validation(
+                "Album images are loaded by send_album".into(),
+            )),
+            #[cfg(feature = "payments")]
+            MessageType::PaymentRequest { .. } => {
+                Err(crate::Error::Validation("Message type has no media".into()))
+            }
+            MessageType::Image {
+                source,
+                mime_type,
+                caption,
+            }
+            | MessageType::Video {
+                source,
+                mime_type,
+                caption,
+            }
+            | MessageType::Document {
+                source,
+                mime_type,
+                caption,
+                ..
+            } => {
+                let data = load(source)?;
+                let mime = mime_type
+                    .clone()
+                    .unwrap_or_else(|| MediaSource::detect_mime_from_signature(&data));
+                Ok((data, mime, caption.clone()))
+            }
+            MessageType::Audio { source, mime_type } => {
+                let data = load(source)?;
+                let mime = mime_type
+                    .clone()
+                    .unwrap_or_else(|| MediaSource::detect_mime_from_signature(&data));
+                Ok((data, mime, None))
+            }
+            MessageType::Sticker { source } => Ok((load(source)?, "image/webp".into(), None)),
+        }
+    }
+
     /// Get text content if this is a text message
     pub fn as_text(&self) -> Option<&str> {
         match self {
@@ -304,18 +688,68 @@ pub enum Event {
     Disconnected,
     /// Logged out
     LoggedOut(LoggedOutEvent),
+    /// The session was opened on another machine, which took over the
+    /// connection
+    ///
+    /// The client does not reconnect on its own, since that would knock the
+    /// other client offline in turn. Alert the user, and call
+    /// [`WhatsApp::reconnect`](crate::WhatsApp::reconnect) only once this
+    /// instance should win.
+    StreamReplaced,
+    /// Incoming voice or video call
+    CallOffer(CallOfferEvent),
     /// Incoming message
+    ///
+    /// Messages carrying a `protocolMessage` arrive as [`Event::Protocol`]
+    /// (or a more specific variant) instead.
     Message(MessageEvent),
+    /// A message arrived that couldn't be decrypted
+    DecryptionFailure(DecryptFailEvent),
     /// Message delivery receipt
     Receipt(ReceiptEvent),
     /// Presence update
     Presence(PresenceEvent),
-    /// History sync progress
+    /// A history sync finished delivering its [`HistoryChunk`](Event::HistoryChunk)s
     HistorySync,
+    /// Conversations from a history sync, streamed from disk in small batches
+    HistoryChunk(HistoryChunk),
     /// Offline sync preview
     OfflineSyncPreview(OfflineSyncPreviewEvent),
     /// Offline sync completed
     OfflineSyncCompleted(OfflineSyncCompletedEvent),
+    /// App state collection fully synced (e.g. after [`WhatsApp::resync_contacts`](crate::WhatsApp::resync_contacts))
+    AppStateSynced(AppStateSyncedEvent),
+    /// The phone shared app state keys with this device
+    ///
+    /// The bridge then fetches every collection again, so changes that
+    /// couldn't be decrypted before the keys arrived are applied and follow
+    /// as their usual events.
+    AppStateKeyReceived,
+    /// A chat was deleted on the phone or another linked device
+    ChatDeleted(ChatDeletedEvent),
+    /// Decrypted vote on a poll
+    PollVote(PollVoteEvent),
+    /// Protocol message (revokes, app-state keys, history sync notices, ...)
+    ///
+    /// These used to arrive as [`Event::Message`]; they no longer do.
+    Protocol(ProtocolEvent),
+    /// A message was edited by its sender
+    MessageEdited(MessageEditedEvent),
+    /// Disappearing messages were turned on, off or retimed in a chat
+    EphemeralSettingChanged(EphemeralSettingEvent),
+    /// Reply button tapped or list row picked
+    InteractiveResponse(InteractiveResponseEvent),
+    /// System notice in a chat (security code changed, disappearing messages, ...)
+    System(SystemEvent),
+    /// Payment request, payment or request cancellation (experimental)
+    #[cfg(feature = "payments")]
+    Payment(PaymentEvent),
+    /// This stream fell behind and `missed` events were dropped
+    ///
+    /// Only produced locally by [`EventStream`](crate::EventStream); re-query
+    /// state (e.g. [`WhatsApp::messages`](crate::WhatsApp::messages)) if the
+    /// gap matters.
+    StreamLagged { missed: u64 },
     /// Unknown event type (contains raw JSON for inspection)
     Unknown {
         event_type: String,
@@ -388,6 +822,86 @@ pub struct LoggedOutEvent {
     pub reason: i32,
 }
 
+impl LoggedOutEvent {
+    /// Typed view of the raw reason code
+    pub fn reason_kind(&self) -> DisconnectReason {
+        DisconnectReason::from_code(self.reason)
+    }
+}
+
+/// Why WhatsApp ended or refused a session (its connect-failure codes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DisconnectReason {
+    /// 401: this device was unlinked
+    LoggedOut,
+    /// 402: the account is temporarily banned
+    TempBanned,
+    /// 403: the primary phone's account is gone (deleted or banned)
+    MainDeviceGone,
+    /// 405: the advertised client version is too old
+    ClientOutdated,
+    /// 406: logged out for an unspecified reason
+    UnknownLogout,
+    /// 409: the server rejected the client's user agent
+    BadUserAgent,
+    /// 500: server-side error
+    InternalServerError,
+    /// 503: WhatsApp is temporarily unavailable
+    ServiceUnavailable,
+    /// Any other code
+    Unknown(i32),
+}
+
+impl DisconnectReason {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            401 => Self::LoggedOut,
+            402 => Self::TempBanned,
+            403 => Self::MainDeviceGone,
+            405 => Self::ClientOutdated,
+            406 => Self::UnknownLogout,
+            409 => Self::BadUserAgent,
+            500 => Self::InternalServerError,
+            503 => Self::ServiceUnavailable,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// Raw WhatsApp code
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::LoggedOut => 401,
+            Self::TempBanned => 402,
+            Self::MainDeviceGone => 403,
+            Self::ClientOutdated => 405,
+            Self::UnknownLogout => 406,
+            Self::BadUserAgent => 409,
+            Self::InternalServerError => 500,
+            Self::ServiceUnavailable => 503,
+            Self::Unknown(code) => *code,
+        }
+    }
+
+    /// The session is gone for good; pair again to continue
+    pub fn is_logged_out(&self) -> bool {
+        matches!(
+            self,
+            Self::LoggedOut | Self::MainDeviceGone | Self::UnknownLogout
+        )
+    }
+
+    /// A server-side hiccup worth retrying after a delay
+    ///
+    /// Only 500 and 503 qualify: they describe the server, not this client,
+    /// so the same session can succeed later. Every other known code needs
+    /// action first (pairing again, waiting out a ban, upgrading), and
+    /// retrying unknown codes risks hammering the server over a permanent
+    /// failure.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::InternalServerError | Self::ServiceUnavailable)
+    }
+}
+
 /// Message info from WhatsApp
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageInfo {
@@ -465,9 +979,86 @@ impl MessageEvent {
         }
         String::new()
     }
+
+    /// Get the message this one replies to, if any
+    pub fn quoted(&self) -> Option<QuotedMessage> {
+        QuotedMessage::from_message(self.message.as_ref()?)
+    }
+
+    /// Parse the message body once for repeated typed access
+    pub fn parsed(&self) -> ParsedMessage<'_> {
+        ParsedMessage::new(self.message.as_ref())
+    }
+}
+
+impl QuotedMessage {
+    /// Extract the quoted message from a message body's `contextInfo`
+    pub(crate) fn from_message(msg: &Value) -> Option<Self> {
+        let ctx = context_info(msg)?;
+        let quoted = ctx.get("quotedMessage")?;
+
+        let (key, body) = quoted
+            .as_object()?
+            .iter()
+            .find(|(k, _)| *k != "messageContextInfo")?;
+        let message_type = match key.as_str() {
+            "conversation" | "extendedTextMessage" => "text".to_string(),
+            other => other.trim_end_matches("Message").to_string(),
+        };
+        let text = body
+            .as_str()
+            .or_else(|| body.get("text").and_then(|v| v.as_str()))
+            .or_else(|| body.get("caption").and_then(|v| v.as_str()))
+            .map(str::to_string);
+
+        Some(QuotedMessage {
+            stanza_id: ctx
+                .get("stanzaID")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            sender: ctx
+                .get("participant")
+                .and_then(|v| v.as_str())
+                .map(Jid::new),
+            message_type,
+            text,
+            message: quoted.clone(),
+        })
+    }
+
+    /// Whether the quoted message is media rather than text
+    pub fn is_media(&self) -> bool {
+        self.message_type != "text"
+    }
+}
+
+/// Find the `contextInfo` of the first message body that carries one
+pub(crate) fn context_info(msg: &Value) -> Option<&Value> {
+    msg.as_object()?
+        .values()
+        .find_map(|body| body.get("contextInfo"))
+}
+
+/// The original message a reply refers to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotedMessage {
+    /// ID of the quoted message
+    pub stanza_id: String,
+    /// Author of the quoted message (set in groups and for replies to others)
+    pub sender: Option<Jid>,
+    /// Kind of quoted content: "text", "image", "video", "sticker", ...
+    pub message_type: String,
+    /// Quoted text, or caption for media
+    pub text: Option<String>,
+    /// Raw quoted message JSON
+    pub message: Value,
 }
 
 /// Message receipt
+///
+/// WhatsApp batches receipts: one receipt can acknowledge several messages,
+/// and it carries a single timestamp for all of them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReceiptEvent {
     #[serde(rename = "MessageIDs")]
@@ -478,11 +1069,67 @@ pub struct ReceiptEvent {
     pub sender: String,
     #[serde(rename = "Type")]
     pub receipt_type: String,
+    /// When the receipt was issued, shared by every ID in `message_ids`
     #[serde(rename = "Timestamp")]
     pub timestamp: String,
 }
 
+/// What a [`ReceiptEvent`] acknowledges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptStatus {
+    /// Reached the recipient's device
+    Delivered,
+    /// Read by the recipient
+    Read,
+    /// Read on another of this account's devices
+    ReadSelf,
+    /// Voice note or video listened to by the recipient
+    Played,
+    /// Played on another of this account's devices
+    PlayedSelf,
+    /// Delivered to another of this account's devices
+    Sender,
+    /// The recipient could not decrypt the message and asks for a resend
+    Retry,
+    /// A receipt type this crate doesn't classify
+    Other(String),
+}
+
+impl ReceiptStatus {
+    /// Map the bridge's receipt type string
+    pub fn from_type(receipt_type: &str) -> Self {
+        match receipt_type {
+            "" | "delivery" => Self::Delivered,
+            "read" => Self::Read,
+            "read-self" => Self::ReadSelf,
+            "played" => Self::Played,
+            "played-self" => Self::PlayedSelf,
+            "sender" => Self::Sender,
+            "retry" => Self::Retry,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl ReceiptEvent {
+    /// What this receipt acknowledges
+    pub fn status(&self) -> ReceiptStatus {
+        ReceiptStatus::from_type(&self.receipt_type)
+    }
+
+    /// Parse the receipt time
+    ///
+    /// This is when the batch was acknowledged, so it is an upper bound on
+    /// when each individual message was delivered or read.
+    pub fn timestamp_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.timestamp)
+    }
+}
+
 /// Presence event
+///
+/// Only carries online/offline state; typing ("composing") indicators arrive
+/// as separate chat presence events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresenceEvent {
     #[serde(rename = "From")]
@@ -497,6 +1144,13 @@ impl PresenceEvent {
     pub fn is_online(&self) -> bool {
         !self.unavailable
     }
+
+    /// Parse the last-seen time
+    ///
+    /// Returns `None` when the contact hides their last seen or it was never sent.
+    pub fn last_seen_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.last_seen)
+    }
 }
 
 /// Offline sync preview event
@@ -514,6 +1168,243 @@ pub struct OfflineSyncPreviewEvent {
     pub receipts: i32,
 }
 
+/// Vote on a poll, decrypted by the bridge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollVoteEvent {
+    /// ID of the poll creation message
+    pub poll_id: String,
+    /// ID of the vote message
+    pub message_id: String,
+    pub chat: Jid,
+    pub voter: Jid,
+    /// Names of the selected options (empty when the vote was withdrawn)
+    pub options: Vec<String>,
+    /// Vote time (RFC 3339)
+    pub timestamp: String,
+}
+
+impl PollVoteEvent {
+    /// Parse the vote time
+    pub fn timestamp_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.timestamp)
+    }
+}
+
+/// A previously sent message was edited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEditedEvent {
+    pub chat: Jid,
+    /// ID of the original (edited) message
+    pub message_id: MessageId,
+    /// Who edited the message (always its original sender)
+    pub sender: Jid,
+    /// Replacement text or caption
+    pub new_text: String,
+    pub edited_at: SystemTime,
+}
+
+impl MessageEditedEvent {
+    /// Build from a `MESSAGE_EDIT` protocol message
+    fn from_protocol(info: &MessageInfo, raw: &Value) -> Option<Self> {
+        let message_id = raw.get("key")?.get("ID")?.as_str()?;
+        let edited = raw.get("editedMessage");
+
+        let new_text = edited
+            .and_then(|m| {
+                m.get("conversation")
+                    .or_else(|| m.get("extendedTextMessage").and_then(|e| e.get("text")))
+                    .or_else(|| m.as_object()?.values().find_map(|b| b.get("caption")))
+            })
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        Some(Self {
+            chat: Jid::new(&info.chat),
+            message_id: MessageId::new(message_id),
+            sender: Jid::new(&info.sender),
+            new_text: new_text.to_owned(),
+            edited_at: parse_rfc3339(&info.timestamp).unwrap_or_else(SystemTime::now),
+        })
+    }
+}
+
+/// A message that couldn't be decrypted, usually after a session desync
+///
+/// The bridge already asks the sender's device to re-encrypt it; if that
+/// works, the message later arrives as a normal [`Event::Message`] with the
+/// same ID. Otherwise this is the only trace of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawDecryptFail")]
+pub struct DecryptFailEvent {
+    pub chat: Jid,
+    pub sender: Jid,
+    pub message_id: MessageId,
+    /// WhatsApp withheld the content (e.g. view-once sent to a linked
+    /// device) rather than it failing to decrypt
+    pub unavailable: bool,
+}
+
+/// Wire shape of whatsmeow's `UndecryptableMessage`
+#[derive(Deserialize)]
+struct RawDecryptFail {
+    #[serde(rename = "Info")]
+    info: MessageInfo,
+    #[serde(rename = "IsUnavailable", default)]
+    is_unavailable: bool,
+}
+
+impl From<RawDecryptFail> for DecryptFailEvent {
+    fn from(raw: RawDecryptFail) -> Self {
+        Self {
+            chat: Jid::new(&raw.info.chat),
+            sender: Jid::new(&raw.info.sender),
+            message_id: MessageId::new(raw.info.id),
+            unavailable: raw.is_unavailable,
+        }
+    }
+}
+
+/// Disappearing-messages setting of a chat changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EphemeralSettingEvent {
+    pub chat: Jid,
+    pub enabled: bool,
+    /// How long messages last, `None` when disabled
+    pub ttl: Option<Duration>,
+    /// Who changed the setting
+    pub actor: Jid,
+}
+
+impl EphemeralSettingEvent {
+    /// Build from an `EPHEMERAL_SETTING` protocol message
+    fn from_protocol(info: &MessageInfo, raw: &Value) -> Self {
+        let seconds = raw
+            .get("ephemeralExpiration")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+
+        Self {
+            chat: Jid::new(&info.chat),
+            enabled: seconds > 0,
+            ttl: (seconds > 0).then(|| Duration::from_secs(seconds)),
+            actor: Jid::new(&info.sender),
+        }
+    }
+}
+
+/// Protocol-level message exchanged between devices rather than shown to users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolEvent {
+    /// Envelope of the message carrying the protocol payload
+    pub info: MessageInfo,
+    /// Classified `protocolMessage.type`
+    pub kind: ProtocolKind,
+    /// Raw `protocolMessage` JSON
+    pub raw: Value,
+}
+
+/// Known `protocolMessage.type` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProtocolKind {
+    Revoke,
+    EphemeralSetting,
+    EphemeralSyncResponse,
+    HistorySyncNotification,
+    AppStateSyncKeyShare,
+    AppStateSyncKeyRequest,
+    MsgFanoutBackfillRequest,
+    InitialSecurityNotificationSettingSync,
+    AppStateFatalExceptionNotification,
+    SharePhoneNumber,
+    MessageEdit,
+    PeerDataOperationRequest,
+    PeerDataOperationRequestResponse,
+    /// Type not known to this crate
+    Other(i32),
+}
+
+impl ProtocolKind {
+    /// Map a raw protobuf enum value
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            0 => Self::Revoke,
+            3 => Self::EphemeralSetting,
+            4 => Self::EphemeralSyncResponse,
+            5 => Self::HistorySyncNotification,
+            6 => Self::AppStateSyncKeyShare,
+            7 => Self::AppStateSyncKeyRequest,
+            8 => Self::MsgFanoutBackfillRequest,
+            9 => Self::InitialSecurityNotificationSettingSync,
+            10 => Self::AppStateFatalExceptionNotification,
+            11 => Self::SharePhoneNumber,
+            14 => Self::MessageEdit,
+            16 => Self::PeerDataOperationRequest,
+            17 => Self::PeerDataOperationRequestResponse,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl MessageEvent {
+    /// Wrap as an event, splitting off system notices, protocol payloads,
+    /// edits and interactive responses
+    fn into_event(self) -> Event {
+        if let Some(system) = SystemEvent::from_message(&self.info, self.message.as_ref()) {
+            return Event::System(system);
+        }
+
+        #[cfg(feature = "payments")]
+        if let Some(payment) = self
+            .message
+            .as_ref()
+            .and_then(|m| PaymentEvent::from_message(&self.info, m))
+        {
+            return Event::Payment(payment);
+        }
+
+        if let Some(response) = self
+            .message
+            .as_ref()
+            .and_then(|m| InteractiveResponseEvent::from_message(&self.info, m))
+        {
+            return Event::InteractiveResponse(response);
+        }
+
+        let Some(raw) = self
+            .message
+            .as_ref()
+            .and_then(|m| m.get("protocolMessage"))
+            .cloned()
+        else {
+            return Event::Message(self);
+        };
+
+        // proto2 default for a missing enum is its first value (REVOKE)
+        let code = raw.get("type").and_then(|t| t.as_i64()).unwrap_or(0);
+        let kind = ProtocolKind::from_code(code as i32);
+
+        if kind == ProtocolKind::MessageEdit
+            && let Some(edit) = MessageEditedEvent::from_protocol(&self.info, &raw)
+        {
+            return Event::MessageEdited(edit);
+        }
+        if kind == ProtocolKind::AppStateSyncKeyShare {
+            return Event::AppStateKeyReceived;
+        }
+        if kind == ProtocolKind::EphemeralSetting {
+            return Event::EphemeralSettingChanged(EphemeralSettingEvent::from_protocol(
+                &self.info, &raw,
+            ));
+        }
+
+        Event::Protocol(ProtocolEvent {
+            info: self.info,
+            kind,
+            raw,
+        })
+    }
+}
+
 /// Offline sync completed event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OfflineSyncCompletedEvent {
@@ -554,6 +1445,7 @@ impl RawEvent {
             }
             "connected" => Ok(Event::Connected),
             "disconnected" => Ok(Event::Disconnected),
+            "stream_replaced" => Ok(Event::StreamReplaced),
             "logged_out" => {
                 if let Some(data) = self.data {
                     Ok(Event::LoggedOut(serde_json::from_value(data)?))
@@ -563,7 +1455,7 @@ impl RawEvent {
             }
             "message" => {
                 if let Some(data) = self.data {
-                    Ok(Event::Message(serde_json::from_value(data)?))
+                    Ok(serde_json::from_value::<MessageEvent>(data)?.into_event())
                 } else {
                     Ok(Event::Unknown {
                         event_type: "message".into(),
@@ -571,6 +1463,16 @@ impl RawEvent {
                     })
                 }
             }
+            "call_offer" => {
+                if let Some(data) = self.data {
+                    Ok(Event::CallOffer(serde_json::from_value(data)?))
+                } else {
+                    Ok(Event::Unknown {
+                        event_type: "call_offer".into(),
+                        data: None,
+                    })
+                }
+            }
             "receipt" => {
                 if let Some(data) = self.data {
                     Ok(Event::Receipt(serde_json::from_value(data)?))
@@ -591,6 +1493,26 @@ impl RawEvent {
                     })
                 }
             }
+            "undecryptable_message" => {
+                if let Some(data) = self.data {
+                    Ok(Event::DecryptionFailure(serde_json::from_value(data)?))
+                } else {
+                    Ok(Event::Unknown {
+                        event_type: "undecryptable_message".into(),
+                        data: None,
+                    })
+                }
+            }
+            "poll_vote" => {
+                if let Some(data) = self.data {
+                    Ok(Event::PollVote(serde_json::from_value(data)?))
+                } else {
+                    Ok(Event::Unknown {
+                        event_type: "poll_vote".into(),
+                        data: None,
+                    })
+                }
+            }
             "history_sync" => Ok(Event::HistorySync),
             "offline_sync_preview" => {
                 if let Some(data) = self.data {
@@ -612,6 +1534,26 @@ impl RawEvent {
                     })
                 }
             }
+            "app_state_sync_complete" => {
+                if let Some(data) = self.data {
+                    Ok(Event::AppStateSynced(serde_json::from_value(data)?))
+                } else {
+                    Ok(Event::Unknown {
+                        event_type: "app_state_sync_complete".into(),
+                        data: None,
+                    })
+                }
+            }
+            "delete_chat" => {
+                if let Some(data) = self.data {
+                    Ok(Event::ChatDeleted(serde_json::from_value(data)?))
+                } else {
+                    Ok(Event::Unknown {
+                        event_type: "delete_chat".into(),
+                        data: None,
+                    })
+                }
+            }
             other => Ok(Event::Unknown {
                 event_type: other.to_string(),
                 data: self.data,
@@ -619,3 +1561,428 @@ impl RawEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock_ffi;
+
+    /// Parse bridge event data the way the run loop does
+    fn parse(event_type: &str, data: Value) -> Event {
+        RawEvent {
+            event_type: event_type.into(),
+            timestamp: 0,
+            data: Some(data),
+        }
+        .into_event()
+        .unwrap()
+    }
+
+    #[test]
+    fn protocol_messages_are_not_delivered_as_messages() {
+        let event = parse(
+            "message",
+            mock_ffi::message(
+                "ID1",
+                "1@s.whatsapp.net",
+                "1@s.whatsapp.net",
+                json!({ "protocolMessage": { "type": 5, "key": { "ID": "ID0" } } }),
+            ),
+        );
+
+        let Event::Protocol(protocol) = event else {
+            panic!("expected a protocol event, got {event:?}");
+        };
+        assert_eq!(protocol.kind, ProtocolKind::HistorySyncNotification);
+        assert_eq!(protocol.info.id, "ID1");
+        assert_eq!(protocol.raw["key"]["ID"], "ID0");
+    }
+
+    #[test]
+    fn a_protocol_message_without_a_type_is_a_revoke() {
+        let event = parse(
+            "message",
+            mock_ffi::message(
+                "ID1",
+                "1@s.whatsapp.net",
+                "1@s.whatsapp.net",
+                json!({ "protocolMessage": {} }),
+            ),
+        );
+
+        assert!(matches!(
+            event,
+            Event::Protocol(ProtocolEvent {
+                kind: ProtocolKind::Revoke,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn unknown_protocol_types_keep_their_code() {
+        assert_eq!(ProtocolKind::from_code(99), ProtocolKind::Other(99));
+    }
+
+    #[test]
+    fn an_edit_is_delivered_as_message_edited() {
+        let event = parse(
+            "message",
+            mock_ffi::message(
+                "E1",
+                "1@s.whatsapp.net",
+                "2@s.whatsapp.net",
+                json!({
+                    "protocolMessage": {
+                        "type": 14,
+                        "key": { "ID": "M1" },
+                        "editedMessage": { "conversation": "fixed typo" },
+                    }
+                }),
+            ),
+        );
+
+        let Event::MessageEdited(edit) = event else {
+            panic!("expected an edit, got {event:?}");
+        };
+        assert_eq!(edit.message_id, MessageId::from("M1"));
+        assert_eq!(edit.chat, Jid::from("1@s.whatsapp.net"));
+        assert_eq!(edit.sender, Jid::from("2@s.whatsapp.net"));
+        assert_eq!(edit.new_text, "fixed typo");
+    }
+
+    #[test]
+    fn a_normal_text_is_delivered_as_message() {
+        let event = parse(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "2@s.whatsapp.net", "hello"),
+        );
+
+        let Event::Message(message) = event else {
+            panic!("expected a message, got {event:?}");
+        };
+        assert_eq!(message.text(), "hello");
+    }
+
+    #[test]
+    fn disconnect_reasons_map_their_codes() {
+        use DisconnectReason::*;
+
+        let known = [
+            (401, LoggedOut, true, false),
+            (402, TempBanned, false, false),
+            (403, MainDeviceGone, true, false),
+            (405, ClientOutdated, false, false),
+            (406, UnknownLogout, true, false),
+            (409, BadUserAgent, false, false),
+            (500, InternalServerError, false, true),
+            (503, ServiceUnavailable, false, true),
+        ];
+        for (code, reason, logged_out, transient) in known {
+            assert_eq!(DisconnectReason::from_code(code), reason);
+            assert_eq!(reason.code(), code);
+            assert_eq!(reason.is_logged_out(), logged_out, "{reason:?}");
+            assert_eq!(reason.is_transient(), transient, "{reason:?}");
+        }
+
+        let unknown = DisconnectReason::from_code(418);
+        assert_eq!(unknown, Unknown(418));
+        assert_eq!(unknown.code(), 418);
+        assert!(!unknown.is_logged_out() && !unknown.is_transient());
+    }
+
+    #[test]
+    fn logged_out_events_expose_the_typed_reason() {
+        let Event::LoggedOut(event) =
+            parse("logged_out", json!({ "OnConnect": true, "Reason": 402 }))
+        else {
+            panic!("expected a logout");
+        };
+        assert_eq!(event.reason_kind(), DisconnectReason::TempBanned);
+    }
+
+    #[test]
+    fn presence_last_seen_is_parsed_when_shared() {
+        let presence = |last_seen: &str| {
+            let Event::Presence(presence) = parse(
+                "presence",
+                json!({ "From": "1@s.whatsapp.net", "Unavailable": true, "LastSeen": last_seen }),
+            ) else {
+                panic!("expected presence");
+            };
+            presence
+        };
+
+        assert_eq!(
+            presence("2024-01-01T00:00:00Z").last_seen_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+        // Hidden last seen arrives as Go's zero time or not at all
+        assert_eq!(presence("0001-01-01T00:00:00Z").last_seen_at(), None);
+        assert_eq!(presence("").last_seen_at(), None);
+    }
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn media_mime_is_detected_unless_given() {
+        let auto = MessageType::image_auto(MediaSource::bytes(PNG.to_vec()));
+        let (data, mime, _) = auto.resolve_media().unwrap();
+        assert_eq!(data, PNG);
+        assert_eq!(mime, "image/png");
+
+        let explicit = MessageType::Image {
+            source: MediaSource::bytes(PNG.to_vec()),
+            mime_type: Some("image/x-custom".into()),
+            caption: Some("look".into()),
+        };
+        let (_, mime, caption) = explicit.resolve_media().unwrap();
+        assert_eq!(mime, "image/x-custom");
+        assert_eq!(caption.as_deref(), Some("look"));
+    }
+
+    #[test]
+    fn media_that_fails_to_load_is_a_send_error() {
+        let missing = MessageType::image_auto(MediaSource::file("/nonexistent/image.png"));
+        assert!(matches!(
+            missing.resolve_media(),
+            Err(crate::Error::Send(_))
+        ));
+
+        let text = MessageType::text("no media");
+        assert!(matches!(
+            text.resolve_media(),
+            Err(crate::Error::Validation(_))
+        ));
+    }
+
+    fn message_event(content: Value) -> MessageEvent {
+        let Event::Message(message) = parse(
+            "message",
+            mock_ffi::message("M2", "g@g.us", "2@s.whatsapp.net", content),
+        ) else {
+            panic!("expected a message");
+        };
+        message
+    }
+
+    #[test]
+    fn a_reply_exposes_the_quoted_text_message() {
+        let reply = message_event(json!({
+            "extendedTextMessage": {
+                "text": "agreed",
+                "contextInfo": {
+                    "stanzaID": "M1",
+                    "participant": "3@s.whatsapp.net",
+                    "quotedMessage": { "conversation": "lunch at noon?" },
+                },
+            }
+        }));
+
+        let quoted = reply.quoted().unwrap();
+        assert_eq!(reply.text(), "agreed");
+        assert_eq!(quoted.stanza_id, "M1");
+        assert_eq!(quoted.sender, Some(Jid::from("3@s.whatsapp.net")));
+        assert_eq!(quoted.message_type, "text");
+        assert_eq!(quoted.text.as_deref(), Some("lunch at noon?"));
+    }
+
+    #[test]
+    fn a_message_without_context_quotes_nothing() {
+        assert!(
+            message_event(json!({ "conversation": "hi" }))
+                .quoted()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn jid_lists_accept_mixed_separators_and_bare_numbers() {
+        let jids =
+            Jid::parse_list("1234567890,\n120363@g.us  +15550001111 123456@s.whatsapp.net,,")
+                .unwrap();
+
+        assert_eq!(
+            jids,
+            [
+                Jid::user("1234567890"),
+                Jid::group("120363"),
+                Jid::user("15550001111"),
+                Jid::from("123456@s.whatsapp.net"),
+            ]
+        );
+    }
+
+    #[test]
+    fn jid_lists_fail_on_the_first_invalid_entry() {
+        assert_eq!(
+            Jid::parse_list("1234567890, not-a-number, @g.us"),
+            Err(JidError::InvalidPhone("not-a-number".into()))
+        );
+        assert_eq!(
+            Jid::parse_list("1234567890 @g.us"),
+            Err(JidError::Malformed("@g.us".into()))
+        );
+        assert_eq!(Jid::parse_list(" , "), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn poll_votes_carry_the_resolved_option_names() {
+        let vote = |options: Value| match parse(
+            "poll_vote",
+            json!({
+                "poll_id": "POLL1",
+                "message_id": "VOTE1",
+                "chat": "123-456@g.us",
+                "voter": "15550001111@s.whatsapp.net",
+                "options": options,
+                "timestamp": "2024-01-01T00:00:00Z",
+            }),
+        ) {
+            Event::PollVote(vote) => vote,
+            other => panic!("expected a poll vote, got {:?}", other),
+        };
+
+        let chosen = vote(json!(["Blue", "Red"]));
+        assert_eq!(chosen.poll_id, "POLL1");
+        assert_eq!(chosen.voter, Jid::from("15550001111@s.whatsapp.net"));
+        assert_eq!(chosen.options, ["Blue", "Red"]);
+        assert_eq!(
+            chosen.timestamp_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+
+        assert!(vote(json!([])).options.is_empty());
+    }
+
+    #[test]
+    fn message_ids_round_trip_as_plain_strings() {
+        let id = MessageId::new("3EB0ABCDEF");
+
+        let json = serde_json::to_value(&id).unwrap();
+
+        assert_eq!(json, json!("3EB0ABCDEF"));
+        assert_eq!(serde_json::from_value::<MessageId>(json).unwrap(), id);
+        assert_eq!(id.to_string(), "3EB0ABCDEF");
+    }
+
+    #[test]
+    fn message_ids_key_maps_and_look_up_by_str() {
+        let mut replies = std::collections::HashMap::new();
+        replies.insert(MessageId::from("A"), 1);
+        replies.insert(MessageId::from("B"), 2);
+        replies.insert(MessageId::from("A".to_string()), 3);
+
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[&MessageId::from("A")], 3);
+        assert_eq!(replies.get("B"), Some(&2));
+    }
+
+    #[test]
+    fn multi_id_receipts_share_one_timestamp() {
+        let receipt = match parse(
+            "receipt",
+            json!({
+                "MessageIDs": ["M1", "M2", "M3"],
+                "Chat": "15550001111@s.whatsapp.net",
+                "Sender": "15550001111@s.whatsapp.net",
+                "IsFromMe": false,
+                "IsGroup": false,
+                "Type": "read",
+                "Timestamp": "2024-01-01T00:00:00Z",
+            }),
+        ) {
+            Event::Receipt(receipt) => receipt,
+            other => panic!("expected a receipt, got {:?}", other),
+        };
+
+        assert_eq!(receipt.message_ids, ["M1", "M2", "M3"]);
+        assert_eq!(receipt.status(), ReceiptStatus::Read);
+        assert_eq!(
+            receipt.timestamp_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+    }
+
+    fn ephemeral_setting(expiration: Option<u64>) -> Event {
+        let mut protocol = json!({ "type": 3 });
+        if let Some(seconds) = expiration {
+            protocol["ephemeralExpiration"] = json!(seconds);
+        }
+        parse(
+            "message",
+            mock_ffi::message(
+                "ID1",
+                "123-456@g.us",
+                "1@s.whatsapp.net",
+                json!({ "protocolMessage": protocol }),
+            ),
+        )
+    }
+
+    #[test]
+    fn enabling_disappearing_messages_carries_the_ttl() {
+        let event = ephemeral_setting(Some(604_800));
+
+        let Event::EphemeralSettingChanged(setting) = event else {
+            panic!("expected an ephemeral setting change, got {event:?}");
+        };
+        assert_eq!(setting.chat.as_str(), "123-456@g.us");
+        assert_eq!(setting.actor.as_str(), "1@s.whatsapp.net");
+        assert!(setting.enabled);
+        assert_eq!(setting.ttl, Some(Duration::from_secs(604_800)));
+    }
+
+    #[test]
+    fn disabling_disappearing_messages_has_no_ttl() {
+        for expiration in [None, Some(0)] {
+            let event = ephemeral_setting(expiration);
+
+            let Event::EphemeralSettingChanged(setting) = event else {
+                panic!("expected an ephemeral setting change, got {event:?}");
+            };
+            assert!(!setting.enabled);
+            assert_eq!(setting.ttl, None);
+        }
+    }
+
+    #[test]
+    fn undecryptable_messages_become_decryption_failures() {
+        let mut data = mock_ffi::message("ID9", "123-456@g.us", "1@s.whatsapp.net", Value::Null);
+        data["IsUnavailable"] = json!(true);
+
+        let event = parse("undecryptable_message", data);
+
+        let Event::DecryptionFailure(failure) = event else {
+            panic!("expected a decryption failure, got {event:?}");
+        };
+        assert_eq!(failure.chat.as_str(), "123-456@g.us");
+        assert_eq!(failure.sender.as_str(), "1@s.whatsapp.net");
+        assert_eq!(failure.message_id.as_str(), "ID9");
+        assert!(failure.unavailable);
+    }
+
+    #[test]
+    fn chat_deletions_are_parsed() {
+        let event = parse(
+            "delete_chat",
+            json!({
+                "JID": "123-456@g.us",
+                "Timestamp": "2024-01-01T00:00:00Z",
+                "FromFullSync": true,
+            }),
+        );
+
+        let Event::ChatDeleted(deleted) = event else {
+            panic!("expected a chat deletion, got {event:?}");
+        };
+        assert_eq!(deleted.chat.as_str(), "123-456@g.us");
+        assert!(deleted.from_full_sync);
+        assert_eq!(
+            deleted.timestamp_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+    }
+}
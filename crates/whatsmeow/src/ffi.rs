@@ -1,27 +1,93 @@
 //! Safe wrappers around FFI bindings
 
-use std::ffi::CString;
-use std::path::Path;
+use std::collections::HashMap;
+use std::ffi::{CString, c_char, c_int};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+use parking_lot::{Mutex, MutexGuard};
 use tracing::{debug, warn};
-use whatsmeow_sys::{self as sys, ClientHandle, error_codes::*};
+#[cfg(not(test))]
+use whatsmeow_sys as sys;
+use whatsmeow_sys::{ClientHandle, error_codes::*};
 
-use crate::allocator::TrackedAllocator;
+use crate::allocator::{MemoryStats, TrackedAllocator};
+use crate::config::StoreOptions;
 use crate::error::{Error, Result};
+use crate::latency::{LatencyRecorder, LatencyStats};
+#[cfg(test)]
+use crate::mock_ffi as sys;
+use crate::outgoing::MediaKind;
+use crate::session::SessionBlob;
+use crate::sticker::StickerInfo;
 
-/// Global allocator reference for tracing (set by the example/app)
-#[global_allocator]
+/// Initial buffer size for FFI queries returning JSON
+const QUERY_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Buffer size for send results (message ID and timestamp)
+const SEND_RESULT_BUFFER_SIZE: usize = 1024;
+
+/// Upper bound for query buffer growth
+const MAX_QUERY_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+
+/// Allocation counters for tracing; only fed when installed as the global
+/// allocator by the `track-allocations` feature
+#[cfg_attr(feature = "track-allocations", global_allocator)]
 static GLOBAL: TrackedAllocator = TrackedAllocator::new();
 
+/// Snapshot of the tracking allocator's counters
+pub(crate) fn memory_stats() -> MemoryStats {
+    GLOBAL.stats()
+}
+
+/// Override the WhatsApp Web version the bridge advertises on connect
+#[tracing::instrument(name = "ffi.set_client_version")]
+pub(crate) fn set_client_version(major: u32, minor: u32, patch: u32) -> Result<()> {
+    let (Ok(major), Ok(minor), Ok(patch)) = (
+        c_int::try_from(major),
+        c_int::try_from(minor),
+        c_int::try_from(patch),
+    ) else {
+        return Err(Error::Validation(
+            "Client version component out of range".into(),
+        ));
+    };
+
+    let result = GLOBAL.trace_operation("wm_set_client_version", || unsafe {
+        sys::wm_set_client_version(major, minor, patch)
+    });
+
+    match result {
+        WM_OK => Ok(()),
+        _ => Err(Error::Validation(format!(
+            "Invalid client version {}.{}.{}",
+            major, minor, patch
+        ))),
+    }
+}
+
 /// Safe wrapper around the raw FFI handle
 pub(crate) struct FfiClient {
     handle: ClientHandle,
     event_buffer: Vec<u8>,
+    /// What the handle was opened with, kept for [`reopen`](Self::reopen)
+    db_path: PathBuf,
+    device_name: String,
+    options: StoreOptions,
+    /// Kept across [`reopen`](Self::reopen), and shared with [`FfiLock`] so
+    /// reading it never waits for the handle
+    latencies: Arc<LatencyRecorder>,
 }
 
 impl FfiClient {
-    #[tracing::instrument(skip_all, name = "ffi.new", fields(path = %db_path.as_ref().display(), device = %device_name))]
-    pub fn new(db_path: impl AsRef<Path>, device_name: &str) -> Result<Self> {
+    #[tracing::instrument(skip_all, name = "ffi.new", fields(path = %db_path.as_ref().display(), device = %device_name, wal = options.wal_mode))]
+    pub fn new(
+        db_path: impl AsRef<Path>,
+        device_name: &str,
+        options: &StoreOptions,
+    ) -> Result<Self> {
         let path = db_path.as_ref();
 
         // Create parent directory if it doesn't exist
@@ -29,6 +95,12 @@ impl FfiClient {
             && !parent.as_os_str().is_empty()
             && !parent.exists()
         {
+            if !options.create_db_dir {
+                return Err(Error::Init(format!(
+                    "Database directory {} does not exist",
+                    parent.display()
+                )));
+            }
             debug!(dir = %parent.display(), "Creating parent directory");
             std::fs::create_dir_all(parent)
                 .map_err(|e| Error::Init(format!("Failed to create directory: {}", e)))?;
@@ -43,8 +115,11 @@ impl FfiClient {
         let c_device = CString::new(device_name)
             .map_err(|_| Error::Init("Device name contains null byte".into()))?;
 
-        let handle = GLOBAL.trace_operation("wm_client_new", || unsafe {
-            sys::wm_client_new(c_path.as_ptr(), c_device.as_ptr())
+        let c_options = CString::new(serde_json::to_string(options)?)
+            .map_err(|_| Error::Init("Store options contain null byte".into()))?;
+
+        let handle = GLOBAL.trace_operation("wm_client_new_ex", || unsafe {
+            sys::wm_client_new_ex(c_path.as_ptr(), c_device.as_ptr(), c_options.as_ptr())
         });
 
         if handle.is_null() {
@@ -56,12 +131,59 @@ impl FfiClient {
         Ok(Self {
             handle,
             event_buffer: vec![0u8; 64 * 1024],
+            db_path: path.to_path_buf(),
+            device_name: device_name.to_string(),
+            options: options.clone(),
+            latencies: Arc::default(),
         })
     }
 
+    /// Trace a bridge call, timing it if latency recording is on
+    fn trace<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        self.latencies
+            .time(name, || GLOBAL.trace_operation(name, f))
+    }
+
+    #[cfg(test)]
+    pub fn handle(&self) -> ClientHandle {
+        self.handle
+    }
+
+    /// Overwrite the last event's plaintext with zeros
+    ///
+    /// Done once per client on drop rather than per poll: wiping on every
+    /// poll would slow the loop.
+    #[cfg(feature = "zeroize")]
+    fn wipe_event_buffer(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.event_buffer);
+    }
+
+    /// Replace the handle with a fresh, unconnected one on the same session
+    ///
+    /// The old handle is destroyed first: two handles on one session
+    /// database would fight over it. If opening fails, calls fail with
+    /// [`Error::InvalidHandle`] until a later reopen succeeds.
+    pub fn reopen(&mut self) -> Result<()> {
+        self.destroy();
+        let mut fresh = Self::new(&self.db_path, &self.device_name, &self.options)?;
+        self.handle = std::mem::replace(&mut fresh.handle, std::ptr::null_mut());
+        Ok(())
+    }
+
+    /// Release the Go-side client; later calls fail with an invalid handle
+    fn destroy(&mut self) {
+        if self.handle.is_null() {
+            return;
+        }
+        self.trace("wm_client_destroy", || unsafe {
+            sys::wm_client_destroy(self.handle)
+        });
+        self.handle = std::ptr::null_mut();
+    }
+
     #[tracing::instrument(skip(self), name = "ffi.connect")]
     pub fn connect(&self) -> Result<()> {
-        let result = GLOBAL.trace_operation("wm_client_connect", || unsafe {
+        let result = self.trace("wm_client_connect", || unsafe {
             sys::wm_client_connect(self.handle)
         });
         self.check_result(result)
@@ -69,20 +191,20 @@ impl FfiClient {
 
     #[tracing::instrument(skip(self), name = "ffi.disconnect")]
     pub fn disconnect(&self) -> Result<()> {
-        let result = GLOBAL.trace_operation("wm_client_disconnect", || unsafe {
+        let result = self.trace("wm_client_disconnect", || unsafe {
             sys::wm_client_disconnect(self.handle)
         });
         self.check_result(result)
     }
 
     pub fn poll_event(&mut self) -> Result<Option<Vec<u8>>> {
-        let n = unsafe {
-            sys::wm_poll_event(
-                self.handle,
-                self.event_buffer.as_mut_ptr() as *mut i8,
-                self.event_buffer.len() as i32,
-            )
-        };
+        let (buf, len) = (
+            self.event_buffer.as_mut_ptr() as *mut i8,
+            self.event_buffer.len() as i32,
+        );
+        let n = self.trace("wm_poll_event", || unsafe {
+            sys::wm_poll_event(self.handle, buf, len)
+        });
 
         if n < 0 {
             self.check_result(n)?;
@@ -96,26 +218,45 @@ impl FfiClient {
     }
 
     #[tracing::instrument(skip(self), name = "ffi.send_message", fields(to = %jid, text_len = text.len()))]
-    pub fn send_message(&self, jid: &str, text: &str) -> Result<()> {
+    pub fn send_message(&self, jid: &str, text: &str) -> Result<Vec<u8>> {
         let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
         let c_text =
             CString::new(text).map_err(|_| Error::Send("Text contains null byte".into()))?;
 
-        let result = GLOBAL.trace_operation("wm_send_message", || unsafe {
-            sys::wm_send_message(self.handle, c_jid.as_ptr(), c_text.as_ptr())
-        });
+        self.call_once_with_buffer("wm_send_message", |buf, len| unsafe {
+            sys::wm_send_message(self.handle, c_jid.as_ptr(), c_text.as_ptr(), buf, len)
+        })
+    }
 
-        self.check_result(result)
+    #[tracing::instrument(skip(self, text, options_json), name = "ffi.send_message_ex", fields(to = %jid, text_len = text.len()))]
+    pub fn send_message_ex(&self, jid: &str, text: &str, options_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_text =
+            CString::new(text).map_err(|_| Error::Send("Text contains null byte".into()))?;
+        let c_options = CString::new(options_json)
+            .map_err(|_| Error::Send("Options contain null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_message_ex", |buf, len| unsafe {
+            sys::wm_send_message_ex(
+                self.handle,
+                c_jid.as_ptr(),
+                c_text.as_ptr(),
+                c_options.as_ptr(),
+                buf,
+                len,
+            )
+        })
     }
 
-    #[tracing::instrument(skip(self, data), name = "ffi.send_image", fields(to = %jid, data_len = data.len(), mime = %mime_type))]
-    pub fn send_image(
+    #[tracing::instrument(skip(self, data, thumbnail), name = "ffi.send_image", fields(to = %jid, data_len = data.len(), mime = %mime_type, thumbnail = thumbnail.is_some()))]
+    pub fn send_image_ex(
         &self,
         jid: &str,
         data: &[u8],
         mime_type: &str,
         caption: Option<&str>,
-    ) -> Result<()> {
+        thumbnail: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
         let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
         let c_mime = CString::new(mime_type)
             .map_err(|_| Error::Send("MIME type contains null byte".into()))?;
@@ -128,20 +269,629 @@ impl FfiClient {
             .map(|c| c.as_ptr())
             .unwrap_or(std::ptr::null());
 
-        let result = GLOBAL.trace_operation("wm_send_image", || unsafe {
-            sys::wm_send_image(
+        let (thumb_ptr, thumb_len) = thumbnail
+            .map(|t| (t.as_ptr() as *const c_char, t.len() as c_int))
+            .unwrap_or((std::ptr::null(), 0));
+
+        self.call_once_with_buffer("wm_send_image_ex", |buf, len| unsafe {
+            sys::wm_send_image_ex(
                 self.handle,
                 c_jid.as_ptr(),
                 data.as_ptr() as *const i8,
                 data.len() as i32,
                 c_mime.as_ptr(),
                 caption_ptr,
+                thumb_ptr,
+                thumb_len,
+                buf,
+                len,
+            )
+        })
+    }
+
+    #[tracing::instrument(skip(self, thumbnail), name = "ffi.send_image_url", fields(to = %jid, url = %url, thumbnail = thumbnail.is_some()))]
+    pub fn send_image_url(
+        &self,
+        jid: &str,
+        url: &str,
+        mime_type: Option<&str>,
+        caption: Option<&str>,
+        thumbnail: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_url = CString::new(url).map_err(|_| Error::Send("URL contains null byte".into()))?;
+        let c_mime = CString::new(mime_type.unwrap_or_default())
+            .map_err(|_| Error::Send("MIME type contains null byte".into()))?;
+        let c_caption = CString::new(caption.unwrap_or_default())
+            .map_err(|_| Error::Send("Caption contains null byte".into()))?;
+
+        let (thumb_ptr, thumb_len) = thumbnail
+            .map(|t| (t.as_ptr() as *const c_char, t.len() as c_int))
+            .unwrap_or((std::ptr::null(), 0));
+
+        self.call_once_with_buffer("wm_send_image_url", |buf, len| unsafe {
+            sys::wm_send_image_url(
+                self.handle,
+                c_jid.as_ptr(),
+                c_url.as_ptr(),
+                c_mime.as_ptr(),
+                c_caption.as_ptr(),
+                thumb_ptr,
+                thumb_len,
+                buf,
+                len,
+            )
+        })
+    }
+
+    /// Returns the JSON array of sent message IDs
+    #[tracing::instrument(skip(self, items_json), name = "ffi.send_album", fields(to = %jid))]
+    pub fn send_album(
+        &self,
+        jid: &str,
+        items_json: &str,
+        caption: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_items = CString::new(items_json)
+            .map_err(|_| Error::Send("Album data contains null byte".into()))?;
+        let c_caption = CString::new(caption.unwrap_or_default())
+            .map_err(|_| Error::Send("Caption contains null byte".into()))?;
+
+        // The ID list is far below the initial buffer size, so the send is
+        // never repeated by a buffer regrow
+        self.call_with_buffer("wm_send_album", |buf, len| unsafe {
+            sys::wm_send_album(
+                self.handle,
+                c_jid.as_ptr(),
+                c_items.as_ptr(),
+                c_caption.as_ptr(),
+                buf,
+                len,
+            )
+        })
+    }
+
+    #[tracing::instrument(skip(self, data), name = "ffi.send_sticker", fields(to = %jid, data_len = data.len(), animated = info.animated))]
+    pub fn send_sticker(&self, jid: &str, data: &[u8], info: &StickerInfo) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_sticker", |buf, len| unsafe {
+            sys::wm_send_sticker(
+                self.handle,
+                c_jid.as_ptr(),
+                data.as_ptr() as *const c_char,
+                data.len() as c_int,
+                info.width as c_int,
+                info.height as c_int,
+                info.animated as c_int,
+                buf,
+                len,
+            )
+        })
+    }
+
+    #[tracing::instrument(skip(self, data, file_name, caption), name = "ffi.send_media", fields(to = %jid, kind = kind.as_str(), data_len = data.len()))]
+    pub fn send_media(
+        &self,
+        jid: &str,
+        kind: MediaKind,
+        data: &[u8],
+        mime_type: &str,
+        file_name: &str,
+        caption: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_kind = CString::new(kind.as_str()).expect("media kind has no null byte");
+        let c_mime = CString::new(mime_type)
+            .map_err(|_| Error::Send("MIME type contains null byte".into()))?;
+        let c_file_name = CString::new(file_name)
+            .map_err(|_| Error::Send("File name contains null byte".into()))?;
+        let c_caption = CString::new(caption.unwrap_or_default())
+            .map_err(|_| Error::Send("Caption contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_media", |buf, len| unsafe {
+            sys::wm_send_media(
+                self.handle,
+                c_jid.as_ptr(),
+                c_kind.as_ptr(),
+                data.as_ptr() as *const c_char,
+                data.len() as c_int,
+                c_mime.as_ptr(),
+                c_file_name.as_ptr(),
+                c_caption.as_ptr(),
+                buf,
+                len,
+            )
+        })
+    }
+
+    #[tracing::instrument(skip(self, payload_json), name = "ffi.send_buttons", fields(to = %jid))]
+    pub fn send_buttons(&self, jid: &str, payload_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_payload = CString::new(payload_json)
+            .map_err(|_| Error::Send("Buttons contain null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_buttons", |buf, len| unsafe {
+            sys::wm_send_buttons(self.handle, c_jid.as_ptr(), c_payload.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self, payload_json), name = "ffi.send_list", fields(to = %jid))]
+    pub fn send_list(&self, jid: &str, payload_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_payload = CString::new(payload_json)
+            .map_err(|_| Error::Send("List contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_list", |buf, len| unsafe {
+            sys::wm_send_list(self.handle, c_jid.as_ptr(), c_payload.as_ptr(), buf, len)
+        })
+    }
+
+    #[cfg(feature = "payments")]
+    #[tracing::instrument(skip(self, request_json), name = "ffi.send_payment_request", fields(to = %jid))]
+    pub fn send_payment_request(&self, jid: &str, request_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_request = CString::new(request_json)
+            .map_err(|_| Error::Send("Payment request contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_payment_request", |buf, len| unsafe {
+            sys::wm_send_payment_request(self.handle, c_jid.as_ptr(), c_request.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self, payload_json), name = "ffi.send_template", fields(to = %jid))]
+    pub fn send_template(&self, jid: &str, payload_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_payload = CString::new(payload_json)
+            .map_err(|_| Error::Send("Template contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_template", |buf, len| unsafe {
+            sys::wm_send_template(self.handle, c_jid.as_ptr(), c_payload.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self, message_json), name = "ffi.send_raw", fields(to = %jid))]
+    pub fn send_raw(&self, jid: &str, message_json: &str) -> Result<Vec<u8>> {
+        let c_jid = CString::new(jid).map_err(|_| Error::Send("JID contains null byte".into()))?;
+        let c_message = CString::new(message_json)
+            .map_err(|_| Error::Send("Message contains null byte".into()))?;
+
+        self.call_once_with_buffer("wm_send_raw", |buf, len| unsafe {
+            sys::wm_send_raw(self.handle, c_jid.as_ptr(), c_message.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.reject_call")]
+    pub fn reject_call(&self, from: &str, call_id: &str) -> Result<()> {
+        let c_from =
+            CString::new(from).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+        let c_call_id = CString::new(call_id)
+            .map_err(|_| Error::Validation("Call ID contains null byte".into()))?;
+
+        let result = self.trace("wm_reject_call", || unsafe {
+            sys::wm_reject_call(self.handle, c_from.as_ptr(), c_call_id.as_ptr())
+        });
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self, ids), name = "ffi.mark_read", fields(chat = %chat, count = ids.len()))]
+    pub fn mark_read(&self, chat: &str, sender: &str, ids: &[&str]) -> Result<()> {
+        let c_chat = CString::new(chat)
+            .map_err(|_| Error::Validation("Chat JID contains null byte".into()))?;
+        let c_sender = CString::new(sender)
+            .map_err(|_| Error::Validation("Sender JID contains null byte".into()))?;
+        let c_ids = CString::new(serde_json::to_string(ids)?)
+            .map_err(|_| Error::Validation("Message ID contains null byte".into()))?;
+
+        let result = self.trace("wm_mark_read", || unsafe {
+            sys::wm_mark_read(
+                self.handle,
+                c_chat.as_ptr(),
+                c_sender.as_ptr(),
+                c_ids.as_ptr(),
             )
         });
 
         self.check_result(result)
     }
 
+    #[tracing::instrument(skip(self, ids), name = "ffi.mark_played", fields(chat = %chat, count = ids.len()))]
+    pub fn mark_played(&self, chat: &str, sender: &str, ids: &[&str]) -> Result<()> {
+        let c_chat = CString::new(chat)
+            .map_err(|_| Error::Validation("Chat JID contains null byte".into()))?;
+        let c_sender = CString::new(sender)
+            .map_err(|_| Error::Validation("Sender JID contains null byte".into()))?;
+        let c_ids = CString::new(serde_json::to_string(ids)?)
+            .map_err(|_| Error::Validation("Message ID contains null byte".into()))?;
+
+        let result = self.trace("wm_mark_played", || unsafe {
+            sys::wm_mark_played(
+                self.handle,
+                c_chat.as_ptr(),
+                c_sender.as_ptr(),
+                c_ids.as_ptr(),
+            )
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.query_messages")]
+    pub fn query_messages(&self, chat: &str, limit: i32, before: Option<&str>) -> Result<Vec<u8>> {
+        let c_chat = CString::new(chat)
+            .map_err(|_| Error::Validation("Chat JID contains null byte".into()))?;
+        let c_before = before
+            .map(|b| {
+                CString::new(b)
+                    .map_err(|_| Error::Validation("Message ID contains null byte".into()))
+            })
+            .transpose()?;
+        let before_ptr = c_before
+            .as_ref()
+            .map(|b| b.as_ptr())
+            .unwrap_or(std::ptr::null());
+
+        self.call_with_buffer("wm_query_messages", |buf, len| unsafe {
+            sys::wm_query_messages(self.handle, c_chat.as_ptr(), limit, before_ptr, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.star_message")]
+    pub fn star_message(&self, chat: &str, id: &str, starred: bool) -> Result<()> {
+        let c_chat = CString::new(chat)
+            .map_err(|_| Error::Validation("Chat JID contains null byte".into()))?;
+        let c_id = CString::new(id)
+            .map_err(|_| Error::Validation("Message ID contains null byte".into()))?;
+
+        let result = self.trace("wm_star_message", || unsafe {
+            sys::wm_star_message(
+                self.handle,
+                c_chat.as_ptr(),
+                c_id.as_ptr(),
+                i32::from(starred),
+            )
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.starred")]
+    pub fn starred(&self, chat: &str) -> Result<Vec<u8>> {
+        let c_chat = CString::new(chat)
+            .map_err(|_| Error::Validation("Chat JID contains null byte".into()))?;
+
+        self.call_with_buffer("wm_get_starred", |buf, len| unsafe {
+            sys::wm_get_starred(self.handle, c_chat.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.resync_app_state")]
+    pub fn resync_app_state(&self, name: &str) -> Result<()> {
+        let c_name = CString::new(name)
+            .map_err(|_| Error::Validation("Collection name contains null byte".into()))?;
+
+        let result = self.trace("wm_resync_app_state", || unsafe {
+            sys::wm_resync_app_state(self.handle, c_name.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.mark_chat_unread")]
+    pub fn mark_chat_unread(&self, jid: &str) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        let result = self.trace("wm_mark_chat_unread", || unsafe {
+            sys::wm_mark_chat_unread(self.handle, c_jid.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.mute_chat")]
+    pub fn mute_chat(&self, jid: &str, mute: bool, duration_secs: i64) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        let result = self.trace("wm_mute_chat", || unsafe {
+            sys::wm_mute_chat(self.handle, c_jid.as_ptr(), mute as c_int, duration_secs)
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.clear_chat")]
+    pub fn clear_chat(&self, jid: &str) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        let result = self.trace("wm_clear_chat", || unsafe {
+            sys::wm_clear_chat(self.handle, c_jid.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.export_session")]
+    pub fn export_session(&self) -> Result<SessionBlob> {
+        self.call_with_buffer("wm_export_session", |buf, len| unsafe {
+            sys::wm_export_session(self.handle, buf, len)
+        })
+        .map(SessionBlob::from_bytes)
+    }
+
+    #[tracing::instrument(skip(self, blob), name = "ffi.import_session")]
+    pub fn import_session(&self, blob: &SessionBlob) -> Result<()> {
+        let data = blob.as_bytes();
+
+        let result = self.trace("wm_import_session", || unsafe {
+            sys::wm_import_session(
+                self.handle,
+                data.as_ptr() as *const c_char,
+                data.len() as c_int,
+            )
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.unread_counts")]
+    pub fn unread_counts(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_unread_counts", |buf, len| unsafe {
+            sys::wm_get_unread_counts(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.store_stats")]
+    pub fn store_stats(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_store_stats", |buf, len| unsafe {
+            sys::wm_get_store_stats(self.handle, buf, len)
+        })
+    }
+
+    /// Session database this handle was opened on
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.get_contact")]
+    pub fn get_contact(&self, jid: &str) -> Result<Vec<u8>> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        self.call_with_buffer("wm_get_contact", |buf, len| unsafe {
+            sys::wm_get_contact(self.handle, c_jid.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.resolve_lid")]
+    pub fn resolve_lid(&self, lid: &str) -> Result<Vec<u8>> {
+        let c_lid =
+            CString::new(lid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        self.call_with_buffer("wm_resolve_lid", |buf, len| unsafe {
+            sys::wm_resolve_lid(self.handle, c_lid.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.profile_picture")]
+    pub fn profile_picture(&self, jid: &str, full: bool) -> Result<Vec<u8>> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        self.call_with_buffer("wm_get_profile_picture", |buf, len| unsafe {
+            sys::wm_get_profile_picture(self.handle, c_jid.as_ptr(), full as c_int, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.own_jid")]
+    pub fn own_jid(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_own_jid", |buf, len| unsafe {
+            sys::wm_get_own_jid(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.server_time")]
+    pub fn server_time(&self) -> Result<Option<i64>> {
+        let millis = unsafe { sys::wm_server_time(self.handle) };
+        if millis < 0 {
+            self.check_result(millis as i32)?;
+        }
+        Ok((millis > 0).then_some(millis))
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.push_name")]
+    pub fn push_name(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_push_name", |buf, len| unsafe {
+            sys::wm_get_push_name(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.set_push_name")]
+    pub fn set_push_name(&self, name: &str) -> Result<()> {
+        let c_name = CString::new(name)
+            .map_err(|_| Error::Validation("Push name contains null byte".into()))?;
+
+        let result = self.trace("wm_set_push_name", || unsafe {
+            sys::wm_set_push_name(self.handle, c_name.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.is_on_whatsapp", fields(count = phones.len()))]
+    pub fn is_on_whatsapp(&self, phones: &[&str]) -> Result<Vec<u8>> {
+        let c_phones = CString::new(serde_json::to_string(phones)?)
+            .map_err(|_| Error::Validation("Phone number contains null byte".into()))?;
+
+        self.call_with_buffer("wm_is_on_whatsapp", |buf, len| unsafe {
+            sys::wm_is_on_whatsapp(self.handle, c_phones.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.linked_devices")]
+    pub fn linked_devices(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_linked_devices", |buf, len| unsafe {
+            sys::wm_get_linked_devices(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.joined_groups")]
+    pub fn joined_groups(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_joined_groups", |buf, len| unsafe {
+            sys::wm_get_joined_groups(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.group_info_from_link")]
+    pub fn group_info_from_link(&self, code: &str) -> Result<Vec<u8>> {
+        let c_code = CString::new(code)
+            .map_err(|_| Error::Validation("Invite code contains null byte".into()))?;
+
+        self.call_with_buffer("wm_get_group_info_from_link", |buf, len| unsafe {
+            sys::wm_get_group_info_from_link(self.handle, c_code.as_ptr(), buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.privacy_settings")]
+    pub fn privacy_settings(&self) -> Result<Vec<u8>> {
+        self.call_with_buffer("wm_get_privacy_settings", |buf, len| unsafe {
+            sys::wm_get_privacy_settings(self.handle, buf, len)
+        })
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.set_privacy_setting")]
+    pub fn set_privacy_setting(&self, key: &str, value: &str) -> Result<()> {
+        let c_key = CString::new(key)
+            .map_err(|_| Error::Validation("Privacy key contains null byte".into()))?;
+        let c_value = CString::new(value)
+            .map_err(|_| Error::Validation("Privacy value contains null byte".into()))?;
+
+        let result = self.trace("wm_set_privacy_setting", || unsafe {
+            sys::wm_set_privacy_setting(self.handle, c_key.as_ptr(), c_value.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.send_chat_presence")]
+    pub fn send_chat_presence(&self, jid: &str, state: &str, media: &str) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+        let c_state = CString::new(state)
+            .map_err(|_| Error::Validation("Presence state contains null byte".into()))?;
+        let c_media = CString::new(media)
+            .map_err(|_| Error::Validation("Presence media contains null byte".into()))?;
+
+        let result = self.trace("wm_send_chat_presence", || unsafe {
+            sys::wm_send_chat_presence(
+                self.handle,
+                c_jid.as_ptr(),
+                c_state.as_ptr(),
+                c_media.as_ptr(),
+            )
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.subscribe_presence")]
+    pub fn subscribe_presence(&self, jid: &str) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        let result = self.trace("wm_subscribe_presence", || unsafe {
+            sys::wm_subscribe_presence(self.handle, c_jid.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    #[tracing::instrument(skip(self), name = "ffi.remove_device")]
+    pub fn remove_device(&self, jid: &str) -> Result<()> {
+        let c_jid =
+            CString::new(jid).map_err(|_| Error::Validation("JID contains null byte".into()))?;
+
+        let result = self.trace("wm_remove_device", || unsafe {
+            sys::wm_remove_device(self.handle, c_jid.as_ptr())
+        });
+
+        self.check_result(result)
+    }
+
+    /// Call an FFI function with side effects that writes a small result
+    ///
+    /// Unlike [`Self::call_with_buffer`] this never retries, so the operation
+    /// cannot run twice.
+    fn call_once_with_buffer<F>(&self, name: &str, f: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce(*mut c_char, c_int) -> c_int,
+    {
+        let mut buf = vec![0u8; SEND_RESULT_BUFFER_SIZE];
+        let n = self.trace(name, || {
+            f(buf.as_mut_ptr() as *mut c_char, buf.len() as c_int)
+        });
+
+        if n < 0 {
+            self.check_result(n)?;
+        }
+
+        buf.truncate(n as usize);
+        Ok(buf)
+    }
+
+    /// Call an FFI query that writes into a caller buffer, growing it as needed
+    fn call_with_buffer<F>(&self, name: &str, f: F) -> Result<Vec<u8>>
+    where
+        F: Fn(*mut c_char, c_int) -> c_int,
+    {
+        let mut buf = vec![0u8; QUERY_BUFFER_SIZE];
+
+        loop {
+            let n = self.trace(name, || {
+                f(buf.as_mut_ptr() as *mut c_char, buf.len() as c_int)
+            });
+
+            if n == WM_ERR_BUFFER_TOO_SMALL && buf.len() < MAX_QUERY_BUFFER_SIZE {
+                debug!(
+                    operation = name,
+                    size = buf.len() * 4,
+                    "Growing query buffer"
+                );
+                buf.resize(buf.len() * 4, 0);
+                continue;
+            }
+
+            if n < 0 {
+                self.check_result(n)?;
+            }
+
+            buf.truncate(n as usize);
+            return Ok(buf);
+        }
+    }
+
+    /// Fetch the last error message recorded by the Go bridge
+    fn last_error(&self) -> Option<String> {
+        let mut buf = vec![0u8; 512];
+        let n = unsafe {
+            sys::wm_last_error(
+                self.handle,
+                buf.as_mut_ptr() as *mut c_char,
+                buf.len() as c_int,
+            )
+        };
+
+        if n <= 0 {
+            return None;
+        }
+
+        buf.truncate(n as usize);
+        Some(String::from_utf8_lossy(&buf).into_owned())
+    }
+
     fn check_result(&self, code: i32) -> Result<()> {
         match code {
             WM_OK => Ok(()),
@@ -161,6 +911,20 @@ impl FfiClient {
                 warn!(code, "FFI invalid handle");
                 Err(Error::InvalidHandle)
             }
+            WM_ERR_INVALID_ARGUMENT => {
+                warn!(code, "FFI rejected argument");
+                Err(Error::Validation(
+                    self.last_error()
+                        .unwrap_or_else(|| "Invalid argument".into()),
+                ))
+            }
+            WM_ERR_UNSUPPORTED => {
+                warn!(code, "FFI operation unsupported");
+                Err(Error::Ffi {
+                    code,
+                    message: self.last_error().unwrap_or_else(|| "Not supported".into()),
+                })
+            }
             _ => {
                 warn!(code, "FFI unknown error");
                 Err(Error::Ffi {
@@ -174,12 +938,272 @@ impl FfiClient {
 
 impl Drop for FfiClient {
     fn drop(&mut self) {
-        GLOBAL.trace_operation("wm_client_destroy", || unsafe {
-            sys::wm_client_destroy(self.handle)
-        });
+        self.destroy();
 
-        GLOBAL.print_stats();
+        #[cfg(feature = "zeroize")]
+        self.wipe_event_buffer();
+
+        #[cfg(feature = "track-allocations")]
+        {
+            let stats = GLOBAL.stats();
+            debug!(
+                current_bytes = stats.current_bytes,
+                peak_bytes = stats.peak_bytes,
+                outstanding = stats.outstanding_allocations,
+                "FFI client destroyed"
+            );
+        }
     }
 }
 
 unsafe impl Send for FfiClient {}
+
+/// Serialized access to an [`FfiClient`] with FIFO hand-off between waiters
+///
+/// The Go handle must only be used by one caller at a time. The lock is
+/// released fairly, handing the handle straight to the longest waiter, so
+/// callers acquire it in the order they queued instead of racing (and
+/// starving) each other.
+///
+/// Whoever holds the handle runs on a thread of its own: blocking callers
+/// wait with [`lock`](Self::lock), and async callers queue through
+/// [`run_async`](Self::run_async), which waits and makes the call on the
+/// blocking pool. No holder ever needs a runtime thread to make progress,
+/// so a sync API called from a handler can block its runtime thread, even
+/// on a current-thread runtime, without deadlocking the run loop.
+/// [`FfiGuard`] is `!Send`, and `clippy.toml` flags it as an await-holding
+/// type, so holding one across an `.await` fails to compile in spawned
+/// tasks and fails lint elsewhere.
+pub(crate) struct FfiLock {
+    client: Mutex<FfiClient>,
+    queued: AtomicUsize,
+    latencies: Arc<LatencyRecorder>,
+}
+
+impl FfiLock {
+    pub fn new(client: FfiClient) -> Self {
+        Self {
+            latencies: client.latencies.clone(),
+            client: Mutex::new(client),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Block until the handle is available
+    ///
+    /// For blocking callers only; from async code use [`run_async`](Self::run_async).
+    pub fn lock(&self) -> FfiGuard<'_> {
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        FfiGuard {
+            guard: Some(self.client.lock()),
+            queued: &self.queued,
+        }
+    }
+
+    /// Wait for the handle and run `f` with it, both on the blocking pool
+    ///
+    /// Queues in the same FIFO order as [`lock`](Self::lock). If this future
+    /// is dropped while still waiting, `f` is skipped once the handle comes
+    /// round; a call already started runs to completion.
+    pub async fn run_async<T, F>(self: &Arc<Self>, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut FfiClient) -> T + Send + 'static,
+    {
+        let abandoned = Arc::new(AtomicBool::new(false));
+        let _on_drop = AbandonOnDrop(abandoned.clone());
+        let lock = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut client = lock.lock();
+            (!abandoned.load(Ordering::SeqCst)).then(|| f(&mut client))
+        })
+        .await
+        // Blocking tasks can't be cancelled, so a join error is a panic in `f`
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+        result.expect("only skipped after the caller stopped waiting")
+    }
+
+    /// Replace the handle with a fresh one for the same session and connect it
+    ///
+    /// For when the bridge no longer recognises the old handle. Holds the
+    /// handle throughout, so no call straddles the swap. Everything built
+    /// around the lock (handlers, streams, caches) is untouched and simply
+    /// talks to the new handle from the next call on.
+    pub fn reopen(&self) -> Result<()> {
+        let mut client = self.lock();
+        client.reopen()?;
+        client.connect()
+    }
+
+    /// Number of callers holding or waiting for the handle
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Start timing this client's bridge calls
+    pub fn record_latencies(&self) {
+        self.latencies.enable();
+    }
+
+    /// Latency summary per bridge function, for this client's calls so far
+    pub fn latencies(&self) -> HashMap<String, LatencyStats> {
+        self.latencies.stats()
+    }
+}
+
+/// Exclusive access to the FFI client for a blocking caller
+pub(crate) struct FfiGuard<'a> {
+    guard: Option<MutexGuard<'a, FfiClient>>,
+    queued: &'a AtomicUsize,
+}
+
+impl Deref for FfiGuard<'_> {
+    type Target = FfiClient;
+
+    fn deref(&self) -> &FfiClient {
+        self.guard.as_ref().expect("guard held until drop")
+    }
+}
+
+impl DerefMut for FfiGuard<'_> {
+    fn deref_mut(&mut self) -> &mut FfiClient {
+        self.guard.as_mut().expect("guard held until drop")
+    }
+}
+
+impl Drop for FfiGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(guard) = self.guard.take() {
+            MutexGuard::unlock_fair(guard);
+        }
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Tells a queued [`FfiLock::run_async`] call that nobody awaits it any more
+struct AbandonOnDrop(Arc<AtomicBool>);
+
+impl Drop for AbandonOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+// Holding the handle across awaits is how these tests line waiters up
+#[allow(clippy::await_holding_invalid_type)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::mock_ffi;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn blocking_and_async_sends_go_out_in_queue_order() {
+        let path = mock_ffi::db_path("fair-order");
+        let client = FfiClient::new(&path, "test", &StoreOptions::default()).unwrap();
+        let mock = mock_ffi::clients_at(&path)[0];
+        let lock = Arc::new(FfiLock::new(client));
+
+        // Hold the handle while the senders line up behind it
+        let held = lock.lock();
+        let mut tasks = Vec::new();
+        for i in 0..6 {
+            let sender = lock.clone();
+            let text = i.to_string();
+            tasks.push(if i % 2 == 0 {
+                tokio::spawn(async move {
+                    sender
+                        .run_async(move |client| client.send_message("1@s.whatsapp.net", &text))
+                        .await
+                })
+            } else {
+                tokio::task::spawn_blocking(move || {
+                    sender.lock().send_message("1@s.whatsapp.net", &text)
+                })
+            });
+            while lock.queue_depth() < i + 2 {
+                tokio::task::yield_now().await;
+            }
+            // Counted just before parking; let the waiter actually park
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        drop(held);
+
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        let sent: Vec<String> = mock
+            .calls("wm_send_message")
+            .into_iter()
+            .map(|args| args[1].clone())
+            .collect();
+        assert_eq!(sent, ["0", "1", "2", "3", "4", "5"]);
+        assert_eq!(lock.queue_depth(), 0);
+    }
+
+    #[test]
+    fn reopen_destroys_the_old_handle_before_opening_a_new_one() {
+        let path = mock_ffi::db_path("reopen-order");
+        let client = FfiClient::new(&path, "test", &StoreOptions::default()).unwrap();
+        let lock = FfiLock::new(client);
+
+        lock.reopen().unwrap();
+        let [old, new] = mock_ffi::clients_at(&path)[..] else {
+            panic!("expected two opens");
+        };
+        assert!(old.is_destroyed());
+        assert_eq!(new.opened_alongside(), 0);
+        assert_eq!(new.calls("wm_client_connect").len(), 1);
+
+        drop(lock);
+        assert!(new.is_destroyed());
+    }
+
+    #[tokio::test]
+    async fn an_abandoned_async_call_is_skipped() {
+        let path = mock_ffi::db_path("abandoned-waiter");
+        let client = FfiClient::new(&path, "test", &StoreOptions::default()).unwrap();
+        let mock = mock_ffi::clients_at(&path)[0];
+        let lock = Arc::new(FfiLock::new(client));
+
+        let held = lock.lock();
+        let waiter = tokio::time::timeout(
+            Duration::from_millis(10),
+            lock.run_async(|client| client.send_message("1@s.whatsapp.net", "late")),
+        )
+        .await;
+        assert!(waiter.is_err());
+        drop(held);
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while lock.queue_depth() > 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("abandoned waiter still queued");
+        assert!(mock.calls("wm_send_message").is_empty());
+        drop(lock.lock());
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn the_event_buffer_is_wiped_on_drop() {
+        let path = mock_ffi::db_path("zeroize-events");
+        let mut client = FfiClient::new(&path, "test", &StoreOptions::default()).unwrap();
+        mock_ffi::clients_at(&path)[0].push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "1@s.whatsapp.net", "secret"),
+        );
+        let event = client.poll_event().unwrap().unwrap();
+        let (ptr, len) = (client.event_buffer.as_ptr(), event.len());
+        assert_eq!(&client.event_buffer[..len], &event[..]);
+
+        client.wipe_event_buffer();
+
+        // The allocation outlives the wipe, so its old contents can be read
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(bytes.iter().all(|&b| b == 0));
+    }
+}
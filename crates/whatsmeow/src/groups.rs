@@ -0,0 +1,199 @@
+//! Group types
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::events::Jid;
+use crate::timestamp::parse_rfc3339;
+
+/// Prefix of WhatsApp group invite links
+const INVITE_LINK_PREFIX: &str = "chat.whatsapp.com/";
+
+/// Group name, description and members
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupMetadata {
+    pub jid: Jid,
+    pub name: String,
+    /// Group description
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Creator, when known
+    #[serde(default)]
+    pub owner: Option<Jid>,
+    /// Creation time (RFC 3339)
+    pub created: String,
+    /// Only admins can send messages
+    #[serde(default)]
+    pub is_announce: bool,
+    /// Only admins can edit group info
+    #[serde(default)]
+    pub is_locked: bool,
+    pub participant_count: usize,
+    #[serde(default)]
+    pub participants: Vec<GroupParticipant>,
+}
+
+impl GroupMetadata {
+    /// Parse the creation time
+    pub fn created_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.created)
+    }
+}
+
+/// Member of a group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupParticipant {
+    pub jid: Jid,
+    #[serde(default)]
+    pub is_admin: bool,
+    #[serde(default)]
+    pub is_super_admin: bool,
+}
+
+/// Extract and validate the code from an invite link or bare code
+pub(crate) fn invite_code(link: &str) -> Result<&str> {
+    let link = link.trim();
+    let code = match link.find(INVITE_LINK_PREFIX) {
+        Some(pos) => {
+            let scheme = &link[..pos];
+            if !matches!(scheme, "" | "https://" | "http://") {
+                return Err(Error::Validation(format!(
+                    "Not a group invite link: {}",
+                    link
+                )));
+            }
+            link[pos + INVITE_LINK_PREFIX.len()..].trim_end_matches('/')
+        }
+        None if link.contains('/') => {
+            return Err(Error::Validation(format!(
+                "Not a group invite link: {}",
+                link
+            )));
+        }
+        None => link,
+    };
+
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Error::Validation(format!(
+            "Invalid invite code: {:?}",
+            code
+        )));
+    }
+
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    #[test]
+    fn invite_links_and_bare_codes_are_accepted() {
+        for link in [
+            "https://chat.whatsapp.com/AbC123",
+            "http://chat.whatsapp.com/AbC123/",
+            "chat.whatsapp.com/AbC123",
+            "  AbC123 ",
+        ] {
+            assert_eq!(invite_code(link).unwrap(), "AbC123", "{}", link);
+        }
+    }
+
+    #[test]
+    fn other_links_and_bad_codes_are_rejected() {
+        for link in [
+            "https://example.com/chat.whatsapp.com/AbC123",
+            "https://example.com/AbC123",
+            "https://chat.whatsapp.com/",
+            "AbC-123",
+            "",
+        ] {
+            assert!(
+                matches!(invite_code(link), Err(Error::Validation(_))),
+                "{}",
+                link
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn preview_parses_metadata_for_the_code() {
+        let (client, mock) = mock_ffi::client("group-preview").await;
+        mock.reply(
+            "wm_get_group_info_from_link",
+            Reply::ok(
+                br#"{
+                    "jid": "123456789-987654321@g.us",
+                    "name": "Book club",
+                    "topic": "Monthly reads",
+                    "created": "2024-01-01T00:00:00Z",
+                    "is_announce": true,
+                    "participant_count": 2,
+                    "participants": [
+                        {"jid": "15550001111@s.whatsapp.net", "is_admin": true},
+                        {"jid": "15550002222@s.whatsapp.net"}
+                    ]
+                }"#
+                .to_vec(),
+            ),
+        );
+
+        let group = client
+            .preview_group("https://chat.whatsapp.com/AbC123")
+            .unwrap();
+
+        assert_eq!(mock.calls("wm_get_group_info_from_link"), [vec!["AbC123"]]);
+        assert_eq!(group.name, "Book club");
+        assert_eq!(group.topic.as_deref(), Some("Monthly reads"));
+        assert_eq!(group.owner, None);
+        assert!(group.is_announce);
+        assert!(!group.is_locked);
+        assert_eq!(group.participant_count, 2);
+        assert!(group.participants[0].is_admin);
+        assert!(!group.participants[1].is_admin);
+        assert_eq!(
+            group.created_at(),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_704_067_200))
+        );
+    }
+
+    #[tokio::test]
+    async fn every_joined_group_gets_a_mute_call() {
+        let (client, mock) = mock_ffi::client("mute-all-groups").await;
+        mock.reply(
+            "wm_get_joined_groups",
+            Reply::ok(
+                br#"[
+                    {"jid": "111-1@g.us", "name": "One", "created": "", "participant_count": 3},
+                    {"jid": "222-2@g.us", "name": "Two", "created": "", "participant_count": 5}
+                ]"#
+                .to_vec(),
+            ),
+        );
+        mock.reply(
+            "wm_mute_chat",
+            Reply::err(whatsmeow_sys::error_codes::WM_ERR_UNSUPPORTED),
+        );
+
+        let results = client
+            .mute_all_groups(Some(std::time::Duration::from_secs(3600)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            mock.calls("wm_mute_chat"),
+            [
+                vec!["111-1@g.us".to_string(), "1".into(), "3600".into()],
+                vec!["222-2@g.us".to_string(), "1".into(), "3600".into()],
+            ]
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, Jid::from("111-1@g.us"));
+        // One group failing doesn't stop the others
+        assert!(results[0].1.is_err());
+        assert!(results[1].1.is_ok());
+    }
+}
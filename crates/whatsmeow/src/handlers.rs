@@ -1,11 +1,17 @@
 //! Callback-based event handling with async support
 
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use crate::events::{Event, MessageEvent, PresenceEvent, QrEvent, ReceiptEvent};
+use crate::appstate::ChatDeletedEvent;
+use crate::events::{
+    EphemeralSettingEvent, Event, MessageEditedEvent, MessageEvent, PresenceEvent, QrEvent,
+    ReceiptEvent,
+};
+use crate::interactive::InteractiveResponseEvent;
 
 /// Boxed future type for async callbacks
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -13,11 +19,49 @@ pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 /// Async callback type
 type AsyncCallback<T> = Arc<dyn Fn(T) -> BoxFuture<'static, ()> + Send + Sync + 'static>;
 
+/// Number of callbacks registered per event type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct HandlerStats {
+    pub qr: usize,
+    pub message: usize,
+    pub message_edited: usize,
+    pub ephemeral_setting: usize,
+    pub interactive_response: usize,
+    pub chat_deleted: usize,
+    pub connected: usize,
+    pub reconnected: usize,
+    pub disconnected: usize,
+    pub receipt: usize,
+    pub presence: usize,
+}
+
+impl HandlerStats {
+    /// Callbacks registered across all event types
+    pub fn total(&self) -> usize {
+        self.qr
+            + self.message
+            + self.message_edited
+            + self.ephemeral_setting
+            + self.interactive_response
+            + self.chat_deleted
+            + self.connected
+            + self.reconnected
+            + self.disconnected
+            + self.receipt
+            + self.presence
+    }
+}
+
 /// Registry for event callbacks (supports async)
 pub(crate) struct Handlers {
     on_qr: RwLock<Vec<AsyncCallback<QrEvent>>>,
     on_message: RwLock<Vec<AsyncCallback<MessageEvent>>>,
+    on_message_edited: RwLock<Vec<AsyncCallback<MessageEditedEvent>>>,
+    on_ephemeral_setting: RwLock<Vec<AsyncCallback<EphemeralSettingEvent>>>,
+    on_interactive_response: RwLock<Vec<AsyncCallback<InteractiveResponseEvent>>>,
+    on_chat_deleted: RwLock<Vec<AsyncCallback<ChatDeletedEvent>>>,
     on_connected: RwLock<Vec<AsyncCallback<()>>>,
+    on_reconnected: RwLock<Vec<AsyncCallback<()>>>,
     on_disconnected: RwLock<Vec<AsyncCallback<()>>>,
     on_receipt: RwLock<Vec<AsyncCallback<ReceiptEvent>>>,
     on_presence: RwLock<Vec<AsyncCallback<PresenceEvent>>>,
@@ -28,7 +72,12 @@ impl Handlers {
         Self {
             on_qr: RwLock::new(Vec::new()),
             on_message: RwLock::new(Vec::new()),
+            on_message_edited: RwLock::new(Vec::new()),
+            on_ephemeral_setting: RwLock::new(Vec::new()),
+            on_interactive_response: RwLock::new(Vec::new()),
+            on_chat_deleted: RwLock::new(Vec::new()),
             on_connected: RwLock::new(Vec::new()),
+            on_reconnected: RwLock::new(Vec::new()),
             on_disconnected: RwLock::new(Vec::new()),
             on_receipt: RwLock::new(Vec::new()),
             on_presence: RwLock::new(Vec::new()),
@@ -53,6 +102,62 @@ impl Handlers {
             .push(Arc::new(move |e| Box::pin(f(e))));
     }
 
+    /// Register a message handler that only runs for messages matching `predicate`
+    pub fn register_message_where<P, F, Fut>(&self, predicate: P, f: F)
+    where
+        P: Fn(&MessageEvent) -> bool + Send + Sync + 'static,
+        F: Fn(MessageEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_message.write().push(Arc::new(move |e| {
+            if predicate(&e) {
+                Box::pin(f(e))
+            } else {
+                Box::pin(async {})
+            }
+        }));
+    }
+
+    pub fn register_message_edited<F, Fut>(&self, f: F)
+    where
+        F: Fn(MessageEditedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_message_edited
+            .write()
+            .push(Arc::new(move |e| Box::pin(f(e))));
+    }
+
+    pub fn register_ephemeral_setting<F, Fut>(&self, f: F)
+    where
+        F: Fn(EphemeralSettingEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_ephemeral_setting
+            .write()
+            .push(Arc::new(move |e| Box::pin(f(e))));
+    }
+
+    pub fn register_interactive_response<F, Fut>(&self, f: F)
+    where
+        F: Fn(InteractiveResponseEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_interactive_response
+            .write()
+            .push(Arc::new(move |e| Box::pin(f(e))));
+    }
+
+    pub fn register_chat_deleted<F, Fut>(&self, f: F)
+    where
+        F: Fn(ChatDeletedEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_chat_deleted
+            .write()
+            .push(Arc::new(move |e| Box::pin(f(e))));
+    }
+
     pub fn register_connected<F, Fut>(&self, f: F)
     where
         F: Fn(()) -> Fut + Send + Sync + 'static,
@@ -63,6 +168,16 @@ impl Handlers {
             .push(Arc::new(move |e| Box::pin(f(e))));
     }
 
+    pub fn register_reconnected<F, Fut>(&self, f: F)
+    where
+        F: Fn(()) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_reconnected
+            .write()
+            .push(Arc::new(move |e| Box::pin(f(e))));
+    }
+
     pub fn register_disconnected<F, Fut>(&self, f: F)
     where
         F: Fn(()) -> Fut + Send + Sync + 'static,
@@ -73,64 +188,111 @@ impl Handlers {
             .push(Arc::new(move |e| Box::pin(f(e))));
     }
 
-    /// Dispatch event to all registered handlers (spawns tasks for async execution)
-    pub fn dispatch(&self, event: &Event) {
+    pub fn stats(&self) -> HandlerStats {
+        HandlerStats {
+            qr: self.on_qr.read().len(),
+            message: self.on_message.read().len(),
+            message_edited: self.on_message_edited.read().len(),
+            ephemeral_setting: self.on_ephemeral_setting.read().len(),
+            interactive_response: self.on_interactive_response.read().len(),
+            chat_deleted: self.on_chat_deleted.read().len(),
+            connected: self.on_connected.read().len(),
+            reconnected: self.on_reconnected.read().len(),
+            disconnected: self.on_disconnected.read().len(),
+            receipt: self.on_receipt.read().len(),
+            presence: self.on_presence.read().len(),
+        }
+    }
+
+    /// Tasks for the reconnect hooks, run after every `Connected` but the first
+    pub fn reconnected_jobs(&self) -> Vec<BoxFuture<'static, ()>> {
+        calls(&self.on_reconnected, &())
+    }
+
+    /// Build one task per registered handler interested in the event
+    ///
+    /// Handlers are invoked lazily, when the returned futures are first polled.
+    pub fn jobs(&self, event: &Event) -> Vec<BoxFuture<'static, ()>> {
         match event {
-            Event::Qr(data) => {
-                let handlers = self.on_qr.read().clone();
-                let data = data.clone();
-                for h in handlers {
-                    let data = data.clone();
-                    tokio::spawn(async move { h(data).await });
-                }
-            }
-            Event::Message(data) => {
-                let handlers = self.on_message.read().clone();
-                let data = data.clone();
-                for h in handlers {
-                    let data = data.clone();
-                    tokio::spawn(async move { h(data).await });
-                }
-            }
-            Event::Connected | Event::PairSuccess(_) => {
-                let handlers = self.on_connected.read().clone();
-                for h in handlers {
-                    tokio::spawn(async move { h(()).await });
-                }
-            }
-            Event::Disconnected | Event::LoggedOut(_) => {
-                let handlers = self.on_disconnected.read().clone();
-                for h in handlers {
-                    tokio::spawn(async move { h(()).await });
-                }
-            }
-            Event::Receipt(data) => {
-                let handlers = self.on_receipt.read().clone();
-                let data = data.clone();
-                for h in handlers {
-                    let data = data.clone();
-                    tokio::spawn(async move { h(data).await });
-                }
-            }
-            Event::Presence(data) => {
-                let handlers = self.on_presence.read().clone();
-                let data = data.clone();
-                for h in handlers {
-                    let data = data.clone();
-                    tokio::spawn(async move { h(data).await });
-                }
+            Event::Qr(data) => calls(&self.on_qr, data),
+            Event::Message(data) => calls(&self.on_message, data),
+            Event::MessageEdited(data) => calls(&self.on_message_edited, data),
+            Event::EphemeralSettingChanged(data) => calls(&self.on_ephemeral_setting, data),
+            Event::InteractiveResponse(data) => calls(&self.on_interactive_response, data),
+            Event::ChatDeleted(data) => calls(&self.on_chat_deleted, data),
+            Event::Connected | Event::PairSuccess(_) => calls(&self.on_connected, &()),
+            Event::Disconnected | Event::LoggedOut(_) | Event::StreamReplaced => {
+                calls(&self.on_disconnected, &())
             }
+            Event::Receipt(data) => calls(&self.on_receipt, data),
+            Event::Presence(data) => calls(&self.on_presence, data),
             // Ignored events
             Event::HistorySync
+            | Event::HistoryChunk(_)
             | Event::OfflineSyncPreview(_)
             | Event::OfflineSyncCompleted(_)
-            | Event::Unknown { .. } => {}
+            | Event::AppStateSynced(_)
+            | Event::AppStateKeyReceived
+            | Event::CallOffer(_)
+            | Event::PollVote(_)
+            | Event::DecryptionFailure(_)
+            | Event::Protocol(_)
+            | Event::System(_)
+            | Event::StreamLagged { .. }
+            | Event::Unknown { .. } => Vec::new(),
+            #[cfg(feature = "payments")]
+            Event::Payment(_) => Vec::new(),
         }
     }
 }
 
+fn calls<T>(handlers: &RwLock<Vec<AsyncCallback<T>>>, data: &T) -> Vec<BoxFuture<'static, ()>>
+where
+    T: Clone + Send + 'static,
+{
+    handlers
+        .read()
+        .iter()
+        .map(|h| {
+            let h = h.clone();
+            let data = data.clone();
+            Box::pin(async move { h(data).await }) as BoxFuture<'static, ()>
+        })
+        .collect()
+}
+
 impl Default for Handlers {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WhatsApp;
+    use crate::mock_ffi;
+
+    #[tokio::test]
+    async fn registrations_are_counted_per_event_type() {
+        let client = WhatsApp::connect(mock_ffi::db_path("handler-stats"))
+            .on_message(|_| async {})
+            .on_message(|_| async {})
+            .on_connected(|_| async {})
+            .build()
+            .await
+            .unwrap();
+
+        let stats = client.handler_stats();
+
+        assert_eq!(
+            stats,
+            HandlerStats {
+                message: 2,
+                connected: 1,
+                ..HandlerStats::default()
+            }
+        );
+        assert_eq!(stats.total(), 3);
+    }
+}
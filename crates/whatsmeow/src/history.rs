@@ -0,0 +1,147 @@
+//! Locally stored message history
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::events::Jid;
+use crate::timestamp::parse_rfc3339;
+
+/// A message read back from the bridge's local history store
+///
+/// Populated from live messages and history sync, so it mirrors the
+/// fields of [`MessageInfo`](crate::MessageInfo) plus the message body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub id: String,
+    pub chat: Jid,
+    pub sender: Jid,
+    pub is_from_me: bool,
+    #[serde(default)]
+    pub push_name: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub message_type: String,
+    /// Text or caption, empty for messages without one
+    #[serde(default)]
+    pub text: String,
+    /// Raw message content as JSON
+    #[serde(default)]
+    pub message: Option<Value>,
+}
+
+impl StoredMessage {
+    /// Parse the message timestamp
+    pub fn timestamp_at(&self) -> Option<SystemTime> {
+        parse_rfc3339(&self.timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    fn stored(id: &str) -> Value {
+        serde_json::json!({
+            "id": id,
+            "chat": "1@s.whatsapp.net",
+            "sender": "2@s.whatsapp.net",
+            "is_from_me": false,
+            "timestamp": "2024-01-01T00:00:00Z",
+            "text": format!("message {id}"),
+            "message": { "conversation": format!("message {id}") },
+        })
+    }
+
+    #[tokio::test]
+    async fn stored_messages_are_parsed() {
+        let (client, mock) = mock_ffi::client("history-parse").await;
+        let page = serde_json::to_vec(&[stored("A"), stored("B")]).unwrap();
+        mock.reply("wm_query_messages", Reply::ok(page));
+
+        let messages = client.messages("1@s.whatsapp.net", 2, None).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "A");
+        assert_eq!(messages[1].sender, Jid::from("2@s.whatsapp.net"));
+        assert_eq!(messages[1].text, "message B");
+        assert!(messages[0].push_name.is_empty());
+        assert_eq!(
+            messages[0].timestamp_at(),
+            Some(UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+    }
+
+    #[tokio::test]
+    async fn the_cursor_and_limit_reach_the_bridge() {
+        let (client, mock) = mock_ffi::client("history-cursor").await;
+
+        assert!(
+            client
+                .messages("1@s.whatsapp.net", 20, None)
+                .unwrap()
+                .is_empty()
+        );
+        client.messages("1@s.whatsapp.net", 20, Some("A")).unwrap();
+        assert!(
+            client
+                .messages("1@s.whatsapp.net", 0, Some("A"))
+                .unwrap()
+                .is_empty()
+        );
+
+        assert_eq!(
+            mock.calls("wm_query_messages"),
+            [
+                vec!["1@s.whatsapp.net".to_string(), "20".into(), String::new()],
+                vec!["1@s.whatsapp.net".to_string(), "20".into(), "A".into()],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn star_flags_reach_the_bridge_and_unknown_ids_fail() {
+        let (client, mock) = mock_ffi::client("star-message").await;
+        client.star("1@s.whatsapp.net", "A", true).unwrap();
+        client.star("1@s.whatsapp.net", "A", false).unwrap();
+        mock.set_last_error("message B not found");
+        mock.reply(
+            "wm_star_message",
+            Reply::err(whatsmeow_sys::error_codes::WM_ERR_INVALID_ARGUMENT),
+        );
+
+        let unknown = client.star("1@s.whatsapp.net", "B", true);
+
+        assert!(
+            matches!(unknown, Err(crate::Error::Validation(ref msg)) if msg.contains("not found"))
+        );
+        assert_eq!(
+            mock.calls("wm_star_message"),
+            [
+                vec!["1@s.whatsapp.net".to_string(), "A".into(), "1".into()],
+                vec!["1@s.whatsapp.net".to_string(), "A".into(), "0".into()],
+                vec!["1@s.whatsapp.net".to_string(), "B".into(), "1".into()],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn starred_messages_are_parsed() {
+        let (client, mock) = mock_ffi::client("starred-list").await;
+        mock.reply(
+            "wm_get_starred",
+            Reply::ok(serde_json::to_vec(&[stored("A"), stored("C")]).unwrap()),
+        );
+
+        let starred = client.starred("1@s.whatsapp.net").unwrap();
+
+        let ids: Vec<&str> = starred.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["A", "C"]);
+        assert_eq!(mock.calls("wm_get_starred"), [vec!["1@s.whatsapp.net"]]);
+        assert!(client.starred("1@s.whatsapp.net").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+//! Streaming history sync payloads from disk
+//!
+//! A full history sync can be hundreds of megabytes, far beyond the event
+//! buffer. The bridge writes its conversations to a temp file instead, one
+//! JSON document per line, and the event loop reads them back a few at a
+//! time as [`Event::HistoryChunk`]s.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::de::IoRead;
+
+use crate::events::{Event, RawEvent};
+
+/// Conversations per [`HistoryChunk`]
+const HISTORY_CHUNK_SIZE: usize = 16;
+
+/// A batch of conversations from one history sync
+///
+/// Chunks of a sync arrive in order, followed by [`Event::HistorySync`]
+/// once all of them were delivered.
+#[derive(Debug, Clone)]
+pub struct HistoryChunk {
+    /// Kind of sync, e.g. `"INITIAL_BOOTSTRAP"`, `"RECENT"` or `"FULL"`
+    pub sync_type: String,
+    /// Position of this chunk within the sync, from 0
+    pub index: usize,
+    /// Conversations in the sync overall
+    pub total: usize,
+    /// Conversations in WhatsApp's JSON form (`id`, `messages`, `unreadCount`, ...)
+    pub conversations: Vec<Value>,
+}
+
+/// Event data naming the file a history sync was written to
+#[derive(Debug, Deserialize)]
+struct HistorySyncFile {
+    path: PathBuf,
+    sync_type: String,
+    conversations: usize,
+}
+
+type Conversations = serde_json::StreamDeserializer<'static, IoRead<BufReader<File>>, Value>;
+
+/// Reads a spilled history sync back chunk by chunk, deleting the file when done
+pub(crate) struct HistoryReader {
+    path: Option<PathBuf>,
+    sync_type: String,
+    total: usize,
+    index: usize,
+    conversations: Option<Conversations>,
+    finished: bool,
+}
+
+impl HistoryReader {
+    /// The event type the bridge uses for spilled history syncs
+    pub const EVENT_TYPE: &'static str = "history_sync_file";
+
+    /// Open the file named by a `history_sync_file` event
+    ///
+    /// A file that can't be read still yields the closing
+    /// [`Event::HistorySync`], just without chunks.
+    pub fn open(raw: RawEvent) -> Self {
+        let file = raw
+            .data
+            .and_then(|data| serde_json::from_value::<HistorySyncFile>(data).ok());
+        let Some(file) = file else {
+            tracing::warn!("Malformed history sync file event");
+            return Self::empty();
+        };
+
+        let conversations = match File::open(&file.path) {
+            Ok(f) => Some(serde_json::Deserializer::from_reader(BufReader::new(f)).into_iter()),
+            Err(e) => {
+                tracing::warn!(error = %e, path = %file.path.display(), "Cannot open history sync file");
+                None
+            }
+        };
+
+        Self {
+            path: Some(file.path),
+            sync_type: file.sync_type,
+            total: file.conversations,
+            index: 0,
+            conversations,
+            finished: false,
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            path: None,
+            sync_type: String::new(),
+            total: 0,
+            index: 0,
+            conversations: None,
+            finished: false,
+        }
+    }
+
+    /// The next chunk, then [`Event::HistorySync`], then `None`
+    pub fn next_event(&mut self) -> Option<Event> {
+        if self.finished {
+            return None;
+        }
+
+        let mut batch = Vec::new();
+        if let Some(conversations) = &mut self.conversations {
+            for conversation in conversations.by_ref().take(HISTORY_CHUNK_SIZE) {
+                match conversation {
+                    Ok(conversation) => batch.push(conversation),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Truncated history sync file");
+                        self.conversations = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if batch.is_empty() {
+            self.finished = true;
+            self.remove_file();
+            return Some(Event::HistorySync);
+        }
+
+        let chunk = HistoryChunk {
+            sync_type: self.sync_type.clone(),
+            index: self.index,
+            total: self.total,
+            conversations: batch,
+        };
+        self.index += 1;
+        Some(Event::HistoryChunk(chunk))
+    }
+
+    fn remove_file(&mut self) {
+        self.conversations = None;
+        if let Some(path) = self.path.take()
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            tracing::debug!(error = %e, path = %path.display(), "Cannot remove history sync file");
+        }
+    }
+}
+
+impl Drop for HistoryReader {
+    fn drop(&mut self) {
+        self.remove_file();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock_ffi;
+
+    /// Spill `count` conversations of about 4 KiB each, as the bridge would
+    fn spill(test: &str, count: usize) -> PathBuf {
+        let path = mock_ffi::db_path(test).with_extension("history.jsonl");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = std::io::BufWriter::new(File::create(&path).unwrap());
+        for i in 0..count {
+            let conversation = json!({
+                "id": format!("{i}@s.whatsapp.net"),
+                "unreadCount": i,
+                "messages": [{ "message": { "conversation": "x".repeat(4096) } }],
+            });
+            writeln!(file, "{}", conversation).unwrap();
+        }
+        file.flush().unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn a_large_spilled_sync_arrives_in_ordered_chunks() {
+        let (client, mock) = mock_ffi::client("history-spill").await;
+        let path = spill("history-spill", 100);
+        assert!(std::fs::metadata(&path).unwrap().len() > 64 * 1024);
+        let mut events = client.events();
+        mock.push_event(
+            HistoryReader::EVENT_TYPE,
+            json!({ "path": path, "sync_type": "INITIAL_BOOTSTRAP", "conversations": 100 }),
+        );
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "1@s.whatsapp.net", "after"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let mut ids = Vec::new();
+        for index in 0..7 {
+            match mock_ffi::next_event(&mut events).await {
+                Event::HistoryChunk(chunk) => {
+                    assert_eq!(chunk.index, index);
+                    assert_eq!(chunk.total, 100);
+                    assert_eq!(chunk.sync_type, "INITIAL_BOOTSTRAP");
+                    ids.extend(chunk.conversations.iter().map(|c| c["id"].clone()));
+                }
+                other => panic!("expected a history chunk, got {:?}", other),
+            }
+        }
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::HistorySync
+        ));
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Message(_)
+        ));
+
+        let expected: Vec<Value> = (0..100)
+            .map(|i| json!(format!("{i}@s.whatsapp.net")))
+            .collect();
+        assert_eq!(ids, expected);
+        assert!(!path.exists());
+        client.disconnect();
+    }
+
+    #[test]
+    fn a_missing_file_still_closes_the_sync() {
+        let raw = RawEvent {
+            event_type: HistoryReader::EVENT_TYPE.into(),
+            timestamp: 0,
+            data: Some(json!({
+                "path": mock_ffi::db_path("history-missing"),
+                "sync_type": "RECENT",
+                "conversations": 3,
+            })),
+        };
+        let mut reader = HistoryReader::open(raw);
+
+        assert!(matches!(reader.next_event(), Some(Event::HistorySync)));
+        assert!(reader.next_event().is_none());
+    }
+}
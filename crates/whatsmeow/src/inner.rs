@@ -1,141 +1,1559 @@
 //! Internal client state
 
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use parking_lot::Mutex;
-use tokio::sync::watch;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use tokio::sync::{mpsc, watch};
+use tokio_util::sync::CancellationToken;
 
-use crate::error::Result;
+use crate::appstate::AppStateCollection;
+use crate::coalesce::PresenceCoalescer;
+use crate::config::ClientConfig;
+use crate::contact_book::ContactBook;
+use crate::contacts::ContactCache;
+use crate::dedup::SeenMessages;
+use crate::dispatch::{Dispatcher, EventOrder};
+use crate::error::{Error, Result};
 use crate::event_bus::EventBus;
-use crate::events::RawEvent;
-use crate::ffi::FfiClient;
+use crate::events::{Event, Jid, RawEvent};
+use crate::ffi::{FfiClient, FfiLock};
 use crate::handlers::Handlers;
-use crate::stream::EventStream;
+use crate::history_sync::HistoryReader;
+use crate::latency::LatencyStats;
+use crate::metrics::Metrics;
+use crate::outgoing::Outgoing;
+use crate::reconnect::Backoff;
+use crate::send_queue::SendQueue;
+use crate::session::SessionBlob;
+use crate::stream::{EnvelopeStream, EventStream};
+use crate::typing::ChatPresence;
 
 /// Set to true to save one sample of each raw event type to debug_events/
 const DEBUG_SAVE_EVENTS: bool = false;
 
+/// Wait after a failed event poll before polling again
+const POLL_ERROR_PAUSE: Duration = Duration::from_millis(100);
+
+/// How long a recipient found on WhatsApp skips the check before sends
+const VERIFIED_RECIPIENT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Events held back while paused; beyond this the oldest are dropped
+const PAUSE_BUFFER_CAPACITY: usize = 1024;
+
+/// Queue an event while paused, dropping the oldest when full
+fn hold(
+    held: &mut VecDeque<(Event, Option<serde_json::Value>)>,
+    event: Event,
+    raw: Option<serde_json::Value>,
+) {
+    if held.len() == PAUSE_BUFFER_CAPACITY {
+        held.pop_front();
+        tracing::warn!("Pause buffer full, dropping oldest event");
+    }
+    held.push_back((event, raw));
+}
+
+/// State shared by every clone of a client
+///
+/// The bridge handle sits behind `ffi` and may be swapped in place (see
+/// [`FfiLock::reopen`]); handlers, the event bus and caches live here and
+/// outlast any one handle.
 pub(crate) struct InnerClient {
-    pub ffi: Arc<Mutex<FfiClient>>,
+    pub ffi: Arc<FfiLock>,
     pub event_bus: EventBus,
     pub handlers: Arc<Handlers>,
+    pub contacts: Arc<ContactCache>,
+    pub contact_book: ContactBook,
+    pub send_queue: SendQueue,
+    seen: SeenMessages,
+    /// Recipients last found on WhatsApp, and when
+    verified: Mutex<HashMap<Jid, Instant>>,
+    config: RwLock<ClientConfig>,
     shutdown_tx: watch::Sender<bool>,
-    shutdown_rx: watch::Receiver<bool>,
     connected: AtomicBool,
+    /// Set by the first `Connected`; later ones are reconnects
+    ever_connected: AtomicBool,
+    closed: AtomicBool,
+    /// True while `run` is executing
+    loop_running: watch::Sender<bool>,
+    paused: AtomicBool,
+    reconnecting: Arc<AtomicBool>,
+    reconnects: Arc<AtomicU64>,
+    events_received: AtomicU64,
+    /// `WhatsApp` values keeping the client open, see [`release_handle`](Self::release_handle)
+    handles: AtomicUsize,
+    /// Events raised outside the bridge, delivered by the run loop
+    injected_tx: mpsc::UnboundedSender<Event>,
+    injected_rx: Mutex<mpsc::UnboundedReceiver<Event>>,
 }
 
 impl InnerClient {
-    pub fn new(ffi: FfiClient) -> Self {
-        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    pub fn new(ffi: FfiClient, handlers: Handlers) -> Self {
+        let shutdown_tx = watch::Sender::new(false);
+        let (injected_tx, injected_rx) = mpsc::unbounded_channel();
 
         Self {
-            ffi: Arc::new(Mutex::new(ffi)),
+            ffi: Arc::new(FfiLock::new(ffi)),
             event_bus: EventBus::new(),
-            handlers: Arc::new(Handlers::new()),
+            handlers: Arc::new(handlers),
+            contacts: Arc::new(ContactCache::new()),
+            contact_book: ContactBook::new(),
+            send_queue: SendQueue::new(ClientConfig::default().batch_limit()),
+            seen: SeenMessages::new(),
+            verified: Mutex::new(HashMap::new()),
+            config: RwLock::new(ClientConfig::default()),
             shutdown_tx,
-            shutdown_rx,
             connected: AtomicBool::new(false),
+            ever_connected: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            loop_running: watch::Sender::new(false),
+            paused: AtomicBool::new(false),
+            reconnecting: Arc::new(AtomicBool::new(false)),
+            reconnects: Arc::new(AtomicU64::new(0)),
+            events_received: AtomicU64::new(0),
+            handles: AtomicUsize::new(0),
+            injected_tx,
+            injected_rx: Mutex::new(injected_rx),
         }
     }
 
+    /// Apply builder options (called once before connecting)
+    pub fn configure(&self, config: ClientConfig) {
+        if let Some(path) = &config.dedup_path
+            && let Err(e) = self.seen.restore(path)
+        {
+            tracing::warn!(error = %e, path = %path.display(), "Failed to load seen messages");
+        }
+        // A batch must not be funnelled through fewer slots than it may use
+        self.send_queue.set_slots(config.batch_limit());
+        *self.config.write() = config;
+    }
+
+    pub fn config(&self) -> RwLockReadGuard<'_, ClientConfig> {
+        self.config.read()
+    }
+
     #[tracing::instrument(skip(self), name = "whatsapp.connect")]
-    pub async fn connect(&self) -> Result<()> {
+    pub fn connect(&self) -> Result<()> {
         tracing::info!("Connecting to WhatsApp");
         self.ffi.lock().connect()?;
         self.connected.store(true, Ordering::SeqCst);
+        // Reconnecting after `disconnect` makes the client usable (and
+        // closable) again
+        self.closed.store(false, Ordering::SeqCst);
+        self.shutdown_tx
+            .send_if_modified(|shutdown| std::mem::replace(shutdown, false));
         tracing::info!("Connected to WhatsApp");
         Ok(())
     }
 
-    pub async fn run(self: &Arc<Self>) -> Result<()> {
+    /// Run the event loop until shutdown or `cancel` fires
+    pub async fn run(self: &Arc<Self>, cancel: CancellationToken) -> Result<()> {
+        self.loop_running.send_replace(true);
+        let result = self.run_loop(cancel).await;
+        self.loop_running.send_replace(false);
+        result
+    }
+
+    /// Wait until the event loop has returned (immediately if it isn't running)
+    pub async fn wait_stopped(&self) {
+        let mut running = self.loop_running.subscribe();
+        let _ = running.wait_for(|running| !running).await;
+    }
+
+    async fn run_loop(self: &Arc<Self>, cancel: CancellationToken) -> Result<()> {
         tracing::info!("Starting event loop");
 
         let ffi = self.ffi.clone();
-        let bus = self.event_bus.clone();
-        let handlers = self.handlers.clone();
-        let mut shutdown = self.shutdown_rx.clone();
+        let contacts = self.contacts.clone();
+        let config = self.config.read().clone();
+        let mut shutdown = self.shutdown_tx.subscribe();
+        let dispatcher = Dispatcher::new(config.dispatch_workers);
 
         // Track which event types we've already saved (for debugging)
         let mut saved_event_types = std::collections::HashSet::new();
         let debug_dir = std::path::Path::new("debug_events");
 
+        let mut held = VecDeque::new();
+        let mut presence = config.coalesce_presence.map(PresenceCoalescer::new);
+        let mut history: Option<HistoryReader> = None;
+        let mut last_poll = Instant::now();
+
         loop {
             if *shutdown.borrow() {
                 tracing::info!("Shutting down");
                 break;
             }
+            if cancel.is_cancelled() {
+                tracing::info!("Event loop cancelled");
+                break;
+            }
+
+            if let Some(throttle) = config.poll_throttle {
+                tokio::select! {
+                    _ = tokio::time::sleep_until((last_poll + throttle).into()) => {}
+                    _ = shutdown.changed() => break,
+                    _ = cancel.cancelled() => break,
+                }
+                last_poll = Instant::now();
+            }
+
+            if !held.is_empty() && !self.is_paused() {
+                tracing::info!(count = held.len(), "Delivering events held while paused");
+                for (event, raw) in held.drain(..) {
+                    self.deliver(&dispatcher, config.event_order, event, raw)
+                        .await;
+                }
+            }
 
-            let data = { ffi.lock().poll_event()? };
-
-            if let Some(bytes) = data {
-                // Save raw event for debugging (once per event type)
-                if DEBUG_SAVE_EVENTS
-                    && let Ok(raw) = serde_json::from_slice::<serde_json::Value>(&bytes)
-                    && let Some(event_type) = raw.get("type").and_then(|t| t.as_str())
-                    && !saved_event_types.contains(event_type)
-                {
-                    saved_event_types.insert(event_type.to_string());
-                    let _ = std::fs::create_dir_all(debug_dir);
-                    let filename = debug_dir.join(format!("{}.json", event_type));
-                    if let Ok(pretty) = serde_json::to_string_pretty(&raw) {
-                        let _ = std::fs::write(&filename, pretty);
-                        tracing::info!("Saved raw event sample: {}", filename.display());
+            if let Some(presence) = &mut presence {
+                for (event, raw) in presence.take_due(Instant::now()) {
+                    if self.is_paused() {
+                        hold(&mut held, event, raw);
+                    } else {
+                        self.deliver(&dispatcher, config.event_order, event, raw)
+                            .await;
                     }
                 }
+            }
 
-                if let Ok(raw) = serde_json::from_slice::<RawEvent>(&bytes)
-                    && let Ok(event) = raw.into_event()
-                {
-                    tracing::debug!(?event, "Event received");
-                    handlers.dispatch(&event);
-                    bus.emit(event);
+            // A spilled history sync is drained before polling again, so
+            // its chunks stay in order with the events after it
+            let injected = self.injected_rx.lock().try_recv().ok().or_else(|| {
+                let event = history.as_mut()?.next_event();
+                if event.is_none() {
+                    history = None;
                 }
-            } else {
-                tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_millis(10)) => {}
-                    _ = shutdown.changed() => break,
+                event
+            });
+            let (event, raw_json) = match injected {
+                Some(event) => (event, None),
+                None => {
+                    let polled = ffi.run_async(|client| client.poll_event()).await;
+                    let data = match polled {
+                        Ok(data) => data,
+                        Err(e) => {
+                            self.poll_failed(e, &config, &cancel);
+                            tokio::select! {
+                                _ = tokio::time::sleep(POLL_ERROR_PAUSE) => {}
+                                _ = shutdown.changed() => break,
+                                _ = cancel.cancelled() => break,
+                            }
+                            continue;
+                        }
+                    };
+
+                    let Some(bytes) = data else {
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+                            _ = shutdown.changed() => break,
+                            _ = cancel.cancelled() => break,
+                        }
+                        continue;
+                    };
+
+                    // Reject malformed FFI output up front (e.g. a multi-byte
+                    // character truncated by the event buffer)
+                    if let Err(e) = std::str::from_utf8(&bytes) {
+                        tracing::warn!(
+                            offset = e.valid_up_to(),
+                            len = bytes.len(),
+                            "Skipping event with invalid UTF-8"
+                        );
+                        continue;
+                    }
+
+                    let json = (DEBUG_SAVE_EVENTS || config.retain_raw_json)
+                        .then(|| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+                        .flatten();
+
+                    // Save raw event for debugging (once per event type)
+                    if DEBUG_SAVE_EVENTS
+                        && let Some(raw) = &json
+                        && let Some(event_type) = raw.get("type").and_then(|t| t.as_str())
+                        && !saved_event_types.contains(event_type)
+                    {
+                        saved_event_types.insert(event_type.to_string());
+                        let _ = std::fs::create_dir_all(debug_dir);
+                        let filename = debug_dir.join(format!("{}.json", event_type));
+                        if let Ok(pretty) = serde_json::to_string_pretty(raw) {
+                            let _ = std::fs::write(&filename, pretty);
+                            tracing::info!("Saved raw event sample: {}", filename.display());
+                        }
+                    }
+
+                    let Ok(raw) = serde_json::from_slice::<RawEvent>(&bytes) else {
+                        continue;
+                    };
+                    if raw.event_type == HistoryReader::EVENT_TYPE {
+                        history = Some(HistoryReader::open(raw));
+                        continue;
+                    }
+                    let Ok(event) = raw.into_event() else {
+                        continue;
+                    };
+
+                    match &event {
+                        Event::Connected => self.connected.store(true, Ordering::SeqCst),
+                        // Reconnecting a logged-out session can't succeed;
+                        // only server hiccups are worth retrying
+                        Event::LoggedOut(logout) => {
+                            self.connected.store(false, Ordering::SeqCst);
+                            if logout.reason_kind().is_transient() {
+                                self.spawn_reconnect(config.reconnect, cancel.clone());
+                            }
+                        }
+                        // Not a drop to recover from: reconnecting would fight
+                        // the client that replaced us
+                        Event::StreamReplaced => {
+                            tracing::warn!("Session opened elsewhere, not reconnecting");
+                            self.connected.store(false, Ordering::SeqCst);
+                        }
+                        // Already reported if a failed call noticed the drop first
+                        Event::Disconnected if !self.connected.swap(false, Ordering::SeqCst) => {
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    (event, json.filter(|_| config.retain_raw_json))
                 }
+            };
+
+            tracing::debug!(?event, "Event received");
+            self.events_received.fetch_add(1, Ordering::Relaxed);
+            // Presence subscriptions are per-connection, so re-issue
+            // them on every (re)connect
+            if matches!(event, Event::Connected) && !config.presence_subscriptions.is_empty() {
+                let ffi = ffi.clone();
+                let jids = config.presence_subscriptions.clone();
+                tokio::task::spawn_blocking(move || {
+                    for jid in &jids {
+                        if let Err(e) = ffi.lock().subscribe_presence(jid.as_str()) {
+                            tracing::warn!(error = %e, %jid, "Presence subscription failed");
+                        }
+                    }
+                });
+            }
+            if matches!(event, Event::Connected) && self.ever_connected.swap(true, Ordering::SeqCst)
+            {
+                dispatcher.dispatch(self.handlers.reconnected_jobs()).await;
+            }
+            if matches!(event, Event::Disconnected) {
+                self.spawn_reconnect(config.reconnect, cancel.clone());
             }
+            if let Event::CallOffer(call) = &event
+                && config.should_reject_call(call)
+            {
+                let ffi = ffi.clone();
+                let call = call.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = ffi.lock().reject_call(&call.from, &call.call_id) {
+                        tracing::warn!(error = %e, call_id = %call.call_id, "Auto call reject failed");
+                    }
+                });
+            }
+            if let Event::Message(msg) = &event {
+                if !self.seen.insert(&msg.info) {
+                    tracing::debug!(id = %msg.info.id, "Skipping redelivered message");
+                    continue;
+                }
+                contacts.observe(msg);
+                self.contact_book.observe(msg);
+
+                if config.should_auto_read(msg) {
+                    let ffi = ffi.clone();
+                    let info = msg.info.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let ids = [info.id.as_str()];
+                        if let Err(e) = ffi.lock().mark_read(&info.chat, &info.sender, &ids) {
+                            tracing::warn!(error = %e, id = %info.id, "Auto mark-read failed");
+                        }
+                    });
+                }
+            }
+            if !config.should_deliver(&event) {
+                continue;
+            }
+            let Some(event) = config.before_dispatch(event).await else {
+                continue;
+            };
+            let (event, raw_json) = match &mut presence {
+                Some(presence) => match presence.offer(event, raw_json) {
+                    Some(passed) => passed,
+                    None => continue,
+                },
+                None => (event, raw_json),
+            };
+            if self.is_paused() {
+                hold(&mut held, event, raw_json);
+                continue;
+            }
+            self.deliver(&dispatcher, config.event_order, event, raw_json)
+                .await;
         }
 
         Ok(())
     }
 
+    /// Keep the loop alive through a failed poll
+    ///
+    /// Go's own auto-reconnect is off, so a lost connection is only ever
+    /// recovered from here: it is reported once and reconnecting starts.
+    fn poll_failed(&self, error: Error, config: &ClientConfig, cancel: &CancellationToken) {
+        match error {
+            // Reconnecting reopens the session if the handle is gone
+            Error::Disconnected | Error::InvalidHandle => {
+                if self.mark_disconnected() {
+                    tracing::warn!(error = %error, "Event poll found the connection dropped");
+                }
+                self.spawn_reconnect(config.reconnect, cancel.clone());
+            }
+            e => tracing::warn!(error = %e, "Event poll failed"),
+        }
+    }
+
+    /// Hand an event to handlers and streams in the configured order
+    async fn deliver(
+        &self,
+        dispatcher: &Dispatcher,
+        order: EventOrder,
+        event: Event,
+        raw_json: Option<serde_json::Value>,
+    ) {
+        if let Some(raw) = raw_json {
+            self.event_bus.emit_raw(&event, raw);
+        }
+        match order {
+            EventOrder::HandlersFirst => {
+                dispatcher.dispatch(self.handlers.jobs(&event)).await;
+                self.event_bus.emit(event);
+            }
+            EventOrder::StreamsFirst => {
+                let jobs = self.handlers.jobs(&event);
+                self.event_bus.emit(event);
+                dispatcher.dispatch(jobs).await;
+            }
+        }
+    }
+
+    /// Retry connecting with backoff until it succeeds or the client shuts down
+    fn spawn_reconnect(&self, backoff: Backoff, cancel: CancellationToken) {
+        if self.reconnecting.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let ffi = self.ffi.clone();
+        let reconnecting = self.reconnecting.clone();
+        let reconnects = self.reconnects.clone();
+        let mut shutdown = self.shutdown_tx.subscribe();
+
+        tokio::spawn(async move {
+            for attempt in 0.. {
+                let delay = backoff.delay(attempt);
+                tracing::info!(attempt, ?delay, "Reconnecting");
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown.changed() => break,
+                    _ = cancel.cancelled() => break,
+                }
+                if *shutdown.borrow() {
+                    break;
+                }
+
+                let ffi = ffi.clone();
+                let reconnect = move || {
+                    // Release the handle before reopening swaps it out
+                    let result = ffi.lock().connect();
+                    match result {
+                        Err(Error::InvalidHandle) => {
+                            tracing::warn!("Bridge handle invalid, reopening session");
+                            ffi.reopen()
+                        }
+                        result => result,
+                    }
+                };
+                match tokio::task::spawn_blocking(reconnect).await {
+                    Ok(Ok(())) => {
+                        tracing::info!(attempt, "Reconnected");
+                        reconnects.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    Ok(Err(e)) => tracing::warn!(error = %e, attempt, "Reconnect failed"),
+                    Err(e) => tracing::warn!(error = %e, attempt, "Reconnect task failed"),
+                }
+            }
+            reconnecting.store(false, Ordering::SeqCst);
+        });
+    }
+
     pub fn events(&self) -> EventStream {
         self.event_bus.subscribe()
     }
 
-    pub fn send_message(&self, jid: &str, text: &str) -> Result<()> {
-        self.ffi.lock().send_message(jid, text)
+    pub fn events_with_raw(&self) -> EnvelopeStream {
+        self.event_bus.subscribe_raw()
+    }
+
+    /// Run an FFI call, noticing when it reveals a dropped connection
+    ///
+    /// The first call to fail with [`Error::Disconnected`] marks the client
+    /// disconnected and hands [`Event::Disconnected`] to the run loop, which
+    /// delivers it and starts reconnecting, as if the bridge had reported it.
+    fn call<T>(&self, f: impl FnOnce(&FfiClient) -> Result<T>) -> Result<T> {
+        let result = f(&self.ffi.lock());
+        self.observe(result)
+    }
+
+    /// [`call`](Self::call) for async callers
+    ///
+    /// Waits for the handle and runs `f` on the blocking pool, in the same
+    /// FIFO order as blocking callers, without blocking the runtime.
+    pub async fn call_async<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&FfiClient) -> Result<T> + Send + 'static,
+    {
+        let result = self.ffi.run_async(|client| f(client)).await;
+        self.observe(result)
     }
 
-    pub fn send_image(
+    /// Notice a dropped connection in an FFI call's result
+    fn observe<T>(&self, result: Result<T>) -> Result<T> {
+        if matches!(result, Err(Error::Disconnected)) && self.mark_disconnected() {
+            tracing::warn!("FFI call found the connection dropped");
+        }
+        result
+    }
+
+    /// Mark the client disconnected and have the run loop deliver
+    /// [`Event::Disconnected`], unless it already was
+    fn mark_disconnected(&self) -> bool {
+        let was_connected = self.connected.swap(false, Ordering::SeqCst);
+        if was_connected {
+            let _ = self.injected_tx.send(Event::Disconnected);
+        }
+        was_connected
+    }
+
+    /// Hand a prepared message to the bridge
+    pub fn send(&self, jid: &str, outgoing: &Outgoing) -> Result<Vec<u8>> {
+        self.call(|ffi| outgoing.deliver(ffi, jid))
+    }
+
+    pub fn send_album(
         &self,
         jid: &str,
-        data: &[u8],
-        mime_type: &str,
+        items_json: &str,
         caption: Option<&str>,
-    ) -> Result<()> {
-        self.ffi.lock().send_image(jid, data, mime_type, caption)
+    ) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.send_album(jid, items_json, caption))
+    }
+
+    pub fn reject_call(&self, from: &str, call_id: &str) -> Result<()> {
+        self.call(|ffi| ffi.reject_call(from, call_id))
+    }
+
+    pub fn mark_read(&self, chat: &str, sender: &str, ids: &[&str]) -> Result<()> {
+        self.call(|ffi| ffi.mark_read(chat, sender, ids))
+    }
+
+    pub fn mark_played(&self, chat: &str, sender: &str, ids: &[&str]) -> Result<()> {
+        self.call(|ffi| ffi.mark_played(chat, sender, ids))
+    }
+
+    pub fn send_chat_presence(&self, jid: &str, presence: ChatPresence) -> Result<()> {
+        let (state, media) = presence.wire();
+        self.call(|ffi| ffi.send_chat_presence(jid, state, media))
+    }
+
+    pub fn subscribe_presence(&self, jid: &str) -> Result<()> {
+        self.call(|ffi| ffi.subscribe_presence(jid))
+    }
+
+    pub fn query_messages(&self, chat: &str, limit: i32, before: Option<&str>) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.query_messages(chat, limit, before))
+    }
+
+    pub fn star_message(&self, chat: &str, id: &str, starred: bool) -> Result<()> {
+        self.call(|ffi| ffi.star_message(chat, id, starred))
+    }
+
+    pub fn starred(&self, chat: &str) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.starred(chat))
+    }
+
+    pub fn resync_app_state(&self, collection: AppStateCollection) -> Result<()> {
+        self.call(|ffi| ffi.resync_app_state(collection.as_str()))
+    }
+
+    pub fn mark_chat_unread(&self, jid: &str) -> Result<()> {
+        self.call(|ffi| ffi.mark_chat_unread(jid))
+    }
+
+    pub fn mute_chat(&self, jid: &str, mute: bool, duration_secs: i64) -> Result<()> {
+        self.call(|ffi| ffi.mute_chat(jid, mute, duration_secs))
+    }
+
+    pub fn clear_chat(&self, jid: &str) -> Result<()> {
+        self.call(|ffi| ffi.clear_chat(jid))
+    }
+
+    pub fn export_session(&self) -> Result<SessionBlob> {
+        self.call(|ffi| ffi.export_session())
+    }
+
+    pub fn import_session(&self, blob: &SessionBlob) -> Result<()> {
+        self.call(|ffi| ffi.import_session(blob))
+    }
+
+    pub fn unread_counts(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.unread_counts())
+    }
+
+    /// Store counts, and the database path to measure on disk
+    pub fn store_stats(&self) -> Result<(Vec<u8>, PathBuf)> {
+        self.call(|ffi| Ok((ffi.store_stats()?, ffi.db_path().to_path_buf())))
+    }
+
+    pub fn resolve_lid(&self, lid: &str) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.resolve_lid(lid))
+    }
+
+    pub fn profile_picture(&self, jid: &str, full: bool) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.profile_picture(jid, full))
+    }
+
+    pub fn own_jid(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.own_jid())
+    }
+
+    pub fn server_time(&self) -> Result<Option<i64>> {
+        self.call(|ffi| ffi.server_time())
+    }
+
+    pub fn push_name(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.push_name())
+    }
+
+    pub fn set_push_name(&self, name: &str) -> Result<()> {
+        self.call(|ffi| ffi.set_push_name(name))
+    }
+
+    pub fn get_contact(&self, jid: &str) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.get_contact(jid))
+    }
+
+    pub fn is_on_whatsapp(&self, phones: &[&str]) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.is_on_whatsapp(phones))
+    }
+
+    pub fn linked_devices(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.linked_devices())
+    }
+
+    pub fn joined_groups(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.joined_groups())
+    }
+
+    pub fn group_info_from_link(&self, code: &str) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.group_info_from_link(code))
+    }
+
+    pub fn privacy_settings(&self) -> Result<Vec<u8>> {
+        self.call(|ffi| ffi.privacy_settings())
+    }
+
+    pub fn set_privacy_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.call(|ffi| ffi.set_privacy_setting(key, value))
     }
 
+    pub fn remove_device(&self, jid: &str) -> Result<()> {
+        self.call(|ffi| ffi.remove_device(jid))
+    }
+
+    /// Stop the event loop and disconnect the FFI client (idempotent)
     pub fn disconnect(&self) {
-        let _ = self.shutdown_tx.send(true);
-        if let Some(client) = self.ffi.try_lock() {
-            let _ = client.disconnect();
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        self.shutdown_tx.send_replace(true);
+        if let Err(e) = self.ffi.lock().disconnect() {
+            tracing::debug!(error = %e, "FFI disconnect failed");
         }
         self.connected.store(false, Ordering::SeqCst);
+        self.seen.flush();
+    }
+
+    /// Whether `jid` was found on WhatsApp within [`VERIFIED_RECIPIENT_TTL`]
+    pub fn recently_verified(&self, jid: &Jid) -> bool {
+        self.verified
+            .lock()
+            .get(jid)
+            .is_some_and(|at| at.elapsed() < VERIFIED_RECIPIENT_TTL)
+    }
+
+    pub fn mark_verified(&self, jid: Jid) {
+        let mut verified = self.verified.lock();
+        verified.retain(|_, at| at.elapsed() < VERIFIED_RECIPIENT_TTL);
+        verified.insert(jid, Instant::now());
+    }
+
+    pub fn acquire_handle(&self) {
+        self.handles.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Count a handle dropped, stopping the run loop if it was the last
+    ///
+    /// The loop's own task holds a handle too, so without this a spawned
+    /// loop would keep the client alive after the application let go of it.
+    pub fn release_handle(&self) {
+        if self.handles.fetch_sub(1, Ordering::SeqCst) == 1 && *self.loop_running.borrow() {
+            tracing::info!("Last client handle dropped, stopping event loop");
+            self.disconnect();
+        }
+    }
+
+    /// Take a handle out of the count while it runs the loop, or put it back
+    pub fn set_handle_running(&self, running: bool) {
+        if running {
+            self.handles.fetch_sub(1, Ordering::SeqCst);
+        } else {
+            self.handles.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
     }
 
     pub fn is_connected(&self) -> bool {
         self.connected.load(Ordering::SeqCst)
     }
+
+    pub fn ffi_latencies(&self) -> HashMap<String, LatencyStats> {
+        self.ffi.latencies()
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            ffi_queue_depth: self.ffi.queue_depth(),
+            events_received: self.events_received.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+        }
+    }
 }
 
 impl Drop for InnerClient {
+    /// Runs when the last `WhatsApp` clone is dropped
     fn drop(&mut self) {
-        let _ = self.shutdown_tx.send(true);
+        self.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use whatsmeow_sys::error_codes::WM_ERR_DISCONNECTED;
+
+    use super::*;
+    use crate::WhatsApp;
+    use crate::mock_ffi::{self, Mock, Reply};
+
+    #[tokio::test]
+    async fn poll_error_reports_the_drop_once_and_reconnects() {
+        let client = WhatsApp::connect(mock_ffi::db_path("poll-disconnected"))
+            .reconnect_base(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.reply("wm_poll_event", Reply::err(WM_ERR_DISCONNECTED));
+        mock.reply("wm_poll_event", Reply::err(WM_ERR_DISCONNECTED));
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Disconnected
+        ));
+        assert!(!client.is_connected());
+
+        // The bridge confirms the reconnect with a fresh Connected
+        while mock.calls("wm_client_connect").len() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        mock.push_event("connected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        assert!(client.is_connected());
+        assert!(!run.is_finished());
+
+        client.disconnect();
+        run.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn handlers_survive_a_reconnect_that_reopens_the_handle() {
+        let path = mock_ffi::db_path("reopen-handlers");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let client = WhatsApp::connect(&path)
+            .reconnect_base(Duration::from_millis(10))
+            .on_message(move |msg| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(msg.info.id);
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        // The bridge forgets the handle; the loop must reopen, not exit
+        Mock::of(&client).invalidate();
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Disconnected
+        ));
+        let reopened = loop {
+            if let [_, reopened] = mock_ffi::clients_at(&path)[..]
+                && !reopened.calls("wm_client_connect").is_empty()
+            {
+                break reopened;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+
+        reopened.push_event(
+            "message",
+            mock_ffi::text_message("R1", "1@s.whatsapp.net", "1@s.whatsapp.net", "back"),
+        );
+        let id = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(id.as_deref(), Some("R1"));
+        assert!(!run.is_finished());
+
+        client.disconnect();
+        run.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn reconnect_after_disconnect_makes_the_client_usable_again() {
+        let (client, mock) = mock_ffi::client("disconnect-reconnect").await;
+        client.disconnect();
+        assert!(!client.is_connected());
+
+        client.reconnect().await.unwrap();
+        assert!(client.is_connected());
+
+        // The loop runs again, and disconnecting again reaches the bridge
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+        mock.push_event("connected", Value::Null);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!run.is_finished());
+        client.disconnect();
+        run.await.unwrap().unwrap();
+        assert_eq!(mock.calls("wm_client_disconnect").len(), 2);
+    }
+
+    fn presence(from: &str) -> Value {
+        serde_json::json!({ "From": from, "Unavailable": false, "LastSeen": "" })
+    }
+
+    #[tokio::test]
+    async fn before_dispatch_rewrites_what_handlers_see() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let client = WhatsApp::connect(mock_ffi::db_path("before-dispatch-rewrite"))
+            .before_dispatch(|event| async move {
+                match event {
+                    Event::Message(mut msg) => {
+                        msg.info.sender = "15550001111@s.whatsapp.net".into();
+                        Some(Event::Message(msg))
+                    }
+                    other => Some(other),
+                }
+            })
+            .on_message(move |msg| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(msg.info.sender);
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "123@lid", "hi"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let sender = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(sender.as_deref(), Some("15550001111@s.whatsapp.net"));
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn before_dispatch_runs_after_the_filter_and_before_coalescing() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let client = WhatsApp::connect(mock_ffi::db_path("before-dispatch-order"))
+            .event_filter(|event| !matches!(event, Event::Receipt(_)))
+            .coalesce_presence(Duration::from_millis(50))
+            .before_dispatch(move |event| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async move { Some(event) }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        mock.push_event(
+            "receipt",
+            serde_json::json!({
+                "Chat": "1@s.whatsapp.net",
+                "Sender": "1@s.whatsapp.net",
+                "IsFromMe": false,
+                "IsGroup": false,
+                "MessageIDs": ["M1"],
+                "Timestamp": "2024-01-01T00:00:00Z",
+                "Type": "read",
+            }),
+        );
+        for _ in 0..3 {
+            mock.push_event("presence", presence("1@s.whatsapp.net"));
+        }
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Presence(_)
+        ));
+        // The receipt was filtered out first; all three presence updates
+        // passed through before being coalesced into one
+        assert_eq!(seen.load(Ordering::SeqCst), 3);
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8_is_skipped_with_a_warning() {
+        let warnings = mock_ffi::Warnings::default();
+        let _subscriber = tracing::subscriber::set_default(warnings.clone());
+        let (client, mock) = mock_ffi::client("invalid-utf8").await;
+        let mut events = client.events();
+        mock.push_raw(b"{\"type\":\"connected\",\"data\":\"\xC3\"}".to_vec());
+        mock.push_event("connected", Value::Null);
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        assert!(!run.is_finished());
+        assert!(
+            warnings
+                .messages()
+                .iter()
+                .any(|m| m.contains("invalid UTF-8"))
+        );
+        client.disconnect();
+    }
+
+    async fn run_with_message(client: &WhatsApp, mock: &Mock) {
+        let mut events = client.events();
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "2@s.whatsapp.net", "hi"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Message(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn auto_read_marks_incoming_messages_read() {
+        let client = WhatsApp::connect(mock_ffi::db_path("auto-read-on"))
+            .auto_read(true)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+
+        run_with_message(&client, &mock).await;
+
+        assert_eq!(
+            mock.wait_for_calls("wm_mark_read", 1).await,
+            [vec![
+                "1@s.whatsapp.net".to_string(),
+                "2@s.whatsapp.net".to_string(),
+                r#"["M1"]"#.to_string(),
+            ]]
+        );
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn messages_are_not_marked_read_without_auto_read() {
+        let (client, mock) = mock_ffi::client("auto-read-off").await;
+
+        run_with_message(&client, &mock).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(mock.calls("wm_mark_read").is_empty());
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn presence_subscriptions_are_reissued_after_a_reconnect() {
+        let client = WhatsApp::connect(mock_ffi::db_path("presence-resubscribe"))
+            .subscribe_presence(["1@s.whatsapp.net", "2@s.whatsapp.net"])
+            .reconnect_base(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        mock.push_event("connected", Value::Null);
+        mock.wait_for_calls("wm_subscribe_presence", 2).await;
+        mock.push_event("disconnected", Value::Null);
+        mock.wait_for_calls("wm_client_connect", 2).await;
+        mock.push_event("connected", Value::Null);
+
+        let calls = mock.wait_for_calls("wm_subscribe_presence", 4).await;
+        let jids: Vec<&str> = calls.iter().map(|args| args[0].as_str()).collect();
+        assert_eq!(
+            jids,
+            [
+                "1@s.whatsapp.net",
+                "2@s.whatsapp.net",
+                "1@s.whatsapp.net",
+                "2@s.whatsapp.net",
+            ]
+        );
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn filtered_presence_never_reaches_a_stream() {
+        let client = WhatsApp::connect(mock_ffi::db_path("event-filter-presence"))
+            .event_filter(|event| !matches!(event, Event::Presence(_)))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        mock.push_event("presence", presence("1@s.whatsapp.net"));
+        mock.push_event("presence", presence("2@s.whatsapp.net"));
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "1@s.whatsapp.net", "hi"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Message(_)
+        ));
+        client.disconnect();
+    }
+
+    #[test]
+    fn a_full_pause_buffer_drops_the_oldest_events() {
+        let mut held = VecDeque::new();
+        for i in 0..PAUSE_BUFFER_CAPACITY + 5 {
+            let event = Event::Unknown {
+                event_type: i.to_string(),
+                data: None,
+            };
+            hold(&mut held, event, None);
+        }
+
+        assert_eq!(held.len(), PAUSE_BUFFER_CAPACITY);
+        assert!(matches!(&held[0].0, Event::Unknown { event_type, .. } if event_type == "5"));
+    }
+
+    #[tokio::test]
+    async fn events_during_a_pause_are_delivered_in_order_on_resume() {
+        use futures::StreamExt;
+
+        let (client, mock) = mock_ffi::client("pause-resume").await;
+        let mut events = client.events();
+        client.pause();
+        for id in ["M1", "M2", "M3"] {
+            mock.push_event(
+                "message",
+                mock_ffi::text_message(id, "1@s.whatsapp.net", "1@s.whatsapp.net", "hi"),
+            );
+        }
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), events.next())
+                .await
+                .is_err()
+        );
+        client.resume();
+
+        for id in ["M1", "M2", "M3"] {
+            match mock_ffi::next_event(&mut events).await {
+                Event::Message(msg) => assert_eq!(msg.info.id, id),
+                other => panic!("expected a message, got {:?}", other),
+            }
+        }
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn a_poll_throttle_spaces_out_iterations() {
+        let client = WhatsApp::connect(mock_ffi::db_path("poll-throttle"))
+            .poll_throttle(Duration::from_millis(50))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        for id in ["M1", "M2", "M3", "M4"] {
+            mock.push_event(
+                "message",
+                mock_ffi::text_message(id, "1@s.whatsapp.net", "1@s.whatsapp.net", "hi"),
+            );
+        }
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let mut arrivals = Vec::new();
+        for _ in 0..4 {
+            mock_ffi::next_event(&mut events).await;
+            arrivals.push(Instant::now());
+        }
+
+        // Allow for timer granularity when the receiver wakes late
+        for pair in arrivals.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(45));
+        }
+        assert!(arrivals[3] - arrivals[0] >= Duration::from_millis(150));
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn a_send_finding_the_connection_gone_reports_the_drop() {
+        let client = WhatsApp::connect(mock_ffi::db_path("send-disconnected"))
+            .reconnect_base(Duration::from_secs(60))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.reply("wm_send_message", Reply::err(WM_ERR_DISCONNECTED));
+        let mut events = client.events();
+        assert!(client.is_connected());
+
+        let sent = client.send("1@s.whatsapp.net", "hi");
+
+        assert!(matches!(sent, Err(Error::Disconnected)));
+        assert!(!client.is_connected());
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Disconnected
+        ));
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn a_replaced_stream_is_reported_without_reconnecting() {
+        let client = WhatsApp::connect(mock_ffi::db_path("stream-replaced"))
+            .reconnect_base(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        mock.push_event("connected", Value::Null);
+        mock.push_event("stream_replaced", Value::Null);
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::StreamReplaced
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(!client.is_connected());
+        assert_eq!(mock.calls("wm_client_connect").len(), 1);
+        client.disconnect();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn streams_see_arrival_order_under_load_in_either_order() {
+        const COUNT: usize = 200;
+
+        for order in [EventOrder::HandlersFirst, EventOrder::StreamsFirst] {
+            let handled = Arc::new(AtomicUsize::new(0));
+            let counter = handled.clone();
+            let client = WhatsApp::connect(mock_ffi::db_path("event-order-load"))
+                .event_order(order)
+                .on_message(move |msg| {
+                    let counter = counter.clone();
+                    async move {
+                        // Uneven handler work so tasks finish out of order
+                        let delay = msg.info.id.as_str().len() as u64 % 3;
+                        tokio::time::sleep(Duration::from_millis(delay)).await;
+                        counter.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+                .build()
+                .await
+                .unwrap();
+            let mock = Mock::of(&client);
+            let mut events = client.events();
+            for i in 0..COUNT {
+                mock.push_event(
+                    "message",
+                    mock_ffi::text_message(
+                        &format!("M{i}"),
+                        "1@s.whatsapp.net",
+                        "1@s.whatsapp.net",
+                        "hi",
+                    ),
+                );
+            }
+            let pump = client.clone();
+            tokio::spawn(async move { pump.run().await });
+
+            for i in 0..COUNT {
+                match mock_ffi::next_event(&mut events).await {
+                    Event::Message(msg) => assert_eq!(msg.info.id, format!("M{i}"), "{:?}", order),
+                    other => panic!("expected a message, got {:?}", other),
+                }
+            }
+            tokio::time::timeout(Duration::from_secs(1), async {
+                while handled.load(Ordering::SeqCst) < COUNT {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .unwrap();
+            client.disconnect();
+        }
+    }
+
+    #[tokio::test]
+    async fn query_presence_returns_the_contacts_next_update() {
+        let (client, mock) = mock_ffi::client("query-presence").await;
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let asking = client.clone();
+        let query =
+            tokio::spawn(async move { asking.query_presence("15550001111@s.whatsapp.net").await });
+        mock.wait_for_calls("wm_subscribe_presence", 1).await;
+        mock.push_event("presence", presence("15550002222@s.whatsapp.net"));
+        mock.push_event("presence", presence("15550001111:4@s.whatsapp.net"));
+
+        let answer = tokio::time::timeout(Duration::from_secs(1), query)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(answer.from, "15550001111:4@s.whatsapp.net");
+        assert!(answer.is_online());
+        assert_eq!(
+            mock.calls("wm_subscribe_presence"),
+            [vec!["15550001111@s.whatsapp.net"]]
+        );
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn on_reconnected_skips_the_first_connect() {
+        let reconnected = Arc::new(AtomicU64::new(0));
+        let counter = reconnected.clone();
+        let client = WhatsApp::connect(mock_ffi::db_path("on-reconnected"))
+            .reconnect_base(Duration::from_millis(10))
+            .on_reconnected(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async {}
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        mock.push_event("connected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(reconnected.load(Ordering::SeqCst), 0);
+
+        mock.push_event("disconnected", Value::Null);
+        mock.wait_for_calls("wm_client_connect", 2).await;
+        mock.push_event("connected", Value::Null);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while reconnected.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("on_reconnected not called");
+        assert_eq!(reconnected.load(Ordering::SeqCst), 1);
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn retained_json_matches_the_parsed_event() {
+        use futures::StreamExt;
+
+        let client = WhatsApp::connect(mock_ffi::db_path("retain-raw-json"))
+            .retain_raw_json(true)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut envelopes = client.events_with_raw();
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("M1", "1@s.whatsapp.net", "2@s.whatsapp.net", "hi"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let envelope = tokio::time::timeout(Duration::from_secs(1), envelopes.next())
+            .await
+            .expect("no envelope")
+            .unwrap();
+
+        let Event::Message(msg) = &envelope.event else {
+            panic!("expected a message, got {:?}", envelope.event);
+        };
+        assert_eq!(envelope.raw["type"], "message");
+        assert_eq!(envelope.raw["data"]["Info"]["ID"], msg.info.id.as_str());
+        assert_eq!(envelope.raw["data"]["Info"]["Chat"], msg.info.chat.as_str());
+        assert_eq!(envelope.raw["data"]["Message"]["conversation"], msg.text());
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn app_state_after_a_key_share_is_delivered() {
+        let (client, mock) = mock_ffi::client("app-state-key-share").await;
+        let mut events = client.events();
+        mock.push_event(
+            "message",
+            mock_ffi::message(
+                "K1",
+                "1@s.whatsapp.net",
+                "1@s.whatsapp.net",
+                serde_json::json!({ "protocolMessage": { "type": 6 } }),
+            ),
+        );
+        mock.push_event(
+            "delete_chat",
+            serde_json::json!({ "JID": "123-456@g.us", "Timestamp": "2024-01-01T00:00:00Z" }),
+        );
+        mock.push_event(
+            "app_state_sync_complete",
+            serde_json::json!({ "Name": "regular" }),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::AppStateKeyReceived
+        ));
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::ChatDeleted(deleted) if deleted.chat.as_str() == "123-456@g.us"
+        ));
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::AppStateSynced(synced) if synced.collection == AppStateCollection::Regular
+        ));
+        client.disconnect();
+    }
+
+    #[tokio::test]
+    async fn a_stream_ends_only_when_the_client_is_dropped() {
+        use futures::StreamExt;
+
+        let client = WhatsApp::connect(mock_ffi::db_path("stream-lifetime"))
+            .reconnect_base(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+
+        // A dropped connection and the reconnect keep the stream going
+        mock.push_event("disconnected", Value::Null);
+        mock.wait_for_calls("wm_client_connect", 2).await;
+        mock.push_event("connected", Value::Null);
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Disconnected
+        ));
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Connected
+        ));
+
+        // An explicit disconnect leaves it open but quiet
+        client.disconnect();
+        run.await.unwrap().unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), events.next())
+                .await
+                .is_err()
+        );
+        assert!(!events.is_closed());
+
+        drop(client);
+        let end = tokio::time::timeout(Duration::from_secs(1), events.next())
+            .await
+            .expect("stream did not end");
+        assert!(end.is_none());
+        assert!(events.is_closed());
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn own_messages_skip_handlers_when_not_dispatched() {
+        let seen = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let handled = seen.clone();
+        let client = WhatsApp::connect(mock_ffi::db_path("skip-own-messages"))
+            .dispatch_own_messages(false)
+            .on_message(move |msg| {
+                handled.lock().push(msg.info.id.to_string());
+                async {}
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        let mut own = mock_ffi::text_message("OWN", "1@s.whatsapp.net", "9@s.whatsapp.net", "hi");
+        own["Info"]["IsFromMe"] = true.into();
+        mock.push_event("message", own);
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("THEIRS", "1@s.whatsapp.net", "1@s.whatsapp.net", "hey"),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while seen.lock().is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("handler not called");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(*seen.lock(), ["THEIRS"]);
+        client.disconnect();
+    }
+
+    #[test]
+    fn a_handler_can_send_synchronously_on_a_current_thread_runtime() {
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        // The runtime gets its own thread so a deadlock fails the test
+        // instead of hanging it
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let slot = Arc::new(std::sync::OnceLock::<WhatsApp>::new());
+                let replier = slot.clone();
+                let client = WhatsApp::connect(mock_ffi::db_path("sync-send-in-handler"))
+                    .on_message(move |msg| {
+                        let client = replier.get().unwrap().clone();
+                        async move {
+                            client.send(msg.info.chat.as_str(), "pong").unwrap();
+                        }
+                    })
+                    .build()
+                    .await
+                    .unwrap();
+                let _ = slot.set(client.clone());
+                let mock = Mock::of(&client);
+                // Async sends hold the handle on the blocking pool while the
+                // run loop and the handlers queue up behind them
+                mock.delay("wm_send_message", Duration::from_millis(5));
+                let sender = client.clone();
+                tokio::spawn(async move {
+                    for _ in 0..20 {
+                        let options = crate::SendOptions::default();
+                        let _ = sender.send_async("2@s.whatsapp.net", "hi", options).await;
+                    }
+                });
+                let pump = client.clone();
+                tokio::spawn(async move { pump.run().await });
+                for i in 0..5 {
+                    mock.push_event(
+                        "message",
+                        mock_ffi::text_message(
+                            &format!("M{i}"),
+                            "1@s.whatsapp.net",
+                            "1@s.whatsapp.net",
+                            "ping",
+                        ),
+                    );
+                    tokio::time::sleep(Duration::from_millis(15)).await;
+                }
+
+                mock.wait_for_calls("wm_send_message", 25).await;
+                client.disconnect();
+            });
+            done_tx.send(()).unwrap();
+        });
+
+        done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("sync send from a handler deadlocked the runtime");
     }
 }
@@ -0,0 +1,344 @@
+//! Interactive (button and list) messages
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::events::{MessageId, MessageInfo};
+
+/// Most reply buttons WhatsApp shows on one message
+pub const MAX_BUTTONS: usize = 3;
+
+/// Most rows WhatsApp shows across all sections of a list
+pub const MAX_LIST_ROWS: usize = 10;
+
+/// Quick-reply button
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Button {
+    /// Returned in the [`InteractiveResponseEvent`] when tapped
+    pub id: String,
+    /// Label shown on the button
+    pub title: String,
+}
+
+impl Button {
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+        }
+    }
+}
+
+/// Titled group of rows in a list menu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListSection {
+    pub title: String,
+    pub rows: Vec<ListRow>,
+}
+
+impl ListSection {
+    pub fn new(title: impl Into<String>, rows: Vec<ListRow>) -> Self {
+        Self {
+            title: title.into(),
+            rows,
+        }
+    }
+}
+
+/// Selectable entry of a list menu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListRow {
+    /// Returned in the [`InteractiveResponseEvent`] when selected
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ListRow {
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            description: None,
+        }
+    }
+
+    /// Add a secondary line under the title
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Buttons message as sent to the bridge
+#[derive(Serialize)]
+pub(crate) struct ButtonsPayload<'a> {
+    pub body: &'a str,
+    pub buttons: &'a [Button],
+}
+
+/// List message as sent to the bridge
+#[derive(Serialize)]
+pub(crate) struct ListPayload<'a> {
+    pub body: &'a str,
+    pub button_text: &'a str,
+    pub sections: &'a [ListSection],
+}
+
+/// Reject button messages WhatsApp would refuse or render incorrectly
+pub(crate) fn validate_buttons(buttons: &[Button]) -> Result<()> {
+    if buttons.is_empty() || buttons.len() > MAX_BUTTONS {
+        return Err(Error::Validation(format!(
+            "Buttons message must have 1 to {} buttons, got {}",
+            MAX_BUTTONS,
+            buttons.len()
+        )));
+    }
+    validate_ids(buttons.iter().map(|b| b.id.as_str()))
+}
+
+/// Reject list messages WhatsApp would refuse or render incorrectly
+pub(crate) fn validate_list(button_text: &str, sections: &[ListSection]) -> Result<()> {
+    if button_text.trim().is_empty() {
+        return Err(Error::Validation("List button text is empty".into()));
+    }
+    if sections.iter().any(|s| s.rows.is_empty()) {
+        return Err(Error::Validation("List section has no rows".into()));
+    }
+
+    let rows: usize = sections.iter().map(|s| s.rows.len()).sum();
+    if rows == 0 || rows > MAX_LIST_ROWS {
+        return Err(Error::Validation(format!(
+            "List must have 1 to {} rows, got {}",
+            MAX_LIST_ROWS, rows
+        )));
+    }
+    validate_ids(
+        sections
+            .iter()
+            .flat_map(|s| s.rows.iter().map(|r| r.id.as_str())),
+    )
+}
+
+/// Responses only carry the ID, so IDs must be present and distinct
+fn validate_ids<'a>(ids: impl Iterator<Item = &'a str>) -> Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    for id in ids {
+        if id.is_empty() {
+            return Err(Error::Validation("Interactive option ID is empty".into()));
+        }
+        if !seen.insert(id) {
+            return Err(Error::Validation(format!(
+                "Duplicate interactive option ID '{}'",
+                id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Which kind of interactive message was answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractiveKind {
+    Button,
+    List,
+}
+
+/// A user tapped a reply button or picked a list row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractiveResponseEvent {
+    /// Envelope of the response message
+    pub info: MessageInfo,
+    pub kind: InteractiveKind,
+    /// ID of the chosen [`Button`] or [`ListRow`]
+    pub selected_id: String,
+    /// Title of the chosen option as shown to the user
+    pub selected_title: String,
+    /// ID of the message carrying the buttons or list
+    pub in_reply_to: Option<MessageId>,
+}
+
+impl InteractiveResponseEvent {
+    /// Parse a `buttonsResponseMessage` or `listResponseMessage`, if present
+    pub(crate) fn from_message(info: &MessageInfo, message: &Value) -> Option<Self> {
+        let (kind, raw, selected_id, selected_title) =
+            if let Some(raw) = message.get("buttonsResponseMessage") {
+                (
+                    InteractiveKind::Button,
+                    raw,
+                    raw.get("selectedButtonID"),
+                    // Oneof field, marshaled under its wrapper by the bridge
+                    raw.get("Response")
+                        .and_then(|r| r.get("SelectedDisplayText"))
+                        .or_else(|| raw.get("selectedDisplayText")),
+                )
+            } else if let Some(raw) = message.get("listResponseMessage") {
+                (
+                    InteractiveKind::List,
+                    raw,
+                    raw.get("singleSelectReply")
+                        .and_then(|r| r.get("selectedRowID")),
+                    raw.get("title"),
+                )
+            } else {
+                return None;
+            };
+
+        let str_of = |v: Option<&Value>| v.and_then(Value::as_str).unwrap_or_default().to_owned();
+
+        Some(Self {
+            info: info.clone(),
+            kind,
+            selected_id: str_of(selected_id),
+            selected_title: str_of(selected_title),
+            in_reply_to: raw
+                .get("contextInfo")
+                .and_then(|c| c.get("stanzaID"))
+                .and_then(Value::as_str)
+                .map(MessageId::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::WhatsApp;
+    use crate::events::MessageEvent;
+    use crate::mock_ffi::{self, Mock};
+
+    fn response(content: Value) -> InteractiveResponseEvent {
+        let event: MessageEvent = serde_json::from_value(mock_ffi::message(
+            "R1",
+            "1@s.whatsapp.net",
+            "1@s.whatsapp.net",
+            content,
+        ))
+        .unwrap();
+        InteractiveResponseEvent::from_message(&event.info, event.message.as_ref().unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn buttons_serialize_with_ids_and_titles() {
+        let buttons = [Button::new("yes", "Yes"), Button::new("no", "No")];
+        let payload = ButtonsPayload {
+            body: "Continue?",
+            buttons: &buttons,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "body": "Continue?",
+                "buttons": [
+                    { "id": "yes", "title": "Yes" },
+                    { "id": "no", "title": "No" },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn list_rows_without_a_description_omit_it() {
+        let sections = [ListSection::new(
+            "Sizes",
+            vec![
+                ListRow::new("s", "Small"),
+                ListRow::new("l", "Large").description("Extra cost"),
+            ],
+        )];
+        let payload = ListPayload {
+            body: "Pick a size",
+            button_text: "Sizes",
+            sections: &sections,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "body": "Pick a size",
+                "button_text": "Sizes",
+                "sections": [{
+                    "title": "Sizes",
+                    "rows": [
+                        { "id": "s", "title": "Small" },
+                        { "id": "l", "title": "Large", "description": "Extra cost" },
+                    ],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn button_response_parses_selection_and_quoted_message() {
+        let event = response(json!({
+            "buttonsResponseMessage": {
+                "selectedButtonID": "yes",
+                "Response": { "SelectedDisplayText": "Yes" },
+                "contextInfo": { "stanzaID": "Q1" },
+            }
+        }));
+
+        assert_eq!(event.kind, InteractiveKind::Button);
+        assert_eq!(event.selected_id, "yes");
+        assert_eq!(event.selected_title, "Yes");
+        assert_eq!(event.in_reply_to, Some(MessageId::from("Q1")));
+    }
+
+    #[test]
+    fn list_response_parses_selected_row() {
+        let event = response(json!({
+            "listResponseMessage": {
+                "title": "Large",
+                "singleSelectReply": { "selectedRowID": "l" },
+            }
+        }));
+
+        assert_eq!(event.kind, InteractiveKind::List);
+        assert_eq!(event.selected_id, "l");
+        assert_eq!(event.selected_title, "Large");
+        assert_eq!(event.in_reply_to, None);
+    }
+
+    #[tokio::test]
+    async fn on_interactive_response_receives_button_taps() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let client = WhatsApp::connect(mock_ffi::db_path("interactive-handler"))
+            .on_interactive_response(move |event| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(event.selected_id);
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+        mock.push_event(
+            "message",
+            mock_ffi::message(
+                "R1",
+                "1@s.whatsapp.net",
+                "1@s.whatsapp.net",
+                json!({ "buttonsResponseMessage": { "selectedButtonID": "yes" } }),
+            ),
+        );
+        let pump = client.clone();
+        tokio::spawn(async move { pump.run().await });
+
+        let selected = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(selected.as_deref(), Some("yes"));
+        assert_eq!(client.handler_stats().interactive_response, 1);
+        client.disconnect();
+    }
+}
@@ -0,0 +1,170 @@
+//! Latency histograms for FFI operations
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Buckets per power of two of microseconds, for about 19% resolution
+const SUB_BUCKETS: u32 = 4;
+/// Powers of two covered, up to 2^40 µs (about 12 days)
+const OCTAVES: u32 = 40;
+
+/// Summary of how long one FFI operation has taken
+///
+/// Percentiles are read from a bucketed histogram, so they are accurate to
+/// within about a fifth of their value and never exceed `max`, which is exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LatencyStats {
+    /// Calls recorded
+    pub calls: u64,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// One client's histograms per operation name, fed only once enabled
+#[derive(Default)]
+pub(crate) struct LatencyRecorder {
+    enabled: AtomicBool,
+    histograms: Mutex<BTreeMap<String, LatencyHistogram>>,
+}
+
+impl LatencyRecorder {
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Run `f`, recording its duration under `name` if enabled
+    pub fn time<T>(&self, name: &str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        let mut histograms = self.histograms.lock();
+        match histograms.get_mut(name) {
+            Some(histogram) => histogram.record(elapsed),
+            None => {
+                let mut histogram = LatencyHistogram::new();
+                histogram.record(elapsed);
+                histograms.insert(name.to_string(), histogram);
+            }
+        }
+        result
+    }
+
+    /// Latency summary per operation name, for the calls recorded so far
+    pub fn stats(&self) -> HashMap<String, LatencyStats> {
+        self.histograms
+            .lock()
+            .iter()
+            .map(|(name, histogram)| (name.clone(), histogram.stats()))
+            .collect()
+    }
+}
+
+/// Log-scale histogram of call durations
+pub(crate) struct LatencyHistogram {
+    buckets: Vec<u64>,
+    calls: u64,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: vec![0; (OCTAVES * SUB_BUCKETS) as usize],
+            calls: 0,
+            max: Duration::ZERO,
+        }
+    }
+
+    pub fn record(&mut self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let index = bucket(micros).min(self.buckets.len() - 1);
+        self.buckets[index] += 1;
+        self.calls += 1;
+        self.max = self.max.max(elapsed);
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        LatencyStats {
+            calls: self.calls,
+            p50: self.percentile(0.5),
+            p99: self.percentile(0.99),
+            max: self.max,
+        }
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile, capped at `max`
+    fn percentile(&self, q: f64) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((q * self.calls as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return upper_bound(index).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Bucket for a duration: the power of two, then the quarter within it
+fn bucket(micros: u64) -> usize {
+    let micros = micros.max(1);
+    let octave = micros.ilog2();
+    let sub = ((u128::from(micros) * u128::from(SUB_BUCKETS)) >> octave) as u32 % SUB_BUCKETS;
+    (octave * SUB_BUCKETS + sub) as usize
+}
+
+fn upper_bound(index: usize) -> Duration {
+    let octave = index as u32 / SUB_BUCKETS;
+    let sub = index as u32 % SUB_BUCKETS;
+    let micros = 2f64.powi(octave as i32) * (1.0 + f64::from(sub + 1) / f64::from(SUB_BUCKETS));
+    Duration::from_micros(micros as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_track_the_recorded_durations() {
+        let mut histogram = LatencyHistogram::new();
+        for ms in 1..=100 {
+            histogram.record(Duration::from_millis(ms));
+        }
+
+        let stats = histogram.stats();
+
+        assert_eq!(stats.calls, 100);
+        assert_eq!(stats.max, Duration::from_millis(100));
+        // Within a bucket's width of the true value, rounded up
+        assert!((50..=62).contains(&stats.p50.as_millis()), "{:?}", stats);
+        assert!((99..=100).contains(&stats.p99.as_millis()), "{:?}", stats);
+    }
+
+    #[test]
+    fn an_empty_histogram_reports_zeros() {
+        let stats = LatencyHistogram::new().stats();
+
+        assert_eq!(
+            stats,
+            LatencyStats {
+                calls: 0,
+                p50: Duration::ZERO,
+                p99: Duration::ZERO,
+                max: Duration::ZERO,
+            }
+        );
+    }
+}
@@ -18,33 +18,133 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## Cargo features
+//!
+//! Connecting, sending and events need no features. Optional subsystems:
+//!
+//! - `track-allocations`: count heap usage for [`WhatsApp::diagnostics`] by
+//!   installing a global allocator (opt-in, as it replaces the
+//!   application's allocator)
+//! - `init-tracing` (default): `init_tracing()` console logger
+//! - `image`: generate thumbnails for sent images
+//! - `remote-media`: fetch link previews and remote media
+//! - `qr`: render pairing QR codes in the terminal, or as PNG/SVG for
+//!   dashboards
+//! - `embed-dll`: embed the Go bridge in the binary
+//! - `payments` (experimental): send and receive payment requests, where
+//!   WhatsApp offers payments to the account
+//! - `zeroize`: wipe the bridge's event buffer when a client is dropped
+//!   ([`SessionBlob`]s are always wiped)
 
+mod album;
 mod allocator;
+mod appstate;
 mod builder;
+mod calls;
 mod client;
+mod clock;
+mod coalesce;
+mod config;
+mod contact_book;
+mod contacts;
+mod dedup;
+mod devices;
+mod diagnostics;
+mod dispatch;
 mod embedded;
 mod error;
 mod event_bus;
 mod events;
 mod ffi;
+mod groups;
 mod handlers;
+mod history;
+mod history_sync;
 mod inner;
+mod interactive;
+mod latency;
+mod lid;
 mod manager;
+mod metrics;
+#[cfg(test)]
+mod mock_ffi;
+mod options;
+mod outgoing;
+mod parsed;
+#[cfg(feature = "payments")]
+mod payments;
+mod phone;
+mod privacy;
+mod profile;
+#[cfg(feature = "qr")]
+mod qr;
+mod recipient;
+mod reconnect;
+mod send_queue;
+mod sent;
+mod session;
+mod sticker;
+mod store_stats;
 mod stream;
+mod system;
+mod template;
+mod thumbnail;
+mod timestamp;
+mod typing;
 
-pub use allocator::TrackedAllocator;
+pub use allocator::{MemoryStats, TrackedAllocator};
+pub use appstate::{AppStateCollection, AppStateSyncedEvent, ChatDeletedEvent};
 pub use builder::WhatsAppBuilder;
-pub use client::WhatsApp;
+pub use calls::CallOfferEvent;
+pub use client::{PRESENCE_QUERY_TIMEOUT, WhatsApp};
+pub use clock::ClockOffset;
+pub use contact_book::{ContactBook, KnownContact};
+pub use contacts::ContactInfo;
+pub use devices::LinkedDevice;
+pub use diagnostics::Diagnostics;
+pub use dispatch::EventOrder;
 pub use embedded::ensure_dll_extracted;
-pub use error::{Error, Result};
+pub use error::{Error, JidError, PhoneError, Result};
 pub use events::{
-    Event, Jid, LoggedOutEvent, MediaSource, MessageEvent, MessageInfo, MessageType,
-    PairSuccessEvent, PresenceEvent, QrEvent, ReceiptEvent,
+    DecryptFailEvent, DisconnectReason, EphemeralSettingEvent, Event, Jid, LoggedOutEvent,
+    MediaSource, MessageEditedEvent, MessageEvent, MessageId, MessageInfo, MessageType,
+    PairSuccessEvent, PollVoteEvent, PresenceEvent, ProtocolEvent, ProtocolKind, QrEvent,
+    QuotedMessage, ReceiptEvent, ReceiptStatus,
 };
+pub use groups::{GroupMetadata, GroupParticipant};
+pub use handlers::HandlerStats;
+pub use history::StoredMessage;
+pub use history_sync::HistoryChunk;
+pub use interactive::{
+    Button, InteractiveKind, InteractiveResponseEvent, ListRow, ListSection, MAX_BUTTONS,
+    MAX_LIST_ROWS,
+};
+pub use latency::LatencyStats;
+pub use lid::JidPair;
 pub use manager::{ClientId, WhatsAppManager};
-pub use stream::EventStream;
+pub use metrics::Metrics;
+pub use options::{LinkPreview, MAX_TIMESTAMP_LEAD, SendOptions};
+pub use parsed::{ImageContent, Location, MessageKey, ParsedMessage, Reaction, VideoContent};
+#[cfg(feature = "payments")]
+pub use payments::{PaymentEvent, PaymentKind};
+pub use phone::PhoneNumber;
+pub use privacy::{PrivacyKey, PrivacySettings, PrivacyValue};
+pub use profile::{MAX_PUSH_NAME_LEN, PictureQuality, ProfilePicture};
+#[cfg(feature = "qr")]
+pub use qr::render_qr_to_terminal;
+pub use recipient::Recipient;
+pub use send_queue::Priority;
+pub use sent::SentMessage;
+pub use session::SessionBlob;
+pub use sticker::StickerInfo;
+pub use store_stats::StoreStats;
+pub use stream::{EnvelopeStream, EventEnvelope, EventStream};
+pub use system::{SystemEvent, SystemKind};
+pub use typing::{ChatPresence, TypingGuard};
 
 /// Initialize default tracing subscriber
+#[cfg(feature = "init-tracing")]
 pub fn init_tracing() {
     use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -56,3 +156,51 @@ pub fn init_tracing() {
         .with(fmt::layer().compact())
         .init();
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::mock_ffi::{self, Mock};
+
+    // Uses no feature-gated API: `cargo test --no-default-features` runs it
+    // to check the core client needs none of them
+    #[tokio::test]
+    async fn core_api_works_without_optional_features() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let client = WhatsApp::connect(mock_ffi::db_path("core-api"))
+            .on_message(move |msg| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(msg.info.id);
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&client);
+
+        let sent = client.send(Jid::user("1234567890"), "hi").unwrap();
+        assert!(!sent.id.as_str().is_empty());
+
+        let mut events = client.events();
+        let pump = client.clone();
+        let run = tokio::spawn(async move { pump.run().await });
+        mock.push_event(
+            "message",
+            mock_ffi::text_message("A1", "1@s.whatsapp.net", "1@s.whatsapp.net", "hello"),
+        );
+        assert!(matches!(
+            mock_ffi::next_event(&mut events).await,
+            Event::Message(_)
+        ));
+        assert_eq!(rx.recv().await.unwrap(), "A1");
+
+        #[cfg(not(feature = "track-allocations"))]
+        assert_eq!(client.diagnostics().memory.peak_bytes, 0);
+
+        client.disconnect();
+        run.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+//! LID addressing: WhatsApp's server-assigned user identities
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{Jid, MessageInfo};
+
+/// The phone number and LID addresses of one user, as far as known
+///
+/// During the LID migration a message may identify its sender by phone
+/// number, by LID, or both (`sender` and `sender_alt`). Use the pair to key
+/// per-user state consistently whichever address arrives.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JidPair {
+    /// `…@s.whatsapp.net` address, without device
+    pub phone: Option<Jid>,
+    /// `…@lid` address, without device
+    pub lid: Option<Jid>,
+}
+
+impl JidPair {
+    /// Sort addresses into phone and LID slots; others are ignored
+    pub fn from_jids<'a>(jids: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut pair = Self::default();
+        for raw in jids {
+            let jid = Jid::new(raw).without_device();
+            if jid.is_lid() {
+                pair.lid.get_or_insert(jid);
+            } else if jid.is_user() {
+                pair.phone.get_or_insert(jid);
+            }
+        }
+        pair
+    }
+
+    /// The phone address if known, otherwise the LID
+    pub fn preferred(&self) -> Option<&Jid> {
+        self.phone.as_ref().or(self.lid.as_ref())
+    }
+}
+
+impl From<&MessageInfo> for JidPair {
+    fn from(info: &MessageInfo) -> Self {
+        Self::from_jids([info.sender.as_str(), info.sender_alt.as_str()])
+    }
+}
+
+/// LID to phone mapping as written by the bridge
+#[derive(Deserialize)]
+pub(crate) struct LidMapping {
+    #[serde(default)]
+    pub phone: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::MessageEvent;
+    use crate::mock_ffi::{self, Reply};
+
+    fn pair(sender: &str, sender_alt: &str) -> JidPair {
+        let mut data = mock_ffi::text_message("M1", "123-456@g.us", sender, "hi");
+        data["Info"]["SenderAlt"] = sender_alt.into();
+        let msg: MessageEvent = serde_json::from_value(data).unwrap();
+        JidPair::from(&msg.info)
+    }
+
+    #[test]
+    fn pairs_are_built_from_each_combination_of_addresses() {
+        let phone = || Some(Jid::from("15550001111@s.whatsapp.net"));
+        let lid = || Some(Jid::from("98765@lid"));
+
+        assert_eq!(
+            pair("15550001111:2@s.whatsapp.net", ""),
+            JidPair {
+                phone: phone(),
+                lid: None
+            }
+        );
+        assert_eq!(
+            pair("98765:2@lid", ""),
+            JidPair {
+                phone: None,
+                lid: lid()
+            }
+        );
+        assert_eq!(
+            pair("15550001111@s.whatsapp.net", "98765@lid"),
+            JidPair {
+                phone: phone(),
+                lid: lid()
+            }
+        );
+        assert_eq!(
+            pair("98765@lid", "15550001111@s.whatsapp.net"),
+            JidPair {
+                phone: phone(),
+                lid: lid()
+            }
+        );
+        assert_eq!(pair("", ""), JidPair::default());
+    }
+
+    #[test]
+    fn the_phone_address_is_preferred() {
+        assert_eq!(
+            pair("98765@lid", "15550001111@s.whatsapp.net").preferred(),
+            Some(&Jid::from("15550001111@s.whatsapp.net"))
+        );
+        assert_eq!(
+            pair("98765@lid", "").preferred(),
+            Some(&Jid::from("98765@lid"))
+        );
+    }
+
+    #[tokio::test]
+    async fn lids_resolve_to_phone_jids() {
+        let (client, mock) = mock_ffi::client("resolve-lid").await;
+        mock.reply(
+            "wm_resolve_lid",
+            Reply::ok(br#"{"phone":"15550001111@s.whatsapp.net"}"#.to_vec()),
+        );
+
+        assert_eq!(
+            client.resolve_lid("98765@lid").unwrap(),
+            Some(Jid::from("15550001111@s.whatsapp.net"))
+        );
+        assert_eq!(client.resolve_lid("98765@lid").unwrap(), None);
+        assert!(client.resolve_lid("15550001111@s.whatsapp.net").is_err());
+        assert_eq!(mock.calls("wm_resolve_lid").len(), 2);
+    }
+}
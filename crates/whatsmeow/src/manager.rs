@@ -1,21 +1,21 @@
 //! Multi-client management
 
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use dashmap::DashMap;
+use futures::future::join_all;
 
 use crate::builder::WhatsAppBuilder;
 use crate::client::WhatsApp;
 use crate::error::{Error, Result};
-use crate::inner::InnerClient;
 
 /// Unique identifier for a client
 pub type ClientId = String;
 
 /// Manager for multiple WhatsApp client instances
 pub struct WhatsAppManager {
-    clients: DashMap<ClientId, Arc<InnerClient>>,
+    clients: DashMap<ClientId, WhatsApp>,
 }
 
 impl WhatsAppManager {
@@ -41,11 +41,73 @@ impl WhatsAppManager {
         Ok(WhatsAppBuilder::new(db_path.into()))
     }
 
+    /// Open a client for every `*.db` session file in `dir`
+    ///
+    /// Each client is keyed by its file name stem (`alice.db` becomes
+    /// `alice`), built with default options and connected, but its event
+    /// loop is not started: fetch it with [`get`](Self::get) and call
+    /// [`run`](WhatsApp::run). Other files, IDs already registered and
+    /// sessions that fail to open are skipped with a warning. Returns the
+    /// IDs opened, in file name order; fails only if `dir` can't be read.
+    ///
+    /// Sessions are opened concurrently, so startup takes about as long as
+    /// the slowest one rather than the sum of all.
+    pub async fn load_all(&self, dir: &Path) -> Result<Vec<ClientId>> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| Error::Init(format!("Failed to read {}: {}", dir.display(), e)))?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "db"))
+            .collect();
+        paths.sort();
+
+        let sessions: Vec<(ClientId, PathBuf)> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let Some(id) = path.file_stem().and_then(|s| s.to_str()).map(str::to_owned) else {
+                    tracing::warn!(path = %path.display(), "Skipping session with non-UTF-8 name");
+                    return None;
+                };
+                if self.clients.contains_key(&id) {
+                    tracing::warn!(client_id = %id, "Skipping session, client already exists");
+                    return None;
+                }
+                Some((id, path))
+            })
+            .collect();
+
+        let opened = join_all(
+            sessions
+                .iter()
+                .map(|(_, path)| WhatsAppBuilder::new(path.clone()).build()),
+        )
+        .await;
+
+        let mut loaded = Vec::new();
+        for ((id, path), result) in sessions.into_iter().zip(opened) {
+            match result {
+                // Registered while this was opening; the newer client is dropped
+                Ok(_) if self.clients.contains_key(&id) => {
+                    tracing::warn!(client_id = %id, "Skipping session, client already exists");
+                }
+                Ok(client) => {
+                    self.clients.insert(id.clone(), client);
+                    tracing::info!(client_id = %id, "Client loaded");
+                    loaded.push(id);
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, path = %path.display(), "Failed to open session");
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+
     /// Get an existing client by ID
     pub fn get(&self, id: &str) -> Option<WhatsApp> {
-        self.clients
-            .get(id)
-            .map(|inner| WhatsApp::from_inner(inner.clone()))
+        self.clients.get(id).map(|client| client.clone())
     }
 
     /// Shutdown and remove a client
@@ -57,12 +119,63 @@ impl WhatsAppManager {
     }
 
     /// Shutdown all clients
+    ///
+    /// Disconnects run in parallel, so one slow client doesn't hold up the
+    /// rest. Run loops may still be finishing when this returns; see
+    /// [`shutdown_all_graceful`](Self::shutdown_all_graceful).
     pub fn shutdown_all(&self) {
-        for entry in self.clients.iter() {
-            entry.value().disconnect();
+        let clients = self.take_all();
+        std::thread::scope(|scope| {
+            for (_, client) in &clients {
+                scope.spawn(|| client.disconnect());
+            }
+        });
+        tracing::info!(count = clients.len(), "All clients shut down");
+    }
+
+    /// Shutdown all clients and wait for their run loops to exit
+    ///
+    /// Waits up to `timeout` in total. Returns the IDs of clients whose run
+    /// loop was still going when it expired (empty on a clean shutdown).
+    pub async fn shutdown_all_graceful(&self, timeout: Duration) -> Vec<ClientId> {
+        let clients = self.take_all();
+
+        join_all(clients.iter().map(|(_, client)| {
+            let client = client.share();
+            async move {
+                let _ = tokio::task::spawn_blocking(move || client.disconnect()).await;
+            }
+        }))
+        .await;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let stopped = join_all(
+            clients
+                .iter()
+                .map(|(_, client)| tokio::time::timeout_at(deadline, client.inner.wait_stopped())),
+        )
+        .await;
+
+        let stuck: Vec<ClientId> = clients
+            .into_iter()
+            .zip(stopped)
+            .filter(|(_, stopped)| stopped.is_err())
+            .map(|((id, _), _)| id)
+            .collect();
+        if stuck.is_empty() {
+            tracing::info!("All clients shut down gracefully");
+        } else {
+            tracing::warn!(?stuck, "Timed out waiting for client run loops");
         }
-        self.clients.clear();
-        tracing::info!("All clients shut down");
+        stuck
+    }
+
+    /// Remove every client from the map
+    fn take_all(&self) -> Vec<(ClientId, WhatsApp)> {
+        self.list()
+            .into_iter()
+            .filter_map(|id| self.clients.remove(&id))
+            .collect()
     }
 
     /// Get number of active clients
@@ -81,3 +194,79 @@ impl Default for WhatsAppManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::mock_ffi;
+
+    /// Empty directory holding `files`
+    fn session_dir(test: &str, files: &[&str]) -> PathBuf {
+        let dir = mock_ffi::db_path(test).with_extension("");
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        dir
+    }
+
+    #[tokio::test]
+    async fn load_all_registers_a_client_per_db_file() {
+        let dir = session_dir("load-all", &["bob.db", "alice.db", "notes.txt"]);
+        let manager = WhatsAppManager::new();
+
+        let loaded = manager.load_all(&dir).await.unwrap();
+
+        assert_eq!(loaded, ["alice", "bob"]);
+        assert_eq!(manager.count(), 2);
+        assert!(manager.get("alice").unwrap().is_connected());
+        assert!(manager.get("notes").is_none());
+    }
+
+    #[tokio::test]
+    async fn load_all_opens_sessions_concurrently() {
+        let dir = session_dir("load-all-concurrent", &["a.db", "b.db", "c.db"]);
+        for file in ["a.db", "b.db", "c.db"] {
+            mock_ffi::before_open(&dir.join(file), |mock| {
+                mock.delay("wm_client_connect", Duration::from_millis(200));
+            });
+        }
+        let manager = WhatsAppManager::new();
+        let started = Instant::now();
+
+        assert_eq!(manager.load_all(&dir).await.unwrap().len(), 3);
+
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_waits_for_every_run_loop() {
+        let dir = session_dir("shutdown-graceful", &["a.db", "b.db"]);
+        let manager = WhatsAppManager::new();
+        manager.load_all(&dir).await.unwrap();
+        let clients: Vec<WhatsApp> = ["a", "b"]
+            .iter()
+            .map(|id| manager.get(id).unwrap())
+            .collect();
+        for client in &clients {
+            let mut events = client.events();
+            mock_ffi::Mock::of(client).push_event("connected", serde_json::Value::Null);
+            let pump = client.clone();
+            tokio::spawn(async move { pump.run().await });
+            mock_ffi::next_event(&mut events).await;
+        }
+
+        let stuck = manager.shutdown_all_graceful(Duration::from_secs(1)).await;
+
+        assert!(stuck.is_empty(), "{:?}", stuck);
+        assert_eq!(manager.count(), 0);
+        for client in &clients {
+            // Resolves without waiting only once the loop has returned
+            assert!(client.inner.wait_stopped().now_or_never().is_some());
+        }
+    }
+}
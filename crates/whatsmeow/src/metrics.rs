@@ -0,0 +1,14 @@
+//! Runtime metrics
+
+use serde::Serialize;
+
+/// Point-in-time snapshot of client metrics
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Metrics {
+    /// Callers currently holding or queued for the FFI handle
+    pub ffi_queue_depth: usize,
+    /// Events received from the bridge since the client was built
+    pub events_received: u64,
+    /// Successful automatic reconnects
+    pub reconnects: u64,
+}
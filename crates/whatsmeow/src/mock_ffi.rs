@@ -0,0 +1,1059 @@
+//! In-process stand-in for the Go bridge, used by unit tests
+//!
+//! Under `cfg(test)` the FFI layer calls these functions instead of
+//! `whatsmeow_sys`, so tests run without a WhatsApp connection. Every call is
+//! recorded with its arguments (strings as-is, byte buffers as hex), and
+//! tests script replies, delays and events per client through [`Mock`].
+
+// Mirrors the whole bridge; not every function and helper has a test yet
+#![allow(dead_code, clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CStr, c_char, c_int, c_longlong};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use whatsmeow_sys::{ClientHandle, WmResult, error_codes::*};
+
+use futures::StreamExt;
+
+use crate::client::WhatsApp;
+use crate::events::Event;
+use crate::stream::EventStream;
+
+/// What a scripted call returns
+#[derive(Debug, Clone)]
+pub(crate) struct Reply {
+    pub code: c_int,
+    pub output: Vec<u8>,
+}
+
+impl Reply {
+    pub fn ok(output: impl Into<Vec<u8>>) -> Self {
+        Self {
+            code: WM_OK,
+            output: output.into(),
+        }
+    }
+
+    pub fn err(code: c_int) -> Self {
+        Self {
+            code,
+            output: Vec::new(),
+        }
+    }
+}
+
+/// Arguments a client was opened with
+#[derive(Debug, Clone)]
+pub(crate) struct Opened {
+    pub device_name: String,
+    pub options: Value,
+}
+
+type Setup = Box<dyn FnOnce(&Mock) + Send>;
+
+#[derive(Default)]
+struct MockClient {
+    db_path: PathBuf,
+    calls: Vec<(&'static str, Vec<String>)>,
+    events: VecDeque<Vec<u8>>,
+    replies: HashMap<&'static str, VecDeque<Reply>>,
+    delays: HashMap<&'static str, Duration>,
+    last_error: String,
+    server_time: i64,
+    destroyed: bool,
+    /// Other live clients on the same database when this one was opened
+    opened_alongside: usize,
+}
+
+#[derive(Default)]
+struct Bridge {
+    clients: HashMap<usize, MockClient>,
+    opened: HashMap<PathBuf, Opened>,
+    setups: HashMap<PathBuf, Setup>,
+    client_version: Option<(c_int, c_int, c_int)>,
+}
+
+static BRIDGE: LazyLock<Mutex<Bridge>> = LazyLock::new(Default::default);
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Handle on one mock client, for scripting it and inspecting its calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Mock(usize);
+
+impl Mock {
+    /// The mock behind a built client
+    pub fn of(client: &WhatsApp) -> Self {
+        Self(client.inner.ffi.lock().handle() as usize)
+    }
+
+    /// Queue an event as the bridge would emit it
+    pub fn push_event(&self, event_type: &str, data: Value) {
+        let raw = json!({ "type": event_type, "timestamp": 0, "data": data });
+        self.push_raw(serde_json::to_vec(&raw).unwrap());
+    }
+
+    /// Queue raw bytes to be returned by the next event poll
+    pub fn push_raw(&self, bytes: impl Into<Vec<u8>>) {
+        self.with(|c| c.events.push_back(bytes.into()));
+    }
+
+    /// Script the next call to `name`; unscripted calls succeed with no output
+    /// (send calls get a generated send result)
+    pub fn reply(&self, name: &'static str, reply: Reply) {
+        self.with(|c| c.replies.entry(name).or_default().push_back(reply));
+    }
+
+    /// Make every call to `name` take `delay`
+    pub fn delay(&self, name: &'static str, delay: Duration) {
+        self.with(|c| {
+            c.delays.insert(name, delay);
+        });
+    }
+
+    pub fn set_last_error(&self, message: &str) {
+        self.with(|c| c.last_error = message.to_string());
+    }
+
+    pub fn set_server_time(&self, millis: i64) {
+        self.with(|c| c.server_time = millis);
+    }
+
+    /// Arguments of every call to `name`, oldest first
+    pub fn calls(&self, name: &str) -> Vec<Vec<String>> {
+        self.with(|c| {
+            c.calls
+                .iter()
+                .filter(|(n, _)| *n == name)
+                .map(|(_, args)| args.clone())
+                .collect()
+        })
+    }
+
+    /// [`calls`](Self::calls) once there are at least `count`, failing the
+    /// test if that takes over a second (for calls made by background tasks)
+    pub async fn wait_for_calls(&self, name: &str, count: usize) -> Vec<Vec<String>> {
+        tokio::time::timeout(Duration::from_secs(1), async {
+            loop {
+                let calls = self.calls(name);
+                if calls.len() >= count {
+                    return calls;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("{count} calls to {name} within a second"))
+    }
+
+    /// Names of all calls in order, event polls excluded
+    pub fn call_names(&self) -> Vec<&'static str> {
+        self.with(|c| {
+            c.calls
+                .iter()
+                .map(|(n, _)| *n)
+                .filter(|n| *n != "wm_poll_event")
+                .collect()
+        })
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.with(|c| c.destroyed)
+    }
+
+    /// Forget this handle, as the bridge does when it restarts
+    pub fn invalidate(&self) {
+        self.with(|c| c.destroyed = true);
+    }
+
+    pub fn opened_alongside(&self) -> usize {
+        self.with(|c| c.opened_alongside)
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.with(|c| c.db_path.clone())
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut MockClient) -> T) -> T {
+        let mut bridge = BRIDGE.lock();
+        f(bridge.clients.get_mut(&self.0).expect("mock client exists"))
+    }
+}
+
+/// A connected client on a fresh mock, with default options
+pub(crate) async fn client(test: &str) -> (WhatsApp, Mock) {
+    let client = WhatsApp::connect(db_path(test)).build().await.unwrap();
+    let mock = Mock::of(&client);
+    (client, mock)
+}
+
+/// Bridge `message` event data for a text message
+pub(crate) fn text_message(id: &str, chat: &str, sender: &str, text: &str) -> Value {
+    message(id, chat, sender, json!({ "conversation": text }))
+}
+
+/// Bridge `message` event data carrying `content` as its `waE2E.Message`
+pub(crate) fn message(id: &str, chat: &str, sender: &str, content: Value) -> Value {
+    json!({
+        "Info": {
+            "ID": id,
+            "Chat": chat,
+            "Sender": sender,
+            "IsFromMe": false,
+            "IsGroup": chat.ends_with("@g.us"),
+            "PushName": "",
+            "Timestamp": "2024-01-01T00:00:00Z",
+        },
+        "Message": content,
+    })
+}
+
+/// The next event on `stream`, failing the test if none arrives within a second
+pub(crate) async fn next_event(stream: &mut EventStream) -> Event {
+    tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("event within a second")
+        .expect("stream still open")
+}
+
+/// Tracing subscriber collecting the messages of warnings
+///
+/// Install with `tracing::subscriber::set_default` in a current-thread test
+/// so tasks spawned on the runtime report to it too.
+#[derive(Clone, Default)]
+pub(crate) struct Warnings(Arc<Mutex<Vec<String>>>);
+
+impl Warnings {
+    pub fn messages(&self) -> Vec<String> {
+        self.0.lock().clone()
+    }
+}
+
+impl tracing::Subscriber for Warnings {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        *metadata.level() <= tracing::Level::WARN
+    }
+
+    fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        struct Message<'a>(&'a mut String);
+        impl tracing::field::Visit for Message<'_> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    *self.0 = format!("{value:?}");
+                }
+            }
+        }
+
+        let mut message = String::new();
+        event.record(&mut Message(&mut message));
+        self.0.lock().push(message);
+    }
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+/// A session path no other test uses (nothing is created on disk)
+pub(crate) fn db_path(test: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir()
+        .join(format!("whatsmeow-test-{}", std::process::id()))
+        .join(format!("{test}-{n}.db"))
+}
+
+/// Script a client before it is opened (e.g. to slow down its connect)
+pub(crate) fn before_open(db_path: &Path, setup: impl FnOnce(&Mock) + Send + 'static) {
+    BRIDGE
+        .lock()
+        .setups
+        .insert(db_path.to_path_buf(), Box::new(setup));
+}
+
+/// How the client at `db_path` was opened, if it was
+pub(crate) fn opened(db_path: &Path) -> Option<Opened> {
+    BRIDGE.lock().opened.get(db_path).cloned()
+}
+
+/// Mocks of every client opened on `db_path`, oldest first
+pub(crate) fn clients_at(db_path: &Path) -> Vec<Mock> {
+    let bridge = BRIDGE.lock();
+    let mut ids: Vec<usize> = bridge
+        .clients
+        .iter()
+        .filter(|(_, c)| c.db_path == db_path)
+        .map(|(id, _)| *id)
+        .collect();
+    ids.sort();
+    ids.into_iter().map(Mock).collect()
+}
+
+/// Last version passed to `wm_set_client_version`
+pub(crate) fn client_version() -> Option<(c_int, c_int, c_int)> {
+    BRIDGE.lock().client_version
+}
+
+fn c_str(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn c_bytes(ptr: *const c_char, len: c_int) -> String {
+    if ptr.is_null() || len <= 0 {
+        return String::new();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Record a call and work out its reply, sleeping outside the bridge lock
+fn call_reply(handle: ClientHandle, name: &'static str, args: Vec<String>) -> Reply {
+    let (reply, delay) = {
+        let mut bridge = BRIDGE.lock();
+        let Some(client) = bridge.clients.get_mut(&(handle as usize)) else {
+            return Reply::err(WM_ERR_INVALID_HANDLE);
+        };
+        if client.destroyed {
+            return Reply::err(WM_ERR_INVALID_HANDLE);
+        }
+        let reply = client
+            .replies
+            .get_mut(name)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_else(|| default_reply(name, &args));
+        client.calls.push((name, args));
+        (reply, client.delays.get(name).copied())
+    };
+    if let Some(delay) = delay {
+        std::thread::sleep(delay);
+    }
+    reply
+}
+
+/// Unscripted replies: sends succeed with a fresh ID (or the requested one)
+fn default_reply(name: &str, args: &[String]) -> Reply {
+    static NEXT_MESSAGE: AtomicUsize = AtomicUsize::new(0);
+    let next_id = || format!("3EB0{:018X}", NEXT_MESSAGE.fetch_add(1, Ordering::Relaxed));
+    if !name.starts_with("wm_send_") || name == "wm_send_chat_presence" {
+        return Reply::ok(Vec::new());
+    }
+    if name == "wm_send_album" {
+        let items: Vec<Value> = serde_json::from_str(&args[1]).unwrap();
+        let ids: Vec<String> = items.iter().map(|_| next_id()).collect();
+        return Reply::ok(serde_json::to_vec(&ids).unwrap());
+    }
+    let requested = (name == "wm_send_message_ex")
+        .then(|| serde_json::from_str::<Value>(&args[2]).ok())
+        .flatten()
+        .and_then(|options| options["message_id"].as_str().map(str::to_owned));
+    let id = requested.unwrap_or_else(next_id);
+    let result = json!({ "id": id, "timestamp": "2024-01-01T00:00:00Z" });
+    Reply::ok(serde_json::to_vec(&result).unwrap())
+}
+
+fn write(reply: Reply, buf: *mut c_char, buf_len: c_int) -> c_int {
+    if reply.code != WM_OK {
+        return reply.code;
+    }
+    if reply.output.len() > buf_len as usize {
+        return WM_ERR_BUFFER_TOO_SMALL;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(reply.output.as_ptr(), buf as *mut u8, reply.output.len());
+    }
+    reply.output.len() as c_int
+}
+
+fn open(db_path: *const c_char, device_name: *const c_char, options: Value) -> ClientHandle {
+    let db_path = PathBuf::from(c_str(db_path));
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let setup = {
+        let mut bridge = BRIDGE.lock();
+        let opened_alongside = bridge
+            .clients
+            .values()
+            .filter(|c| c.db_path == db_path && !c.destroyed)
+            .count();
+        bridge.clients.insert(
+            id,
+            MockClient {
+                db_path: db_path.clone(),
+                opened_alongside,
+                ..Default::default()
+            },
+        );
+        bridge.opened.insert(
+            db_path.clone(),
+            Opened {
+                device_name: c_str(device_name),
+                options,
+            },
+        );
+        bridge.setups.remove(&db_path)
+    };
+    if let Some(setup) = setup {
+        setup(&Mock(id));
+    }
+    id as ClientHandle
+}
+
+pub unsafe fn wm_client_new(db_path: *const c_char, device_name: *const c_char) -> ClientHandle {
+    open(db_path, device_name, Value::Null)
+}
+
+pub unsafe fn wm_client_new_ex(
+    db_path: *const c_char,
+    device_name: *const c_char,
+    options_json: *const c_char,
+) -> ClientHandle {
+    let options = serde_json::from_str(&c_str(options_json)).unwrap_or(Value::Null);
+    open(db_path, device_name, options)
+}
+
+pub unsafe fn wm_set_client_version(major: c_int, minor: c_int, patch: c_int) -> WmResult {
+    BRIDGE.lock().client_version = Some((major, minor, patch));
+    WM_OK
+}
+
+pub unsafe fn wm_client_destroy(handle: ClientHandle) {
+    if let Some(client) = BRIDGE.lock().clients.get_mut(&(handle as usize)) {
+        client.destroyed = true;
+    }
+}
+
+pub unsafe fn wm_poll_event(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    let reply = {
+        let mut bridge = BRIDGE.lock();
+        let Some(client) = bridge.clients.get_mut(&(handle as usize)) else {
+            return WM_ERR_INVALID_HANDLE;
+        };
+        if client.destroyed {
+            return WM_ERR_INVALID_HANDLE;
+        }
+        match client
+            .replies
+            .get_mut("wm_poll_event")
+            .and_then(VecDeque::pop_front)
+        {
+            Some(reply) => reply,
+            None => Reply::ok(client.events.pop_front().unwrap_or_default()),
+        }
+    };
+    write(reply, buf, buf_len)
+}
+
+pub unsafe fn wm_server_time(handle: ClientHandle) -> c_longlong {
+    BRIDGE
+        .lock()
+        .clients
+        .get(&(handle as usize))
+        .map_or(0, |c| c.server_time)
+}
+
+pub unsafe fn wm_last_error(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    let message = BRIDGE
+        .lock()
+        .clients
+        .get(&(handle as usize))
+        .map(|c| c.last_error.clone())
+        .unwrap_or_default();
+    write(Reply::ok(message), buf, buf_len)
+}
+
+pub unsafe fn wm_client_connect(handle: ClientHandle) -> WmResult {
+    call_reply(handle, "wm_client_connect", vec![]).code
+}
+
+pub unsafe fn wm_client_disconnect(handle: ClientHandle) -> WmResult {
+    call_reply(handle, "wm_client_disconnect", vec![]).code
+}
+
+pub unsafe fn wm_send_message(
+    handle: ClientHandle,
+    jid: *const c_char,
+    text: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_send_message", vec![c_str(jid), c_str(text)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_message_ex(
+    handle: ClientHandle,
+    jid: *const c_char,
+    text: *const c_char,
+    options_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_message_ex",
+            vec![c_str(jid), c_str(text), c_str(options_json)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_image(
+    handle: ClientHandle,
+    jid: *const c_char,
+    data: *const c_char,
+    data_len: c_int,
+    mime_type: *const c_char,
+    caption: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_image",
+            vec![
+                c_str(jid),
+                c_bytes(data, data_len),
+                c_str(mime_type),
+                c_str(caption),
+            ],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_image_ex(
+    handle: ClientHandle,
+    jid: *const c_char,
+    data: *const c_char,
+    data_len: c_int,
+    mime_type: *const c_char,
+    caption: *const c_char,
+    thumbnail: *const c_char,
+    thumbnail_len: c_int,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_image_ex",
+            vec![
+                c_str(jid),
+                c_bytes(data, data_len),
+                c_str(mime_type),
+                c_str(caption),
+                c_bytes(thumbnail, thumbnail_len),
+            ],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_image_url(
+    handle: ClientHandle,
+    jid: *const c_char,
+    url: *const c_char,
+    mime_type: *const c_char,
+    caption: *const c_char,
+    thumbnail: *const c_char,
+    thumbnail_len: c_int,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_image_url",
+            vec![
+                c_str(jid),
+                c_str(url),
+                c_str(mime_type),
+                c_str(caption),
+                c_bytes(thumbnail, thumbnail_len),
+            ],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_album(
+    handle: ClientHandle,
+    jid: *const c_char,
+    items_json: *const c_char,
+    caption: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_album",
+            vec![c_str(jid), c_str(items_json), c_str(caption)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_sticker(
+    handle: ClientHandle,
+    jid: *const c_char,
+    data: *const c_char,
+    data_len: c_int,
+    width: c_int,
+    height: c_int,
+    animated: c_int,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_sticker",
+            vec![
+                c_str(jid),
+                c_bytes(data, data_len),
+                width.to_string(),
+                height.to_string(),
+                animated.to_string(),
+            ],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_media(
+    handle: ClientHandle,
+    jid: *const c_char,
+    kind: *const c_char,
+    data: *const c_char,
+    data_len: c_int,
+    mime_type: *const c_char,
+    file_name: *const c_char,
+    caption: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_media",
+            vec![
+                c_str(jid),
+                c_str(kind),
+                c_bytes(data, data_len),
+                c_str(mime_type),
+                c_str(file_name),
+                c_str(caption),
+            ],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_buttons(
+    handle: ClientHandle,
+    jid: *const c_char,
+    buttons_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_buttons",
+            vec![c_str(jid), c_str(buttons_json)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_list(
+    handle: ClientHandle,
+    jid: *const c_char,
+    list_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_send_list", vec![c_str(jid), c_str(list_json)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_template(
+    handle: ClientHandle,
+    jid: *const c_char,
+    template_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_template",
+            vec![c_str(jid), c_str(template_json)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_payment_request(
+    handle: ClientHandle,
+    jid: *const c_char,
+    request_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_send_payment_request",
+            vec![c_str(jid), c_str(request_json)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_send_raw(
+    handle: ClientHandle,
+    jid: *const c_char,
+    message_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_send_raw", vec![c_str(jid), c_str(message_json)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_reject_call(
+    handle: ClientHandle,
+    from: *const c_char,
+    call_id: *const c_char,
+) -> WmResult {
+    call_reply(handle, "wm_reject_call", vec![c_str(from), c_str(call_id)]).code
+}
+
+pub unsafe fn wm_mark_read(
+    handle: ClientHandle,
+    chat: *const c_char,
+    sender: *const c_char,
+    ids_json: *const c_char,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_mark_read",
+        vec![c_str(chat), c_str(sender), c_str(ids_json)],
+    )
+    .code
+}
+
+pub unsafe fn wm_mark_played(
+    handle: ClientHandle,
+    chat: *const c_char,
+    sender: *const c_char,
+    ids_json: *const c_char,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_mark_played",
+        vec![c_str(chat), c_str(sender), c_str(ids_json)],
+    )
+    .code
+}
+
+pub unsafe fn wm_send_chat_presence(
+    handle: ClientHandle,
+    jid: *const c_char,
+    state: *const c_char,
+    media: *const c_char,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_send_chat_presence",
+        vec![c_str(jid), c_str(state), c_str(media)],
+    )
+    .code
+}
+
+pub unsafe fn wm_get_contact(
+    handle: ClientHandle,
+    jid: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_contact", vec![c_str(jid)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_resolve_lid(
+    handle: ClientHandle,
+    lid: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_resolve_lid", vec![c_str(lid)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_profile_picture(
+    handle: ClientHandle,
+    jid: *const c_char,
+    full: c_int,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_get_profile_picture",
+            vec![c_str(jid), full.to_string()],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_own_jid(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    write(call_reply(handle, "wm_get_own_jid", vec![]), buf, buf_len)
+}
+
+pub unsafe fn wm_get_push_name(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    write(call_reply(handle, "wm_get_push_name", vec![]), buf, buf_len)
+}
+
+pub unsafe fn wm_set_push_name(handle: ClientHandle, name: *const c_char) -> WmResult {
+    call_reply(handle, "wm_set_push_name", vec![c_str(name)]).code
+}
+
+pub unsafe fn wm_is_on_whatsapp(
+    handle: ClientHandle,
+    phones_json: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_is_on_whatsapp", vec![c_str(phones_json)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_subscribe_presence(handle: ClientHandle, jid: *const c_char) -> WmResult {
+    call_reply(handle, "wm_subscribe_presence", vec![c_str(jid)]).code
+}
+
+pub unsafe fn wm_query_messages(
+    handle: ClientHandle,
+    chat: *const c_char,
+    limit: c_int,
+    before_id: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(
+            handle,
+            "wm_query_messages",
+            vec![c_str(chat), limit.to_string(), c_str(before_id)],
+        ),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_star_message(
+    handle: ClientHandle,
+    chat: *const c_char,
+    message_id: *const c_char,
+    starred: c_int,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_star_message",
+        vec![c_str(chat), c_str(message_id), starred.to_string()],
+    )
+    .code
+}
+
+pub unsafe fn wm_get_starred(
+    handle: ClientHandle,
+    chat: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_starred", vec![c_str(chat)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_resync_app_state(handle: ClientHandle, name: *const c_char) -> WmResult {
+    call_reply(handle, "wm_resync_app_state", vec![c_str(name)]).code
+}
+
+pub unsafe fn wm_mark_chat_unread(handle: ClientHandle, jid: *const c_char) -> WmResult {
+    call_reply(handle, "wm_mark_chat_unread", vec![c_str(jid)]).code
+}
+
+pub unsafe fn wm_mute_chat(
+    handle: ClientHandle,
+    jid: *const c_char,
+    mute: c_int,
+    duration_secs: c_longlong,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_mute_chat",
+        vec![c_str(jid), mute.to_string(), duration_secs.to_string()],
+    )
+    .code
+}
+
+pub unsafe fn wm_clear_chat(handle: ClientHandle, jid: *const c_char) -> WmResult {
+    call_reply(handle, "wm_clear_chat", vec![c_str(jid)]).code
+}
+
+pub unsafe fn wm_export_session(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    write(
+        call_reply(handle, "wm_export_session", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_import_session(
+    handle: ClientHandle,
+    data: *const c_char,
+    data_len: c_int,
+) -> WmResult {
+    call_reply(handle, "wm_import_session", vec![c_bytes(data, data_len)]).code
+}
+
+pub unsafe fn wm_get_unread_counts(
+    handle: ClientHandle,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_unread_counts", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_store_stats(handle: ClientHandle, buf: *mut c_char, buf_len: c_int) -> c_int {
+    write(
+        call_reply(handle, "wm_get_store_stats", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_linked_devices(
+    handle: ClientHandle,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_linked_devices", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_remove_device(handle: ClientHandle, jid: *const c_char) -> WmResult {
+    call_reply(handle, "wm_remove_device", vec![c_str(jid)]).code
+}
+
+pub unsafe fn wm_get_joined_groups(
+    handle: ClientHandle,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_joined_groups", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_group_info_from_link(
+    handle: ClientHandle,
+    code: *const c_char,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_group_info_from_link", vec![c_str(code)]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_get_privacy_settings(
+    handle: ClientHandle,
+    buf: *mut c_char,
+    buf_len: c_int,
+) -> c_int {
+    write(
+        call_reply(handle, "wm_get_privacy_settings", vec![]),
+        buf,
+        buf_len,
+    )
+}
+
+pub unsafe fn wm_set_privacy_setting(
+    handle: ClientHandle,
+    key: *const c_char,
+    value: *const c_char,
+) -> WmResult {
+    call_reply(
+        handle,
+        "wm_set_privacy_setting",
+        vec![c_str(key), c_str(value)],
+    )
+    .code
+}
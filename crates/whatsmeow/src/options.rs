@@ -0,0 +1,397 @@
+//! Per-message send options
+
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::events::MediaSource;
+use crate::send_queue::Priority;
+
+/// Optional parameters applied to an outgoing message
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SendOptions {
+    /// Rich link preview attached to a text message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_preview: Option<LinkPreview>,
+    /// JPEG thumbnail for image messages, overriding the generated one
+    #[serde(skip)]
+    pub thumbnail: Option<Vec<u8>>,
+    /// Show "typing..." for this long before sending (async sends only)
+    #[serde(skip)]
+    pub simulate_typing: Option<Duration>,
+    /// Place among other waiting async sends
+    #[serde(skip)]
+    pub priority: Priority,
+    /// Timestamp reported for a text message instead of the server's
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "unix_seconds"
+    )]
+    pub timestamp: Option<SystemTime>,
+    /// Stanza ID for a text message instead of a generated one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
+/// How far ahead of the local clock [`SendOptions::timestamp`] may be
+pub const MAX_TIMESTAMP_LEAD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Length bounds of a [`SendOptions::message_id`], covering the IDs
+/// generated by WhatsApp Web (22 characters) and by phones (16 to 32)
+const MESSAGE_ID_LEN: std::ops::RangeInclusive<usize> = 16..=32;
+
+impl SendOptions {
+    /// Create empty send options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach a link preview
+    ///
+    /// Only text messages carry previews; sending anything else with one
+    /// fails with [`Error::Validation`].
+    pub fn link_preview(mut self, preview: LinkPreview) -> Self {
+        self.link_preview = Some(preview);
+        self
+    }
+
+    /// Use this JPEG as the image thumbnail instead of generating one
+    pub fn thumbnail(mut self, jpeg: Vec<u8>) -> Self {
+        self.thumbnail = Some(jpeg);
+        self
+    }
+
+    /// Show "typing..." for `duration`, clear it, then send
+    ///
+    /// Honoured by [`WhatsApp::send_async`](crate::WhatsApp::send_async);
+    /// blocking sends ignore it rather than stall the calling thread.
+    pub fn simulate_typing(mut self, duration: Duration) -> Self {
+        self.simulate_typing = Some(duration);
+        self
+    }
+
+    /// Jump ahead of (or fall behind) other queued async sends
+    ///
+    /// Use [`Priority::High`] for replies that can't wait behind a
+    /// [`send_batch`](crate::WhatsApp::send_batch) broadcast, or
+    /// [`Priority::Low`] for the broadcast itself. Blocking sends don't queue.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Report `at` as the send time of a text message
+    ///
+    /// For deterministic replays and scheduled sends: the returned
+    /// [`SentMessage`](crate::SentMessage) carries `at` instead of the
+    /// server's time. WhatsApp still stamps the message itself on receipt,
+    /// and that is what recipients see. Must not be more than
+    /// [`MAX_TIMESTAMP_LEAD`] in the future.
+    pub fn timestamp(mut self, at: SystemTime) -> Self {
+        self.timestamp = Some(at);
+        self
+    }
+
+    /// Send a text message under a caller-chosen ID
+    ///
+    /// For exactly-once delivery across retries: generate the ID once, store
+    /// it, and reuse it for every attempt. WhatsApp treats a resend with the
+    /// same ID as the same message, so a retry after a lost response can't
+    /// show up twice, and [`SentMessage::id`](crate::SentMessage::id) reports the
+    /// ID given here. It must be 16 to 32 uppercase hex digits, the shape
+    /// WhatsApp clients use (e.g. `3EB0` followed by 18 digits). Sending
+    /// anything other than text with an ID fails with
+    /// [`Error::Validation`].
+    pub fn message_id(mut self, id: impl Into<String>) -> Self {
+        self.message_id = Some(id.into());
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(id) = &self.message_id
+            && !(MESSAGE_ID_LEN.contains(&id.len())
+                && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'A'..=b'F')))
+        {
+            return Err(Error::Validation(format!(
+                "Message ID must be 16 to 32 uppercase hex digits: {:?}",
+                id
+            )));
+        }
+        if let Some(at) = self.timestamp
+            && at > SystemTime::now() + MAX_TIMESTAMP_LEAD
+        {
+            return Err(Error::Validation(
+                "Timestamp is too far in the future".into(),
+            ));
+        }
+        if let Some(preview) = &self.link_preview {
+            preview.validate()?;
+        }
+        if let Some(thumb) = &self.thumbnail
+            && MediaSource::detect_mime_from_signature(thumb) != "image/jpeg"
+        {
+            return Err(Error::Validation("Thumbnail must be a JPEG".into()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Link preview shown under a text message containing a URL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkPreview {
+    /// Canonical URL of the preview (should appear in the message text)
+    pub url: String,
+    /// Preview title
+    pub title: String,
+    /// Optional description line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Optional JPEG thumbnail bytes
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "base64_bytes"
+    )]
+    pub thumbnail: Option<Vec<u8>>,
+}
+
+impl LinkPreview {
+    /// Create a preview with a URL and title
+    pub fn new(url: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            title: title.into(),
+            description: None,
+            thumbnail: None,
+        }
+    }
+
+    /// Set the description line
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the thumbnail (must be a JPEG)
+    pub fn thumbnail(mut self, jpeg: Vec<u8>) -> Self {
+        self.thumbnail = Some(jpeg);
+        self
+    }
+
+    /// Check the preview is well-formed before sending
+    pub fn validate(&self) -> Result<()> {
+        if self.url.is_empty() {
+            return Err(Error::Validation("Link preview URL is empty".into()));
+        }
+        if let Some(thumb) = &self.thumbnail
+            && MediaSource::detect_mime_from_signature(thumb) != "image/jpeg"
+        {
+            return Err(Error::Validation(
+                "Link preview thumbnail must be a JPEG".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "remote-media")]
+impl LinkPreview {
+    /// Build a preview by fetching a page and reading its OpenGraph tags
+    ///
+    /// The `og:image` is only used as the thumbnail when it is a JPEG.
+    pub async fn fetch(url: impl Into<String>) -> Result<Self> {
+        let url = url.into();
+        let http = reqwest::Client::new();
+
+        let fetch_error = |e: reqwest::Error| Error::Fetch {
+            url: url.clone(),
+            reason: e.to_string(),
+        };
+        let html = http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(fetch_error)?
+            .text()
+            .await
+            .map_err(fetch_error)?;
+
+        let tags = opengraph::parse(&html);
+        let title = tags.get("og:title").cloned().unwrap_or_else(|| url.clone());
+
+        let mut preview = LinkPreview::new(tags.get("og:url").cloned().unwrap_or(url), title);
+        preview.description = tags.get("og:description").cloned();
+
+        if let Some(image_url) = tags.get("og:image")
+            && let Ok(resp) = http.get(image_url).send().await
+            && let Ok(bytes) = resp.bytes().await
+            && MediaSource::detect_mime_from_signature(&bytes) == "image/jpeg"
+        {
+            preview.thumbnail = Some(bytes.to_vec());
+        }
+
+        Ok(preview)
+    }
+}
+
+#[cfg(feature = "remote-media")]
+mod opengraph {
+    use std::collections::HashMap;
+
+    /// Extract `og:*` meta tags from an HTML document
+    pub fn parse(html: &str) -> HashMap<String, String> {
+        let mut tags = HashMap::new();
+        let mut rest = html;
+
+        while let Some(start) = rest.find("<meta") {
+            rest = &rest[start + 5..];
+            let Some(end) = rest.find('>') else { break };
+            let tag = &rest[..end];
+            rest = &rest[end..];
+
+            let key = attr(tag, "property").or_else(|| attr(tag, "name"));
+            if let (Some(key), Some(content)) = (key, attr(tag, "content"))
+                && key.starts_with("og:")
+            {
+                tags.entry(key).or_insert_with(|| unescape(&content));
+            }
+        }
+
+        tags
+    }
+
+    fn attr(tag: &str, name: &str) -> Option<String> {
+        let needle = format!("{}=", name);
+        // Skip matches inside longer names such as `data-property=`
+        let pos = tag
+            .match_indices(&needle)
+            .map(|(i, _)| i)
+            .find(|&i| tag[..i].chars().next_back().is_none_or(char::is_whitespace))?;
+        let value = &tag[pos + needle.len()..];
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let value = &value[1..];
+        value.find(quote).map(|end| value[..end].to_string())
+    }
+
+    fn unescape(s: &str) -> String {
+        s.replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&")
+    }
+}
+
+/// Serialize optional bytes as base64 (matches Go's `[]byte` JSON encoding)
+mod base64_bytes {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+        match data {
+            Some(bytes) => s.serialize_str(&STANDARD.encode(bytes)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Vec<u8>>, D::Error> {
+        let encoded: Option<String> = Option::deserialize(d)?;
+        encoded
+            .map(|e| STANDARD.decode(e).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Serialize an optional time as whole unix seconds
+mod unix_seconds {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, s: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(t) => {
+                let secs = t
+                    .duration_since(UNIX_EPOCH)
+                    .map_err(serde::ser::Error::custom)?;
+                s.serialize_u64(secs.as_secs())
+            }
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<SystemTime>, D::Error> {
+        let secs: Option<u64> = Option::deserialize(d)?;
+        Ok(secs.map(|s| UNIX_EPOCH + Duration::from_secs(s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::events::MessageType;
+    use crate::outgoing::Outgoing;
+
+    const JPEG: &[u8] = b"\xFF\xD8\xFF\xE0";
+
+    #[test]
+    fn link_preview_serializes_with_a_base64_thumbnail() {
+        let options = SendOptions::new().link_preview(
+            LinkPreview::new("https://www.rust-lang.org", "Rust")
+                .description("A language empowering everyone")
+                .thumbnail(JPEG.to_vec()),
+        );
+
+        let json: Value = serde_json::from_str(&options.to_json().unwrap()).unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "link_preview": {
+                    "url": "https://www.rust-lang.org",
+                    "title": "Rust",
+                    "description": "A language empowering everyone",
+                    "thumbnail": "/9j/4A==",
+                }
+            })
+        );
+        let back: SendOptions = serde_json::from_value(json).unwrap();
+        assert_eq!(back.link_preview, options.link_preview);
+    }
+
+    #[test]
+    fn link_previews_on_non_text_messages_are_rejected() {
+        let options = SendOptions::new().link_preview(LinkPreview::new("https://a.example", "A"));
+        let image = MessageType::image_auto(MediaSource::bytes(JPEG.to_vec()));
+
+        assert!(matches!(
+            Outgoing::prepare(&image, &options),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[cfg(feature = "remote-media")]
+    #[test]
+    fn opengraph_attributes_match_on_a_name_boundary() {
+        let tags = opengraph::parse(
+            r#"<meta data-property="og:title" property="og:title" content="Real">
+               <meta name="og:description" data-content="no" content="Tom &amp; Jerry">"#,
+        );
+
+        assert_eq!(tags["og:title"], "Real");
+        assert_eq!(tags["og:description"], "Tom & Jerry");
+    }
+}
@@ -0,0 +1,254 @@
+//! Preparing outgoing messages for the bridge
+
+use crate::error::{Error, Result};
+use crate::events::{MediaSource, MessageType};
+use crate::ffi::FfiClient;
+use crate::interactive::{ButtonsPayload, ListPayload, validate_buttons, validate_list};
+use crate::options::SendOptions;
+#[cfg(feature = "payments")]
+use crate::payments::{PaymentRequestPayload, validate_payment_request};
+use crate::sticker::{StickerInfo, validate_sticker};
+use crate::template::{TemplatePayload, validate_template};
+use crate::thumbnail;
+
+/// A message with all media loaded and validated, ready for one FFI call
+///
+/// Building this has no access to the FFI handle, so slow media loading and
+/// thumbnailing can never run while the handle is locked.
+pub(crate) enum Outgoing {
+    Text(String),
+    TextWithOptions {
+        text: String,
+        options_json: String,
+    },
+    Image {
+        data: Vec<u8>,
+        mime: String,
+        caption: Option<String>,
+        thumbnail: Option<Vec<u8>>,
+    },
+    /// Fetched by the bridge itself, see [`MediaSource::url`]
+    ImageUrl {
+        url: String,
+        mime: Option<String>,
+        caption: Option<String>,
+        thumbnail: Option<Vec<u8>>,
+    },
+    Sticker {
+        data: Vec<u8>,
+        info: StickerInfo,
+    },
+    Media {
+        kind: MediaKind,
+        data: Vec<u8>,
+        mime: String,
+        /// Only sent for documents
+        file_name: String,
+        caption: Option<String>,
+    },
+    Buttons(String),
+    List(String),
+    Template(String),
+    #[cfg(feature = "payments")]
+    PaymentRequest(String),
+    /// Caller-built `waE2E.Message` JSON, see [`WhatsApp::send_raw`](crate::WhatsApp::send_raw)
+    Raw(String),
+}
+
+/// Media sent through the bridge's generic media call
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MediaKind {
+    Video,
+    Audio,
+    Document,
+}
+
+impl MediaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Video => "video",
+            Self::Audio => "audio",
+            Self::Document => "document",
+        }
+    }
+}
+
+impl Outgoing {
+    /// Load, validate and encode a message
+    pub fn prepare(msg: &MessageType, options: &SendOptions) -> Result<Self> {
+        if options.message_id.is_some() && !matches!(msg, MessageType::Text(_)) {
+            return Err(Error::Validation(
+                "A custom message ID is only supported for text messages".into(),
+            ));
+        }
+        if options.link_preview.is_some() && !matches!(msg, MessageType::Text(_)) {
+            return Err(Error::Validation(
+                "Link previews are only supported for text messages".into(),
+            ));
+        }
+        Ok(match msg {
+            MessageType::Text(text)
+                if options.link_preview.is_some()
+                    || options.timestamp.is_some()
+                    || options.message_id.is_some() =>
+            {
+                Self::TextWithOptions {
+                    text: text.clone(),
+                    options_json: options.to_json()?,
+                }
+            }
+            MessageType::Text(text) => Self::Text(text.clone()),
+            MessageType::Image {
+                source: MediaSource::RemoteUrl { url },
+                mime_type,
+                caption,
+            } => {
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    return Err(Error::Validation(format!(
+                        "Image URL must be http(s): {}",
+                        url
+                    )));
+                }
+                Self::ImageUrl {
+                    url: url.clone(),
+                    mime: mime_type.clone(),
+                    caption: caption.clone(),
+                    thumbnail: options.thumbnail.clone(),
+                }
+            }
+            MessageType::Image { .. } => {
+                let (data, mime, caption) = msg.resolve_media()?;
+                let thumbnail = options
+                    .thumbnail
+                    .clone()
+                    .or_else(|| thumbnail::generate(&data));
+                Self::Image {
+                    data,
+                    mime,
+                    caption,
+                    thumbnail,
+                }
+            }
+            MessageType::Sticker { .. } => {
+                let (data, _, _) = msg.resolve_media()?;
+
+                // Reject stickers WhatsApp would silently drop server-side
+                let info = validate_sticker(&data)?;
+
+                Self::Sticker { data, info }
+            }
+            MessageType::Video { .. } | MessageType::Audio { .. } => {
+                let kind = match msg {
+                    MessageType::Video { .. } => MediaKind::Video,
+                    _ => MediaKind::Audio,
+                };
+                let (data, mime, caption) = msg.resolve_media()?;
+                Self::Media {
+                    kind,
+                    data,
+                    mime,
+                    file_name: String::new(),
+                    caption,
+                }
+            }
+            MessageType::Document { file_name, .. } => {
+                let (data, mime, caption) = msg.resolve_media()?;
+                Self::Media {
+                    kind: MediaKind::Document,
+                    data,
+                    mime,
+                    file_name: file_name.clone(),
+                    caption,
+                }
+            }
+            #[cfg(feature = "payments")]
+            MessageType::PaymentRequest {
+                amount_1000,
+                currency,
+                note,
+            } => {
+                validate_payment_request(*amount_1000, currency)?;
+                Self::PaymentRequest(serde_json::to_string(&PaymentRequestPayload {
+                    amount_1000: *amount_1000,
+                    currency,
+                    note: note.as_deref().unwrap_or_default(),
+                })?)
+            }
+            MessageType::Buttons { body, buttons } => {
+                validate_buttons(buttons)?;
+                Self::Buttons(serde_json::to_string(&ButtonsPayload { body, buttons })?)
+            }
+            MessageType::List {
+                body,
+                button_text,
+                sections,
+            } => {
+                validate_list(button_text, sections)?;
+                Self::List(serde_json::to_string(&ListPayload {
+                    body,
+                    button_text,
+                    sections,
+                })?)
+            }
+            MessageType::Album { .. } => {
+                return Err(Error::Validation(
+                    "Albums can only be sent with send_album".into(),
+                ));
+            }
+            MessageType::Template {
+                namespace,
+                name,
+                params,
+            } => {
+                validate_template(namespace, name, params)?;
+                Self::Template(serde_json::to_string(&TemplatePayload {
+                    namespace,
+                    name,
+                    params,
+                })?)
+            }
+        })
+    }
+
+    /// Make the one bridge call that sends this message
+    pub fn deliver(&self, ffi: &FfiClient, to: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Text(text) => ffi.send_message(to, text),
+            Self::TextWithOptions { text, options_json } => {
+                ffi.send_message_ex(to, text, options_json)
+            }
+            Self::Image {
+                data,
+                mime,
+                caption,
+                thumbnail,
+            } => ffi.send_image_ex(to, data, mime, caption.as_deref(), thumbnail.as_deref()),
+            Self::ImageUrl {
+                url,
+                mime,
+                caption,
+                thumbnail,
+            } => ffi.send_image_url(
+                to,
+                url,
+                mime.as_deref(),
+                caption.as_deref(),
+                thumbnail.as_deref(),
+            ),
+            Self::Sticker { data, info } => ffi.send_sticker(to, data, info),
+            Self::Media {
+                kind,
+                data,
+                mime,
+                file_name,
+                caption,
+            } => ffi.send_media(to, *kind, data, mime, file_name, caption.as_deref()),
+            Self::Buttons(payload) => ffi.send_buttons(to, payload),
+            Self::List(payload) => ffi.send_list(to, payload),
+            Self::Template(payload) => ffi.send_template(to, payload),
+            #[cfg(feature = "payments")]
+            Self::PaymentRequest(request) => ffi.send_payment_request(to, request),
+            Self::Raw(message) => ffi.send_raw(to, message),
+        }
+    }
+}
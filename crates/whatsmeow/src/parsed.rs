@@ -0,0 +1,242 @@
+//! Typed, cached view over a message body
+
+use std::cell::OnceCell;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::events::{Jid, QuotedMessage, context_info};
+
+/// Lazily parsed view of a message body
+///
+/// Each accessor walks the JSON at most once; later calls return the cached
+/// result. Obtain one with [`MessageEvent::parsed`](crate::MessageEvent::parsed).
+#[derive(Debug)]
+pub struct ParsedMessage<'a> {
+    raw: Option<&'a Value>,
+    image: OnceCell<Option<ImageContent>>,
+    video: OnceCell<Option<VideoContent>>,
+    location: OnceCell<Option<Location>>,
+    reaction: OnceCell<Option<Reaction>>,
+    quoted: OnceCell<Option<QuotedMessage>>,
+    mentions: OnceCell<Vec<Jid>>,
+}
+
+impl<'a> ParsedMessage<'a> {
+    pub(crate) fn new(raw: Option<&'a Value>) -> Self {
+        Self {
+            raw,
+            image: OnceCell::new(),
+            video: OnceCell::new(),
+            location: OnceCell::new(),
+            reaction: OnceCell::new(),
+            quoted: OnceCell::new(),
+            mentions: OnceCell::new(),
+        }
+    }
+
+    /// Raw message JSON
+    pub fn raw(&self) -> Option<&'a Value> {
+        self.raw
+    }
+
+    /// Plain or extended text body
+    pub fn text(&self) -> Option<&'a str> {
+        let msg = self.raw?;
+        msg.get("conversation")
+            .or_else(|| msg.get("extendedTextMessage")?.get("text"))
+            .and_then(|v| v.as_str())
+    }
+
+    /// Image attachment metadata
+    pub fn image(&self) -> Option<&ImageContent> {
+        self.image
+            .get_or_init(|| self.body("imageMessage"))
+            .as_ref()
+    }
+
+    /// Video attachment metadata
+    pub fn video(&self) -> Option<&VideoContent> {
+        self.video
+            .get_or_init(|| self.body("videoMessage"))
+            .as_ref()
+    }
+
+    /// Shared location
+    pub fn location(&self) -> Option<&Location> {
+        self.location
+            .get_or_init(|| self.body("locationMessage"))
+            .as_ref()
+    }
+
+    /// Reaction to another message
+    pub fn reaction(&self) -> Option<&Reaction> {
+        self.reaction
+            .get_or_init(|| self.body("reactionMessage"))
+            .as_ref()
+    }
+
+    /// Message this one replies to
+    pub fn quoted(&self) -> Option<&QuotedMessage> {
+        self.quoted
+            .get_or_init(|| QuotedMessage::from_message(self.raw?))
+            .as_ref()
+    }
+
+    /// JIDs mentioned with @ in the message
+    pub fn mentions(&self) -> &[Jid] {
+        self.mentions.get_or_init(|| {
+            self.raw
+                .and_then(context_info)
+                .and_then(|ctx| ctx.get("mentionedJID"))
+                .and_then(|v| v.as_array())
+                .map(|jids| {
+                    jids.iter()
+                        .filter_map(|j| j.as_str())
+                        .map(Jid::new)
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn body<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
+        T::deserialize(self.raw?.get(key)?).ok()
+    }
+}
+
+/// Image attachment metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageContent {
+    #[serde(rename = "mimetype", default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(rename = "fileLength", default)]
+    pub file_length: Option<u64>,
+}
+
+/// Video attachment metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoContent {
+    #[serde(rename = "mimetype", default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub caption: Option<String>,
+    /// Duration in seconds
+    #[serde(default)]
+    pub seconds: Option<u32>,
+    #[serde(rename = "gifPlayback", default)]
+    pub gif_playback: bool,
+    #[serde(rename = "fileLength", default)]
+    pub file_length: Option<u64>,
+}
+
+/// Shared location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(rename = "degreesLatitude")]
+    pub latitude: f64,
+    #[serde(rename = "degreesLongitude")]
+    pub longitude: f64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+/// Reaction to another message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reaction {
+    /// Key of the message reacted to
+    pub key: MessageKey,
+    /// Emoji, empty when the reaction was removed
+    #[serde(default)]
+    pub text: String,
+}
+
+impl Reaction {
+    /// Whether this removes a previous reaction
+    pub fn is_removal(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// Reference to another message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageKey {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+    #[serde(rename = "remoteJID", default)]
+    pub chat: Option<Jid>,
+    #[serde(rename = "fromMe", default)]
+    pub from_me: bool,
+    #[serde(default)]
+    pub participant: Option<Jid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn accessors_read_a_multi_field_payload_once() {
+        let raw = json!({
+            "imageMessage": {
+                "mimetype": "image/jpeg",
+                "caption": "look @3",
+                "width": 640,
+                "height": 480,
+                "fileLength": 12345,
+                "contextInfo": {
+                    "stanzaID": "Q1",
+                    "quotedMessage": { "conversation": "where?" },
+                    "mentionedJID": ["3@s.whatsapp.net"],
+                },
+            },
+            "reactionMessage": {
+                "key": { "ID": "M0", "remoteJID": "1@s.whatsapp.net", "fromMe": true },
+                "text": "",
+            },
+        });
+        let parsed = ParsedMessage::new(Some(&raw));
+
+        let image = parsed.image().unwrap();
+        assert_eq!(image.mime_type, "image/jpeg");
+        assert_eq!(image.caption.as_deref(), Some("look @3"));
+        assert_eq!((image.width, image.height), (Some(640), Some(480)));
+        assert_eq!(image.file_length, Some(12345));
+        assert!(std::ptr::eq(image, parsed.image().unwrap()));
+
+        assert_eq!(parsed.quoted().unwrap().text.as_deref(), Some("where?"));
+        assert!(std::ptr::eq(
+            parsed.quoted().unwrap(),
+            parsed.quoted().unwrap()
+        ));
+        assert_eq!(parsed.mentions(), [Jid::from("3@s.whatsapp.net")]);
+
+        let reaction = parsed.reaction().unwrap();
+        assert!(reaction.is_removal());
+        assert_eq!(reaction.key.id, "M0");
+        assert!(reaction.key.from_me);
+
+        assert!(parsed.text().is_none());
+        assert!(parsed.video().is_none());
+        assert!(parsed.location().is_none());
+    }
+
+    #[test]
+    fn an_empty_body_has_nothing() {
+        let parsed = ParsedMessage::new(None);
+
+        assert!(parsed.raw().is_none());
+        assert!(parsed.image().is_none());
+        assert!(parsed.mentions().is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! Payment requests (experimental)
+//!
+//! WhatsApp payments are only available in some regions and for some
+//! accounts. Elsewhere the server refuses payment messages, which surfaces
+//! as [`Error::Ffi`] from the send.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::events::MessageInfo;
+
+/// Payment request as sent to the bridge
+#[derive(Debug, Serialize)]
+pub(crate) struct PaymentRequestPayload<'a> {
+    pub amount_1000: u64,
+    pub currency: &'a str,
+    pub note: &'a str,
+}
+
+/// Reject payment requests the server is certain to refuse
+pub(crate) fn validate_payment_request(amount_1000: u64, currency: &str) -> Result<()> {
+    if amount_1000 == 0 {
+        return Err(Error::Validation("Payment amount must be positive".into()));
+    }
+    if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(Error::Validation(format!(
+            "Currency must be an ISO 4217 code like \"USD\", got {:?}",
+            currency
+        )));
+    }
+    Ok(())
+}
+
+/// What a payment message does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentKind {
+    /// Asks the recipient to pay
+    Request,
+    /// Money was sent
+    Send,
+    /// The requester withdrew a request
+    CancelRequest,
+    /// The recipient declined a request
+    DeclineRequest,
+}
+
+impl PaymentKind {
+    /// Message fields carrying each kind, in `waE2E.Message` JSON
+    const FIELDS: [(&'static str, Self); 4] = [
+        ("requestPaymentMessage", Self::Request),
+        ("sendPaymentMessage", Self::Send),
+        ("cancelPaymentRequestMessage", Self::CancelRequest),
+        ("declinePaymentRequestMessage", Self::DeclineRequest),
+    ];
+}
+
+/// A payment message was received
+#[derive(Debug, Clone)]
+pub struct PaymentEvent {
+    pub info: MessageInfo,
+    pub kind: PaymentKind,
+    /// Requested amount in thousandths of the currency unit (requests only)
+    pub amount_1000: Option<u64>,
+    /// ISO 4217 currency code (requests only)
+    pub currency: Option<String>,
+    /// Note attached by the requester
+    pub note: Option<String>,
+    /// The payment message as sent
+    pub raw: Value,
+}
+
+impl PaymentEvent {
+    /// Recognise a payment message, if `message` is one
+    pub(crate) fn from_message(info: &MessageInfo, message: &Value) -> Option<Self> {
+        let (raw, kind) = PaymentKind::FIELDS
+            .iter()
+            .find_map(|(field, kind)| Some((message.get(*field)?, *kind)))?;
+
+        let amount_1000 = raw.get("amount1000").and_then(|a| {
+            // proto JSON writes 64-bit integers as strings
+            a.as_u64().or_else(|| a.as_str()?.parse().ok())
+        });
+        let currency = raw
+            .get("currencyCodeIso4217")
+            .and_then(|c| c.as_str())
+            .map(str::to_owned);
+        let note = raw
+            .pointer("/noteMessage/extendedTextMessage/text")
+            .or_else(|| raw.pointer("/noteMessage/conversation"))
+            .and_then(|t| t.as_str())
+            .map(str::to_owned);
+
+        Some(Self {
+            info: info.clone(),
+            kind,
+            amount_1000,
+            currency,
+            note,
+            raw: raw.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::events::{Event, MessageType, RawEvent};
+    use crate::mock_ffi;
+
+    #[tokio::test]
+    async fn the_request_payload_reaches_the_bridge() {
+        let (client, mock) = mock_ffi::client("payment-request").await;
+        let request = MessageType::PaymentRequest {
+            amount_1000: 12_500,
+            currency: "USD".into(),
+            note: Some("Lunch".into()),
+        };
+
+        client.send("15550001111@s.whatsapp.net", request).unwrap();
+        client
+            .send(
+                "15550001111@s.whatsapp.net",
+                MessageType::payment_request(1_000, "EUR"),
+            )
+            .unwrap();
+
+        let payloads: Vec<Value> = mock
+            .calls("wm_send_payment_request")
+            .iter()
+            .map(|args| serde_json::from_str(&args[1]).unwrap())
+            .collect();
+        assert_eq!(
+            payloads,
+            [
+                json!({ "amount_1000": 12_500, "currency": "USD", "note": "Lunch" }),
+                json!({ "amount_1000": 1_000, "currency": "EUR", "note": "" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn implausible_requests_are_rejected() {
+        assert!(validate_payment_request(0, "USD").is_err());
+        assert!(validate_payment_request(1_000, "usd").is_err());
+        assert!(validate_payment_request(1_000, "DOLLARS").is_err());
+        assert!(validate_payment_request(1_000, "BRL").is_ok());
+    }
+
+    #[test]
+    fn received_requests_are_classified() {
+        let content = json!({
+            "requestPaymentMessage": {
+                "amount1000": "12500",
+                "currencyCodeIso4217": "USD",
+                "noteMessage": { "extendedTextMessage": { "text": "Lunch" } },
+            }
+        });
+        let raw = RawEvent {
+            event_type: "message".into(),
+            timestamp: 0,
+            data: Some(mock_ffi::message(
+                "M1",
+                "15550001111@s.whatsapp.net",
+                "15550001111@s.whatsapp.net",
+                content,
+            )),
+        };
+
+        let Ok(Event::Payment(payment)) = raw.into_event() else {
+            panic!("expected a payment event");
+        };
+        assert_eq!(payment.kind, PaymentKind::Request);
+        assert_eq!(payment.amount_1000, Some(12_500));
+        assert_eq!(payment.currency.as_deref(), Some("USD"));
+        assert_eq!(payment.note.as_deref(), Some("Lunch"));
+    }
+}
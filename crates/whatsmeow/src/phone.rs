@@ -0,0 +1,197 @@
+//! Validated phone numbers
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhoneError;
+use crate::events::Jid;
+
+/// Fewest digits (country code included) accepted as a phone number
+const MIN_DIGITS: usize = 7;
+/// E.164 caps numbers at 15 digits, country code included
+const MAX_DIGITS: usize = 15;
+
+/// A phone number in E.164 form: country code and subscriber number, digits only
+///
+/// Parsing tolerates the usual formatting (`+`, `00`, spaces, dashes, dots
+/// and parentheses) and rejects anything that can't be a phone number, so
+/// mistakes surface before a message goes to a non-existent JID.
+///
+/// ```
+/// use whatsmeow::{Jid, PhoneNumber};
+///
+/// let phone = PhoneNumber::parse("+1 (202) 555-0123").unwrap();
+/// assert_eq!(phone.as_str(), "12025550123");
+/// assert_eq!(Jid::from(phone), Jid::user("12025550123"));
+/// assert!(PhoneNumber::parse("not a number").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    /// Parse an international number (with or without a leading `+`)
+    pub fn parse(s: &str) -> Result<Self, PhoneError> {
+        let (_, digits) = normalize(s)?;
+        Self::from_digits(digits)
+    }
+
+    /// Parse a number, completing national numbers with `country_code`
+    ///
+    /// Numbers written with `+` or `00` are taken as international. Others
+    /// that don't already start with `country_code` have a leading trunk `0`
+    /// dropped and the country code prepended, so `"01012345678"` with
+    /// `"20"` becomes `"201012345678"`.
+    pub fn parse_with_country(s: &str, country_code: &str) -> Result<Self, PhoneError> {
+        let (international, digits) = normalize(s)?;
+        let country_code = country_code.trim_start_matches('+');
+        if international || digits.starts_with(country_code) {
+            return Self::from_digits(digits);
+        }
+        Self::from_digits(format!(
+            "{}{}",
+            country_code,
+            digits.trim_start_matches('0')
+        ))
+    }
+
+    fn from_digits(digits: String) -> Result<Self, PhoneError> {
+        match digits.len() {
+            n if n < MIN_DIGITS => Err(PhoneError::TooShort(digits)),
+            n if n > MAX_DIGITS => Err(PhoneError::TooLong(digits)),
+            _ => Ok(Self(digits)),
+        }
+    }
+
+    /// The digits, without a leading `+`
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The user JID for this number
+    pub fn to_jid(&self) -> Jid {
+        Jid::user(&self.0)
+    }
+}
+
+/// Strip formatting; returns whether the number was written internationally
+fn normalize(s: &str) -> Result<(bool, String), PhoneError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(PhoneError::Empty);
+    }
+
+    let (international, rest) = match s.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => match s.strip_prefix("00") {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        },
+    };
+
+    let mut digits = String::with_capacity(rest.len());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            other => return Err(PhoneError::InvalidCharacter(other)),
+        }
+    }
+    Ok((international, digits))
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{}", self.0)
+    }
+}
+
+impl FromStr for PhoneNumber {
+    type Err = PhoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for PhoneNumber {
+    type Error = PhoneError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<PhoneNumber> for String {
+    fn from(phone: PhoneNumber) -> Self {
+        phone.0
+    }
+}
+
+impl AsRef<str> for PhoneNumber {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<PhoneNumber> for Jid {
+    fn from(phone: PhoneNumber) -> Self {
+        phone.to_jid()
+    }
+}
+
+impl From<&PhoneNumber> for Jid {
+    fn from(phone: &PhoneNumber) -> Self {
+        phone.to_jid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatted_and_plus_prefixed_numbers_parse_to_digits() {
+        for input in ["12025550123", "+1 (202) 555-0123", "001.202.555.0123"] {
+            assert_eq!(PhoneNumber::parse(input).unwrap().as_str(), "12025550123");
+        }
+        let phone: PhoneNumber = "+201012345678".parse().unwrap();
+        assert_eq!(phone.to_string(), "+201012345678");
+        assert_eq!(phone.to_jid(), Jid::user("201012345678"));
+    }
+
+    #[test]
+    fn invalid_numbers_are_rejected() {
+        assert_eq!(PhoneNumber::parse("  "), Err(PhoneError::Empty));
+        assert_eq!(
+            PhoneNumber::parse("+1 202 555 O123"),
+            Err(PhoneError::InvalidCharacter('O'))
+        );
+        assert_eq!(
+            PhoneNumber::parse("+12345"),
+            Err(PhoneError::TooShort("12345".into()))
+        );
+        assert_eq!(
+            PhoneNumber::parse("1234567890123456"),
+            Err(PhoneError::TooLong("1234567890123456".into()))
+        );
+    }
+
+    #[test]
+    fn national_numbers_get_the_country_code() {
+        assert_eq!(
+            PhoneNumber::parse_with_country("01012345678", "+20")
+                .unwrap()
+                .as_str(),
+            "201012345678"
+        );
+        assert_eq!(
+            PhoneNumber::parse_with_country("+1 202 555 0123", "20")
+                .unwrap()
+                .as_str(),
+            "12025550123"
+        );
+    }
+}
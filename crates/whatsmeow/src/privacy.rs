@@ -0,0 +1,173 @@
+//! Account privacy settings
+
+use serde::{Deserialize, Serialize};
+
+/// A privacy setting that can be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PrivacyKey {
+    /// Who can add you to groups
+    #[serde(rename = "groupadd")]
+    GroupAdd,
+    /// Who can see your last seen time
+    #[serde(rename = "last")]
+    LastSeen,
+    /// Who can see your status updates
+    #[serde(rename = "status")]
+    Status,
+    /// Who can see your profile photo
+    #[serde(rename = "profile")]
+    Profile,
+    /// Whether read receipts are sent
+    #[serde(rename = "readreceipts")]
+    ReadReceipts,
+    /// Who can see when you're online
+    #[serde(rename = "online")]
+    Online,
+    /// Who can call you
+    #[serde(rename = "calladd")]
+    CallAdd,
+}
+
+impl PrivacyKey {
+    /// Wire name used by the bridge
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GroupAdd => "groupadd",
+            Self::LastSeen => "last",
+            Self::Status => "status",
+            Self::Profile => "profile",
+            Self::ReadReceipts => "readreceipts",
+            Self::Online => "online",
+            Self::CallAdd => "calladd",
+        }
+    }
+}
+
+/// Audience of a privacy setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyValue {
+    /// Everyone
+    All,
+    /// Contacts only
+    Contacts,
+    /// Contacts except an exclusion list
+    ContactBlacklist,
+    /// Same audience as last seen (online only)
+    MatchLastSeen,
+    /// Known contacts (call add only)
+    Known,
+    /// Nobody
+    None,
+}
+
+impl PrivacyValue {
+    /// Wire name used by the bridge
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Contacts => "contacts",
+            Self::ContactBlacklist => "contact_blacklist",
+            Self::MatchLastSeen => "match_last_seen",
+            Self::Known => "known",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Current privacy settings (`None` when the server did not report a value)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacySettings {
+    pub group_add: Option<PrivacyValue>,
+    pub last_seen: Option<PrivacyValue>,
+    pub status: Option<PrivacyValue>,
+    pub profile: Option<PrivacyValue>,
+    pub read_receipts: Option<PrivacyValue>,
+    pub online: Option<PrivacyValue>,
+    pub call_add: Option<PrivacyValue>,
+}
+
+impl PrivacySettings {
+    /// Look up a setting by key
+    pub fn get(&self, key: PrivacyKey) -> Option<PrivacyValue> {
+        match key {
+            PrivacyKey::GroupAdd => self.group_add,
+            PrivacyKey::LastSeen => self.last_seen,
+            PrivacyKey::Status => self.status,
+            PrivacyKey::Profile => self.profile,
+            PrivacyKey::ReadReceipts => self.read_receipts,
+            PrivacyKey::Online => self.online,
+            PrivacyKey::CallAdd => self.call_add,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    #[test]
+    fn wire_names_match_serde() {
+        let keys = [
+            (PrivacyKey::GroupAdd, "groupadd"),
+            (PrivacyKey::LastSeen, "last"),
+            (PrivacyKey::Status, "status"),
+            (PrivacyKey::Profile, "profile"),
+            (PrivacyKey::ReadReceipts, "readreceipts"),
+            (PrivacyKey::Online, "online"),
+            (PrivacyKey::CallAdd, "calladd"),
+        ];
+        for (key, name) in keys {
+            assert_eq!(key.as_str(), name);
+            assert_eq!(serde_json::to_value(key).unwrap(), json!(name));
+        }
+
+        let values = [
+            (PrivacyValue::All, "all"),
+            (PrivacyValue::Contacts, "contacts"),
+            (PrivacyValue::ContactBlacklist, "contact_blacklist"),
+            (PrivacyValue::MatchLastSeen, "match_last_seen"),
+            (PrivacyValue::Known, "known"),
+            (PrivacyValue::None, "none"),
+        ];
+        for (value, name) in values {
+            assert_eq!(value.as_str(), name);
+            assert_eq!(
+                serde_json::from_value::<PrivacyValue>(json!(name)).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn settings_are_parsed_and_changes_reach_the_bridge() {
+        let (client, mock) = mock_ffi::client("privacy").await;
+        mock.reply(
+            "wm_get_privacy_settings",
+            Reply::ok(br#"{"last_seen":"contacts","online":"match_last_seen"}"#.to_vec()),
+        );
+
+        let settings = client.privacy_settings().unwrap();
+        client
+            .set_privacy(PrivacyKey::ReadReceipts, PrivacyValue::None)
+            .unwrap();
+
+        assert_eq!(
+            settings.get(PrivacyKey::LastSeen),
+            Some(PrivacyValue::Contacts)
+        );
+        assert_eq!(
+            settings.get(PrivacyKey::Online),
+            Some(PrivacyValue::MatchLastSeen)
+        );
+        assert_eq!(settings.get(PrivacyKey::Status), None);
+        assert_eq!(
+            mock.calls("wm_set_privacy_setting"),
+            [vec!["readreceipts".to_string(), "none".to_string()]]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Profile pictures and display names
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Longest display name WhatsApp accepts, in characters
+pub const MAX_PUSH_NAME_LEN: usize = 25;
+
+/// Check a display name before it is sent to WhatsApp
+pub(crate) fn validate_push_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(Error::Validation("Push name must not be empty".into()));
+    }
+    if name.chars().count() > MAX_PUSH_NAME_LEN {
+        return Err(Error::Validation(format!(
+            "Push name must be at most {} characters",
+            MAX_PUSH_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Resolution to fetch a profile picture at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PictureQuality {
+    /// Small thumbnail, quick to download
+    #[default]
+    Preview,
+    /// Full resolution image
+    Full,
+}
+
+/// Location of a profile picture on WhatsApp's CDN
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfilePicture {
+    /// Download URL (expires after a while)
+    pub url: String,
+    /// Picture ID; changes whenever the picture does
+    pub id: String,
+    /// `"preview"` or `"image"`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    #[tokio::test]
+    async fn the_quality_flag_reaches_the_bridge() {
+        let (client, mock) = mock_ffi::client("profile-picture-quality").await;
+        mock.reply(
+            "wm_get_profile_picture",
+            Reply::ok(
+                br#"{"url":"https://pps.whatsapp.net/p.jpg","id":"1700","type":"image"}"#.to_vec(),
+            ),
+        );
+
+        let full = client
+            .profile_picture("15550001111@s.whatsapp.net", PictureQuality::Full)
+            .unwrap();
+        let hidden = client
+            .profile_picture("15550001111@s.whatsapp.net", PictureQuality::Preview)
+            .unwrap();
+
+        assert_eq!(full.unwrap().kind, "image");
+        assert_eq!(hidden, None);
+        assert_eq!(
+            mock.calls("wm_get_profile_picture"),
+            [
+                vec!["15550001111@s.whatsapp.net".to_string(), "1".into()],
+                vec!["15550001111@s.whatsapp.net".to_string(), "0".into()],
+            ]
+        );
+    }
+
+    #[test]
+    fn push_names_are_limited_in_characters() {
+        assert!(validate_push_name("Support Bot").is_ok());
+        assert!(validate_push_name(&"é".repeat(MAX_PUSH_NAME_LEN)).is_ok());
+        assert!(validate_push_name(&"a".repeat(MAX_PUSH_NAME_LEN + 1)).is_err());
+        assert!(validate_push_name("   ").is_err());
+    }
+
+    #[tokio::test]
+    async fn push_names_pass_through_the_bridge() {
+        let (client, mock) = mock_ffi::client("push-name").await;
+        mock.reply("wm_get_push_name", Reply::ok(b"Support Bot".to_vec()));
+
+        assert_eq!(client.push_name().unwrap().as_deref(), Some("Support Bot"));
+        assert_eq!(client.push_name().unwrap(), None);
+        client.set_push_name("Helpdesk").unwrap();
+        assert!(matches!(
+            client.set_push_name(&"a".repeat(MAX_PUSH_NAME_LEN + 1)),
+            Err(Error::Validation(_))
+        ));
+
+        assert_eq!(mock.calls("wm_set_push_name"), [vec!["Helpdesk"]]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Rendering of pairing QR codes for terminals and browsers
+
+use std::fmt::Write;
+use std::io::Cursor;
+
+use qrcode::render::unicode;
+use qrcode::{Color, QrCode};
+
+use crate::error::{Error, Result};
+use crate::events::QrEvent;
+
+/// Print a pairing QR code to stdout and return the rendered text
+///
+/// Uses half-block characters with light modules drawn as filled blocks, so
+/// the code scans on the usual dark terminal background. The returned string
+/// can also be logged or written elsewhere.
+///
+/// ```no_run
+/// # fn example(event: &whatsmeow::QrEvent) -> whatsmeow::Result<()> {
+/// if let Some(code) = event.code() {
+///     whatsmeow::render_qr_to_terminal(code)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn render_qr_to_terminal(code: &str) -> Result<String> {
+    let qr = QrCode::new(code.as_bytes())
+        .map_err(|e| Error::Validation(format!("Cannot encode QR code: {}", e)))?;
+
+    let rendered = qr
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build();
+
+    println!("{}", rendered);
+    Ok(rendered)
+}
+
+/// Blank modules around the code, as the QR spec requires
+const QUIET_ZONE: u32 = 4;
+
+impl QrEvent {
+    /// Render the current code as a PNG, for showing pairing in a browser
+    ///
+    /// `size` is the target width in pixels; it is rounded down to a whole
+    /// number of pixels per module, with at least one. Fails if the event
+    /// carries no code.
+    pub fn to_png(&self, size: u32) -> Result<Vec<u8>> {
+        let modules = Modules::new(self)?;
+        let scale = (size / modules.total()).max(1);
+        let pixels = modules.total() * scale;
+
+        let img = image::GrayImage::from_fn(pixels, pixels, |x, y| {
+            let dark = modules.is_dark(x / scale, y / scale);
+            image::Luma([if dark { 0 } else { 255 }])
+        });
+
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| Error::Validation(format!("Cannot encode QR PNG: {}", e)))?;
+        Ok(png)
+    }
+
+    /// Render the current code as a scalable SVG document
+    ///
+    /// Fails if the event carries no code.
+    pub fn to_svg(&self) -> Result<String> {
+        let modules = Modules::new(self)?;
+        let total = modules.total();
+
+        let mut path = String::new();
+        for y in 0..total {
+            for x in 0..total {
+                if modules.is_dark(x, y) {
+                    let _ = write!(path, "M{x},{y}h1v1h-1z");
+                }
+            }
+        }
+
+        Ok(format!(
+            concat!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {total} {total}" shape-rendering="crispEdges">"##,
+                r##"<rect width="100%" height="100%" fill="#fff"/><path fill="#000" d="{path}"/></svg>"##
+            ),
+            total = total,
+            path = path
+        ))
+    }
+}
+
+/// Module grid of an encoded code, addressed with the quiet zone included
+struct Modules {
+    colors: Vec<Color>,
+    width: u32,
+}
+
+impl Modules {
+    fn new(event: &QrEvent) -> Result<Self> {
+        let code = event
+            .code()
+            .ok_or_else(|| Error::Validation("QR event has no code".into()))?;
+        let qr = QrCode::new(code.as_bytes())
+            .map_err(|e| Error::Validation(format!("Cannot encode QR code: {}", e)))?;
+
+        Ok(Self {
+            width: qr.width() as u32,
+            colors: qr.to_colors(),
+        })
+    }
+
+    fn total(&self) -> u32 {
+        self.width + 2 * QUIET_ZONE
+    }
+
+    fn is_dark(&self, x: u32, y: u32) -> bool {
+        let (Some(x), Some(y)) = (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE)) else {
+            return false;
+        };
+        x < self.width
+            && y < self.width
+            && self.colors[(y * self.width + x) as usize] == Color::Dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "2@AbCdEf,GhIjKl,MnOpQr,StUvWx";
+
+    #[test]
+    fn a_known_code_renders_as_block_characters() {
+        let rendered = render_qr_to_terminal(CODE).unwrap();
+
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines.len() > 10);
+        assert!(
+            lines
+                .iter()
+                .all(|line| line.chars().count() == lines[0].chars().count())
+        );
+        assert!(rendered.contains('█') || rendered.contains('▀') || rendered.contains('▄'));
+    }
+
+    #[test]
+    fn a_known_code_renders_as_a_png() {
+        let event = QrEvent {
+            codes: vec![CODE.into()],
+        };
+
+        let png = event.to_png(300).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let img = image::load_from_memory(&png).unwrap().to_luma8();
+        let modules = Modules::new(&event).unwrap().total();
+        let scale = img.width() / modules;
+        assert_eq!(img.width(), img.height());
+        assert_eq!(img.width(), modules * scale);
+        assert!(img.width() <= 300);
+        // White quiet zone, then the dark corner of the finder pattern
+        assert_eq!(img.get_pixel(0, 0).0, [255]);
+        let corner = QUIET_ZONE * scale;
+        assert_eq!(img.get_pixel(corner, corner).0, [0]);
+    }
+
+    #[test]
+    fn an_event_without_codes_has_no_png() {
+        let event = QrEvent { codes: Vec::new() };
+
+        assert!(event.to_png(300).is_err());
+    }
+}
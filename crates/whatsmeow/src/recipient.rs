@@ -0,0 +1,96 @@
+//! Typed message recipients
+
+use crate::events::Jid;
+
+/// Who a message is for, named by kind rather than by JID suffix
+///
+/// Anything sending accepts a `Recipient`, which picks the right [`Jid`]
+/// constructor so a group id can't end up on the user server by mistake.
+///
+/// ```
+/// use whatsmeow::{Jid, Recipient};
+///
+/// assert_eq!(Jid::from(Recipient::User("+1234567890".into())).as_str(), "1234567890@s.whatsapp.net");
+/// assert_eq!(Jid::from(Recipient::Group("120363".into())).as_str(), "120363@g.us");
+/// assert_eq!(Jid::from(Recipient::Broadcast("1700000000".into())).as_str(), "1700000000@broadcast");
+/// assert_eq!(Jid::from(Recipient::Raw(Jid::new("status@broadcast"))).as_str(), "status@broadcast");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Recipient {
+    /// A phone number, with or without a leading `+`
+    User(String),
+    /// A group id, without `@g.us`
+    Group(String),
+    /// A broadcast list id, without `@broadcast`
+    Broadcast(String),
+    /// A JID used as-is
+    Raw(Jid),
+}
+
+impl From<Recipient> for Jid {
+    fn from(recipient: Recipient) -> Self {
+        match recipient {
+            Recipient::User(phone) => Jid::user(phone),
+            Recipient::Group(id) => Jid::group(id),
+            Recipient::Broadcast(id) => Jid::broadcast(id),
+            Recipient::Raw(jid) => jid,
+        }
+    }
+}
+
+impl From<Jid> for Recipient {
+    fn from(jid: Jid) -> Self {
+        Recipient::Raw(jid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_ffi;
+
+    #[test]
+    fn each_variant_lands_on_its_server() {
+        let cases = [
+            (
+                Recipient::User("+201012345678".into()),
+                "201012345678@s.whatsapp.net",
+            ),
+            (
+                Recipient::User("201012345678".into()),
+                "201012345678@s.whatsapp.net",
+            ),
+            (
+                Recipient::Group("120363041234567890".into()),
+                "120363041234567890@g.us",
+            ),
+            (
+                Recipient::Broadcast("1700000000".into()),
+                "1700000000@broadcast",
+            ),
+            (
+                Recipient::Raw(Jid::new("status@broadcast")),
+                "status@broadcast",
+            ),
+            (Jid::new("1@lid").into(), "1@lid"),
+        ];
+
+        for (recipient, expected) in cases {
+            assert_eq!(Jid::from(recipient).as_str(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_go_to_the_recipient_jid() {
+        let (client, mock) = mock_ffi::client("recipient-send").await;
+
+        client
+            .send(Recipient::Group("120363041234567890".into()), "hi all")
+            .unwrap();
+
+        assert_eq!(
+            mock.calls("wm_send_message"),
+            [["120363041234567890@g.us", "hi all"]]
+        );
+    }
+}
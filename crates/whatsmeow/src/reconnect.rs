@@ -0,0 +1,78 @@
+//! Reconnection backoff
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Exponential backoff between reconnect attempts, with random jitter so
+/// many clients dropped at once don't retry in lockstep
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Backoff {
+    /// Delay before the first attempt
+    pub base: Duration,
+    /// Upper bound on the un-jittered delay
+    pub max: Duration,
+    /// Each delay is scaled by a random factor in `1 ± jitter`
+    pub jitter: f64,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(2),
+            max: Duration::from_secs(60),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before reconnect attempt `attempt` (starting at 0)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let nominal = self
+            .base
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(self.max);
+        let factor = 1.0 + self.jitter * (2.0 * random_unit() - 1.0);
+        nominal.mul_f64(factor.max(0.0))
+    }
+}
+
+/// Uniform random value in `[0, 1)`
+///
+/// Every `RandomState` is freshly keyed, which is plenty for spreading out
+/// retries without pulling in an RNG crate.
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn jittered_delays_stay_in_range_and_vary() {
+        let backoff = Backoff::default();
+        let delays: Vec<Duration> = (0..50).map(|_| backoff.delay(0)).collect();
+        for delay in &delays {
+            assert!((Duration::from_millis(1600)..=Duration::from_millis(2400)).contains(delay));
+        }
+        assert!(delays.iter().collect::<HashSet<_>>().len() > 1);
+
+        let capped = backoff.delay(10);
+        assert!((Duration::from_secs(48)..=Duration::from_secs(72)).contains(&capped));
+    }
+
+    #[test]
+    fn zero_jitter_gives_plain_exponential_delays() {
+        let backoff = Backoff {
+            jitter: 0.0,
+            ..Backoff::default()
+        };
+        let delays: Vec<u64> = (0..7).map(|a| backoff.delay(a).as_secs()).collect();
+        assert_eq!(delays, [2, 4, 8, 16, 32, 60, 60]);
+    }
+}
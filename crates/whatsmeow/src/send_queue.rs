@@ -0,0 +1,201 @@
+//! Priority ordering for async sends waiting on the bridge
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// Where an async send is placed among others waiting to go out
+///
+/// Only matters when sends pile up, e.g. a
+/// [`send_batch`](crate::WhatsApp::send_batch) broadcast with an urgent
+/// reply sent meanwhile. Waiting sends of equal priority go out in order.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Send slots handed to the highest-priority waiters
+///
+/// As many sends may be in flight as there are slots (the client's batch
+/// concurrency); a send arriving when all are taken waits by priority.
+pub(crate) struct SendQueue {
+    state: Mutex<QueueState>,
+}
+
+struct QueueState {
+    slots: usize,
+    in_use: usize,
+    next_seq: u64,
+    waiting: BinaryHeap<Waiter>,
+}
+
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: higher priority first, then the earlier arrival
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl SendQueue {
+    pub fn new(slots: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                slots,
+                in_use: 0,
+                next_seq: 0,
+                waiting: BinaryHeap::new(),
+            }),
+        }
+    }
+
+    /// Change the number of slots (only before any send is queued)
+    pub fn set_slots(&self, slots: usize) {
+        self.state.lock().slots = slots.max(1);
+    }
+
+    /// Wait for a send slot; it is released when the permit drops
+    pub async fn acquire(&self, priority: Priority) -> SendPermit<'_> {
+        let rx = {
+            let mut state = self.state.lock();
+            if state.in_use < state.slots {
+                state.in_use += 1;
+                return SendPermit { queue: self };
+            }
+            let (wake, rx) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                wake,
+            });
+            rx
+        };
+
+        let mut pending = Pending { queue: self, rx };
+        // The sender lives in the heap until it hands over the slot
+        let _ = (&mut pending.rx).await;
+        SendPermit { queue: self }
+    }
+
+    /// Pass a slot to the next live waiter, or mark it free
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.in_use -= 1;
+    }
+}
+
+/// A waiter whose future may be dropped before or after the handover
+struct Pending<'a> {
+    queue: &'a SendQueue,
+    rx: oneshot::Receiver<()>,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        // Handed the slot but cancelled before using it: pass it on
+        if self.rx.try_recv().is_ok() {
+            self.queue.release();
+        }
+    }
+}
+
+/// A send slot, held until the bridge call returns
+pub(crate) struct SendPermit<'a> {
+    queue: &'a SendQueue,
+}
+
+impl Drop for SendPermit<'_> {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn high_priority_send_queued_after_low_ones_goes_first() {
+        let queue = Arc::new(SendQueue::new(1));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let held = queue.acquire(Priority::Normal).await;
+
+        let mut tasks = Vec::new();
+        for (name, priority) in [
+            ("low-1", Priority::Low),
+            ("low-2", Priority::Low),
+            ("high", Priority::High),
+        ] {
+            let (waiter, order) = (queue.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let _slot = waiter.acquire(priority).await;
+                order.lock().push(name);
+            }));
+            // Let each waiter join the queue before the next one
+            while queue.state.lock().waiting.len() < tasks.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(held);
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*order.lock(), ["high", "low-1", "low-2"]);
+    }
+
+    #[tokio::test]
+    async fn sends_up_to_the_slot_count_run_at_once() {
+        let queue = SendQueue::new(3);
+        let held: Vec<_> =
+            futures::future::join_all((0..3).map(|_| queue.acquire(Priority::Low))).await;
+
+        let waiting = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            queue.acquire(Priority::High),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        drop(held);
+        assert_eq!(queue.state.lock().in_use, 0);
+    }
+}
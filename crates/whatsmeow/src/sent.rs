@@ -0,0 +1,95 @@
+//! Results of sending a message
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::events::{Jid, MessageId};
+use crate::timestamp::parse_rfc3339;
+
+/// A message accepted by the server
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SentMessage {
+    /// Message ID (use it to reply, react, edit or revoke), as given in
+    /// [`SendOptions::message_id`](crate::SendOptions::message_id) when set
+    pub id: MessageId,
+    /// Server-assigned send time, or [`SendOptions::timestamp`](crate::SendOptions::timestamp) when set
+    pub timestamp: SystemTime,
+    /// Recipient chat
+    pub to: Jid,
+}
+
+/// Send result as written by the bridge
+#[derive(Deserialize)]
+struct SendResult {
+    id: MessageId,
+    timestamp: String,
+}
+
+impl SentMessage {
+    /// Parse the bridge's send result JSON
+    pub(crate) fn from_ffi(to: Jid, data: &[u8]) -> Result<Self> {
+        let result: SendResult = serde_json::from_slice(data)?;
+        Ok(Self {
+            id: result.id,
+            // Fall back to the local clock if the server time is missing
+            timestamp: parse_rfc3339(&result.timestamp).unwrap_or_else(SystemTime::now),
+            to,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+    use crate::mock_ffi::{self, Reply};
+
+    #[test]
+    fn timestamp_is_parsed_from_the_bridge_result() {
+        let sent = SentMessage::from_ffi(
+            Jid::from("15550001111@s.whatsapp.net"),
+            br#"{"id":"3EB0ABCDEF","timestamp":"2024-01-01T00:00:00.5Z"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(sent.id, MessageId::from("3EB0ABCDEF"));
+        assert_eq!(
+            sent.timestamp,
+            UNIX_EPOCH + Duration::new(1_704_067_200, 500_000_000)
+        );
+        assert_eq!(sent.to, Jid::from("15550001111@s.whatsapp.net"));
+    }
+
+    #[test]
+    fn missing_server_time_falls_back_to_now() {
+        let before = SystemTime::now();
+        let sent = SentMessage::from_ffi(
+            Jid::from("15550001111@s.whatsapp.net"),
+            br#"{"id":"3EB0ABCDEF","timestamp":""}"#,
+        )
+        .unwrap();
+
+        assert!(sent.timestamp >= before);
+    }
+
+    #[tokio::test]
+    async fn send_returns_the_parsed_result() {
+        let (client, mock) = mock_ffi::client("sent-message").await;
+        mock.reply(
+            "wm_send_message",
+            Reply::ok(br#"{"id":"3EB0ABCDEF","timestamp":"2024-01-01T00:00:00Z"}"#.to_vec()),
+        );
+
+        let sent = client.send("15550001111@s.whatsapp.net", "hi").unwrap();
+
+        assert_eq!(sent.id, MessageId::from("3EB0ABCDEF"));
+        assert_eq!(
+            sent.timestamp,
+            UNIX_EPOCH + Duration::from_secs(1_704_067_200)
+        );
+        assert_eq!(sent.to, Jid::from("15550001111@s.whatsapp.net"));
+    }
+}
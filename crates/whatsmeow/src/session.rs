@@ -0,0 +1,87 @@
+//! Session credential backups
+
+use std::fmt;
+
+use zeroize::Zeroize;
+
+/// Serialized device credentials from [`WhatsApp::export_session`](crate::WhatsApp::export_session)
+///
+/// Anyone holding these bytes can act as this linked device, so store them
+/// encrypted. The buffer is wiped when dropped, whether or not the `zeroize`
+/// feature is enabled, and never printed by `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionBlob(Vec<u8>);
+
+impl SessionBlob {
+    /// Wrap bytes previously obtained from [`as_bytes`](Self::as_bytes)
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Raw credential bytes, for writing to a backup
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Overwrite the credentials with zeros, keeping the allocation
+    fn wipe(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SessionBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SessionBlob({} bytes, redacted)", self.0.len())
+    }
+}
+
+impl Drop for SessionBlob {
+    fn drop(&mut self) {
+        self.wipe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WhatsApp;
+    use crate::mock_ffi::{self, Mock, Reply};
+
+    #[tokio::test]
+    async fn exported_session_restores_into_a_new_client() {
+        let (source, mock) = mock_ffi::client("session-export").await;
+        let creds = b"\x00\x01device-keys\xff".to_vec();
+        mock.reply("wm_export_session", Reply::ok(creds.clone()));
+
+        let blob = source.export_session().unwrap();
+        assert_eq!(blob.as_bytes(), creds);
+        assert_eq!(format!("{blob:?}"), "SessionBlob(14 bytes, redacted)");
+
+        let restored = WhatsApp::connect(mock_ffi::db_path("session-import"))
+            .restore_session(blob)
+            .build()
+            .await
+            .unwrap();
+        let mock = Mock::of(&restored);
+        let hex: String = creds.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(mock.calls("wm_import_session"), [[hex]]);
+        // Credentials must be in place before the first connect
+        assert_eq!(
+            mock.call_names(),
+            ["wm_import_session", "wm_client_connect"]
+        );
+    }
+
+    #[test]
+    fn wiping_zeroes_the_credential_bytes() {
+        let mut blob = SessionBlob::from_bytes(b"device-keys".to_vec());
+        let (ptr, len) = (blob.0.as_ptr(), blob.0.len());
+
+        blob.wipe();
+
+        // The allocation outlives the wipe, so its old contents can be read
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+        assert!(bytes.iter().all(|&b| b == 0));
+        assert!(blob.as_bytes().is_empty());
+    }
+}
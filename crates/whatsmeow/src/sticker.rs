@@ -0,0 +1,207 @@
+//! WebP sticker inspection and validation
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Maximum sticker width/height in pixels
+pub const MAX_STICKER_DIMENSION: u32 = 512;
+
+/// Maximum file size for a static sticker
+pub const MAX_STATIC_STICKER_BYTES: usize = 100 * 1024;
+
+/// Maximum file size for an animated sticker
+pub const MAX_ANIMATED_STICKER_BYTES: usize = 500 * 1024;
+
+/// Dimensions and animation flag read from a WebP header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StickerInfo {
+    pub width: u32,
+    pub height: u32,
+    pub animated: bool,
+}
+
+impl StickerInfo {
+    /// Parse the RIFF/WebP container header
+    pub fn from_webp(data: &[u8]) -> Result<Self> {
+        if data.len() < 16 || &data[0..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+            return Err(Error::Validation("Sticker must be a WebP image".into()));
+        }
+
+        let mut info = None;
+        let mut animated = false;
+        let mut offset = 12;
+
+        // Walk the chunk list: FourCC, little-endian size, payload (padded to even)
+        while offset + 8 <= data.len() {
+            let fourcc = &data[offset..offset + 4];
+            let size =
+                u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            let payload = &data[offset + 8..data.len().min(offset + 8 + size)];
+
+            match fourcc {
+                b"VP8X" if payload.len() >= 10 => {
+                    animated |= payload[0] & 0x02 != 0;
+                    info.get_or_insert((u24_le(&payload[4..7]) + 1, u24_le(&payload[7..10]) + 1));
+                }
+                b"VP8 " if payload.len() >= 10 => {
+                    let w = u16::from_le_bytes([payload[6], payload[7]]) & 0x3fff;
+                    let h = u16::from_le_bytes([payload[8], payload[9]]) & 0x3fff;
+                    info.get_or_insert((w as u32, h as u32));
+                }
+                b"VP8L" if payload.len() >= 5 => {
+                    let bits = u32::from_le_bytes(payload[1..5].try_into().unwrap());
+                    info.get_or_insert(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1));
+                }
+                b"ANIM" | b"ANMF" => animated = true,
+                _ => {}
+            }
+
+            offset += 8 + size + (size & 1);
+        }
+
+        let (width, height) =
+            info.ok_or_else(|| Error::Validation("WebP header has no image chunk".into()))?;
+
+        Ok(Self {
+            width,
+            height,
+            animated,
+        })
+    }
+
+    /// Check WhatsApp's dimension and size limits for this sticker
+    pub fn validate(&self, len: usize) -> Result<()> {
+        if self.width > MAX_STICKER_DIMENSION || self.height > MAX_STICKER_DIMENSION {
+            return Err(Error::Validation(format!(
+                "Sticker is {}x{}, maximum is {}x{}",
+                self.width, self.height, MAX_STICKER_DIMENSION, MAX_STICKER_DIMENSION
+            )));
+        }
+
+        let limit = if self.animated {
+            MAX_ANIMATED_STICKER_BYTES
+        } else {
+            MAX_STATIC_STICKER_BYTES
+        };
+        if len > limit {
+            return Err(Error::Validation(format!(
+                "{} sticker is {} bytes, maximum is {} bytes",
+                if self.animated { "Animated" } else { "Static" },
+                len,
+                limit
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Inspect and validate sticker bytes in one step
+pub(crate) fn validate_sticker(data: &[u8]) -> Result<StickerInfo> {
+    let info = StickerInfo::from_webp(data)?;
+    info.validate(data.len())?;
+    Ok(info)
+}
+
+fn u24_le(b: &[u8]) -> u32 {
+    b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RIFF/WebP container holding `chunks`
+    fn webp(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut body = b"WEBP".to_vec();
+        for (fourcc, payload) in chunks {
+            body.extend_from_slice(*fourcc);
+            body.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            body.extend_from_slice(payload);
+            if payload.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend(body);
+        data
+    }
+
+    fn lossless(width: u32, height: u32) -> (&'static [u8; 4], Vec<u8>) {
+        let bits = (width - 1) | (height - 1) << 14;
+        let mut payload = vec![0x2f];
+        payload.extend_from_slice(&bits.to_le_bytes());
+        (b"VP8L", payload)
+    }
+
+    fn lossy(width: u16, height: u16) -> (&'static [u8; 4], Vec<u8>) {
+        let mut payload = vec![0; 6];
+        payload.extend_from_slice(&width.to_le_bytes());
+        payload.extend_from_slice(&height.to_le_bytes());
+        (b"VP8 ", payload)
+    }
+
+    fn animated(width: u32, height: u32) -> Vec<u8> {
+        let mut header = vec![0x02, 0, 0, 0];
+        header.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+        header.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+        webp(&[
+            (b"VP8X", header),
+            (b"ANIM", vec![0; 6]),
+            (b"ANMF", vec![0; 16]),
+        ])
+    }
+
+    #[test]
+    fn static_headers_give_dimensions() {
+        for data in [webp(&[lossless(512, 256)]), webp(&[lossy(512, 256)])] {
+            assert_eq!(
+                StickerInfo::from_webp(&data).unwrap(),
+                StickerInfo {
+                    width: 512,
+                    height: 256,
+                    animated: false,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn extended_headers_with_frames_are_animated() {
+        assert_eq!(
+            StickerInfo::from_webp(&animated(320, 320)).unwrap(),
+            StickerInfo {
+                width: 320,
+                height: 320,
+                animated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn limits_depend_on_animation() {
+        let size = 200 * 1024;
+        assert!(validate_sticker(&webp(&[lossless(512, 512)])).is_ok());
+        assert!(
+            StickerInfo::from_webp(&webp(&[lossless(512, 512)]))
+                .unwrap()
+                .validate(size)
+                .is_err()
+        );
+        assert!(
+            StickerInfo::from_webp(&animated(512, 512))
+                .unwrap()
+                .validate(size)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn oversized_and_non_webp_stickers_are_rejected() {
+        assert!(validate_sticker(&webp(&[lossless(513, 512)])).is_err());
+        assert!(validate_sticker(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_err());
+        assert!(validate_sticker(&webp(&[])).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+//! Session database size figures
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// How much the session database holds, for capacity planning
+///
+/// Counts come from the bridge; the size is read from disk. History sync can
+/// grow the database quickly on busy accounts, so this is worth graphing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreStats {
+    /// Messages in the local history store
+    pub messages: u64,
+    /// Chats with at least one stored message
+    pub chats: u64,
+    /// Contacts known to the session
+    pub contacts: u64,
+    /// Bytes on disk, including the `-wal` file in WAL mode
+    #[serde(default)]
+    pub db_size: u64,
+}
+
+/// Size of a sqlite database with its write-ahead log, if any
+pub(crate) fn db_size(path: &Path) -> std::io::Result<u64> {
+    let main = std::fs::metadata(path)?.len();
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    let wal = std::fs::metadata(wal).map(|m| m.len()).unwrap_or(0);
+    Ok(main + wal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WhatsApp;
+    use crate::mock_ffi::{self, Mock, Reply};
+
+    #[tokio::test]
+    async fn counts_come_from_the_bridge_and_size_from_disk() {
+        let path = mock_ffi::db_path("store-stats");
+        let client = WhatsApp::connect(&path).build().await.unwrap();
+        let mock = Mock::of(&client);
+        mock.reply(
+            "wm_get_store_stats",
+            Reply::ok(br#"{"messages":1200,"chats":35,"contacts":410}"#.to_vec()),
+        );
+        std::fs::write(&path, vec![0u8; 4096]).unwrap();
+        let mut wal = path.clone().into_os_string();
+        wal.push("-wal");
+        std::fs::write(&wal, vec![0u8; 512]).unwrap();
+
+        let stats = client.store_stats().unwrap();
+
+        assert_eq!(
+            stats,
+            StoreStats {
+                messages: 1200,
+                chats: 35,
+                contacts: 410,
+                db_size: 4608,
+            }
+        );
+        std::fs::remove_file(&wal).unwrap();
+        assert_eq!(db_size(&path).unwrap(), 4096);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn an_empty_reply_counts_nothing() {
+        let path = mock_ffi::db_path("store-stats-empty");
+        let client = WhatsApp::connect(&path).build().await.unwrap();
+        std::fs::write(&path, b"").unwrap();
+
+        assert_eq!(client.store_stats().unwrap(), StoreStats::default());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
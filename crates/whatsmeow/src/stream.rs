@@ -2,20 +2,73 @@
 
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
-use futures::Stream;
+use futures::stream::FusedStream;
+use futures::{Stream, StreamExt};
+use serde_json::Value;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::ReusableBoxFuture;
 
 use crate::events::Event;
 
 /// Async stream of WhatsApp events
+///
+/// A stream follows the client, not a single connection: it keeps yielding
+/// events across [`Event::Disconnected`] and the automatic reconnect that
+/// follows, so long-running consumers never need to re-subscribe.
+///
+/// It ends (yields `None`) only once the client is gone for good: the last
+/// [`WhatsApp`](crate::WhatsApp) clone is dropped and no
+/// [`WhatsAppManager`](crate::WhatsAppManager) still holds it. Neither a
+/// lost connection nor [`WhatsApp::disconnect`](crate::WhatsApp::disconnect)
+/// ends it; after a disconnect it simply stays quiet. Once ended it stays
+/// ended, which [`is_closed`](Self::is_closed) reports.
+///
+/// Events are yielded in the order the client received them. Whether
+/// callback handlers see an event before or after streams is set with
+/// [`WhatsAppBuilder::event_order`](crate::WhatsAppBuilder::event_order).
+#[derive(Clone)]
 pub struct EventStream {
-    rx: broadcast::Receiver<Event>,
+    inner: BroadcastStream<Event>,
 }
 
 impl EventStream {
-    pub(crate) fn new(rx: broadcast::Receiver<Event>) -> Self {
-        Self { rx }
+    pub(crate) fn new(tx: &broadcast::Sender<Event>) -> Self {
+        Self {
+            inner: BroadcastStream::new(tx),
+        }
+    }
+
+    /// Whether the stream has ended because the client was dropped
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Group events into batches, one per `window`
+    ///
+    /// A window opens with the first event after a quiet spell and its batch
+    /// is yielded when it closes, so a burst of messages becomes one update
+    /// instead of many. Nothing is yielded while no events arrive. When the
+    /// underlying stream ends, the partial batch is yielded before `None`.
+    pub fn chunked(self, window: Duration) -> impl Stream<Item = Vec<Event>> {
+        futures::stream::unfold(Some(self), move |stream| async move {
+            let mut stream = stream?;
+            let mut batch = vec![stream.next().await?];
+
+            let deadline = tokio::time::sleep(window);
+            tokio::pin!(deadline);
+            loop {
+                tokio::select! {
+                    _ = &mut deadline => return Some((batch, Some(stream))),
+                    event = stream.next() => match event {
+                        Some(event) => batch.push(event),
+                        None => return Some((batch, None)),
+                    },
+                }
+            }
+        })
     }
 }
 
@@ -23,25 +76,258 @@ impl Stream for EventStream {
     type Item = Event;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.rx.try_recv() {
-            Ok(event) => Poll::Ready(Some(event)),
-            Err(broadcast::error::TryRecvError::Empty) => {
-                cx.waker().wake_by_ref();
-                Poll::Pending
+        self.inner.poll_recv(cx)
+    }
+}
+
+impl FusedStream for EventStream {
+    fn is_terminated(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+/// Items a [`BroadcastStream`] can report a lag with
+trait Lagged {
+    fn lagged(missed: u64) -> Self;
+}
+
+impl Lagged for Event {
+    fn lagged(missed: u64) -> Self {
+        Event::StreamLagged { missed }
+    }
+}
+
+impl Lagged for EventEnvelope {
+    fn lagged(missed: u64) -> Self {
+        EventEnvelope {
+            event: Event::StreamLagged { missed },
+            raw: Value::Null,
+        }
+    }
+}
+
+type Recv<T> = (Result<T, RecvError>, broadcast::Receiver<T>);
+
+/// A broadcast receiver driven as a stream
+///
+/// Parks on the channel until something is sent instead of polling it,
+/// reports an overflow as a single [`Lagged`] item, and stays ended once
+/// every sender is gone.
+struct BroadcastStream<T> {
+    /// `recv()` on the receiver, which it hands back with the result;
+    /// `None` once the channel has closed
+    recv: Option<ReusableBoxFuture<'static, Recv<T>>>,
+    /// For clones to subscribe without keeping the channel open
+    sender: broadcast::WeakSender<T>,
+}
+
+async fn recv<T: Clone>(mut rx: broadcast::Receiver<T>) -> Recv<T> {
+    let result = rx.recv().await;
+    (result, rx)
+}
+
+impl<T: Lagged + Clone + Send + 'static> BroadcastStream<T> {
+    fn new(tx: &broadcast::Sender<T>) -> Self {
+        Self {
+            recv: Some(ReusableBoxFuture::new(recv(tx.subscribe()))),
+            sender: tx.downgrade(),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.recv.is_none()
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let Some(pending) = &mut self.recv else {
+            return Poll::Ready(None);
+        };
+        let (result, rx) = std::task::ready!(pending.poll(cx));
+        let item = match result {
+            Ok(item) => item,
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!(missed, "Event stream lagged, events dropped");
+                T::lagged(missed)
             }
-            Err(broadcast::error::TryRecvError::Lagged(_)) => {
-                cx.waker().wake_by_ref();
-                Poll::Pending
+            Err(RecvError::Closed) => {
+                self.recv = None;
+                return Poll::Ready(None);
             }
-            Err(broadcast::error::TryRecvError::Closed) => Poll::Ready(None),
-        }
+        };
+        pending.set(recv(rx));
+        Poll::Ready(Some(item))
     }
 }
 
-impl Clone for EventStream {
+impl<T: Lagged + Clone + Send + 'static> Clone for BroadcastStream<T> {
+    /// A stream of events sent from now on, or an ended one if this has ended
     fn clone(&self) -> Self {
+        match self.sender.upgrade() {
+            Some(tx) if !self.is_closed() => Self::new(&tx),
+            _ => Self {
+                recv: None,
+                sender: self.sender.clone(),
+            },
+        }
+    }
+}
+
+/// An event together with the JSON the bridge sent for it
+///
+/// Produced by [`WhatsApp::events_with_raw`](crate::WhatsApp::events_with_raw)
+/// for debugging field mapping without saving events to disk.
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    pub event: Event,
+    /// The bridge's `{"type", "timestamp", "data"}` object (`Null` only
+    /// for [`Event::StreamLagged`])
+    pub raw: Value,
+}
+
+/// Async stream of [`EventEnvelope`]s
+///
+/// Behaves like [`EventStream`], but is only fed when the client was built
+/// with [`retain_raw_json`](crate::WhatsAppBuilder::retain_raw_json), and
+/// skips events without bridge JSON of their own, such as
+/// [`Event::HistoryChunk`].
+#[derive(Clone)]
+pub struct EnvelopeStream {
+    inner: BroadcastStream<EventEnvelope>,
+}
+
+impl EnvelopeStream {
+    pub(crate) fn new(tx: &broadcast::Sender<EventEnvelope>) -> Self {
         Self {
-            rx: self.rx.resubscribe(),
+            inner: BroadcastStream::new(tx),
+        }
+    }
+
+    /// Whether the stream has ended because the client was dropped
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+impl Stream for EnvelopeStream {
+    type Item = EventEnvelope;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_recv(cx)
+    }
+}
+
+impl FusedStream for EnvelopeStream {
+    fn is_terminated(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn overflow_reports_the_missed_count() {
+        let (tx, _) = broadcast::channel(4);
+        let mut stream = EventStream::new(&tx);
+
+        for _ in 0..10 {
+            tx.send(Event::Connected).unwrap();
+        }
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Event::StreamLagged { missed: 6 })
+        ));
+        for _ in 0..4 {
+            assert!(matches!(stream.next().await, Some(Event::Connected)));
         }
     }
+
+    #[tokio::test]
+    async fn envelope_overflow_reports_the_missed_count_without_json() {
+        let (tx, _) = broadcast::channel(2);
+        let mut stream = EnvelopeStream::new(&tx);
+
+        for _ in 0..3 {
+            let envelope = EventEnvelope {
+                event: Event::Connected,
+                raw: serde_json::json!({"type": "connected"}),
+            };
+            tx.send(envelope).unwrap();
+        }
+
+        let lagged = stream.next().await.unwrap();
+        assert!(matches!(lagged.event, Event::StreamLagged { missed: 1 }));
+        assert!(lagged.raw.is_null());
+        assert_eq!(stream.next().await.unwrap().raw["type"], "connected");
+
+        drop(tx);
+        stream.next().await.unwrap();
+        assert!(stream.next().await.is_none());
+        assert!(stream.is_terminated());
+        assert!(stream.clone().next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn an_idle_stream_waits_for_the_next_send() {
+        let (tx, _) = broadcast::channel(4);
+        let mut stream = EventStream::new(&tx);
+
+        let sender = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            sender.send(Event::Connected).unwrap();
+        });
+        let mut polls = 0;
+        let event = std::future::poll_fn(|cx| {
+            polls += 1;
+            stream.poll_next_unpin(cx)
+        })
+        .await;
+
+        assert!(matches!(event, Some(Event::Connected)));
+        // Once to park and once when the send wakes it
+        assert_eq!(polls, 2);
+    }
+
+    #[tokio::test]
+    async fn chunked_batches_a_burst_and_flushes_after_the_window() {
+        let (tx, _) = broadcast::channel(16);
+        let mut batches = Box::pin(EventStream::new(&tx).chunked(Duration::from_millis(50)));
+
+        for _ in 0..3 {
+            tx.send(Event::Connected).unwrap();
+        }
+        let started = std::time::Instant::now();
+        assert_eq!(batches.next().await.unwrap().len(), 3);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        // Nothing is yielded during a quiet spell
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), batches.next())
+                .await
+                .is_err()
+        );
+
+        tx.send(Event::Disconnected).unwrap();
+        let batch = batches.next().await.unwrap();
+        assert!(matches!(batch[..], [Event::Disconnected]));
+    }
+
+    #[tokio::test]
+    async fn chunked_yields_the_partial_batch_when_the_stream_ends() {
+        let (tx, _) = broadcast::channel(16);
+        let mut batches = Box::pin(EventStream::new(&tx).chunked(Duration::from_secs(60)));
+
+        tx.send(Event::Connected).unwrap();
+        tx.send(Event::Connected).unwrap();
+        drop(tx);
+
+        let batch = tokio::time::timeout(Duration::from_secs(1), batches.next())
+            .await
+            .unwrap();
+        assert_eq!(batch.map(|b| b.len()), Some(2));
+        assert!(batches.next().await.is_none());
+    }
 }
@@ -0,0 +1,112 @@
+//! System notices shown inline in a chat
+
+use serde_json::Value;
+
+use crate::events::{Jid, MessageInfo};
+
+/// What a system notice reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemKind {
+    /// The other party's security code changed (new phone or reinstall)
+    SecurityCodeChanged,
+    /// Disappearing messages were turned on, off or changed
+    DisappearingMessages,
+    /// A category this crate doesn't classify yet
+    Other(String),
+}
+
+impl SystemKind {
+    /// Classify a message category, `None` for ordinary messages
+    pub fn from_category(category: &str) -> Option<Self> {
+        match category {
+            // "peer" marks messages between this account's own devices
+            "" | "peer" => None,
+            "e2e_notification" | "identity" | "security" => Some(Self::SecurityCodeChanged),
+            "ephemeral" | "disappearing_mode" => Some(Self::DisappearingMessages),
+            other => Some(Self::Other(other.to_string())),
+        }
+    }
+}
+
+/// A system notice, such as "security code changed"
+#[derive(Debug, Clone)]
+pub struct SystemEvent {
+    pub chat: Jid,
+    pub kind: SystemKind,
+    /// Who triggered the notice, when WhatsApp says
+    pub actor: Option<Jid>,
+}
+
+impl SystemEvent {
+    /// Classify a message as a system notice by its category
+    ///
+    /// Messages with content are never notices, whatever their category.
+    pub(crate) fn from_message(info: &MessageInfo, message: Option<&Value>) -> Option<Self> {
+        let kind = SystemKind::from_category(&info.category)?;
+        if message.is_some_and(has_content) {
+            return None;
+        }
+
+        Some(Self {
+            chat: Jid::from(info.chat.as_str()),
+            kind,
+            actor: (!info.sender.is_empty()).then(|| Jid::from(info.sender.as_str())),
+        })
+    }
+}
+
+/// Whether a message carries anything besides context metadata
+fn has_content(message: &Value) -> bool {
+    message
+        .as_object()
+        .is_some_and(|fields| fields.keys().any(|k| k != "messageContextInfo"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::events::{Event, RawEvent};
+    use crate::mock_ffi;
+
+    fn parse(category: &str, content: Value) -> Event {
+        let mut data = mock_ffi::message("S1", "1@s.whatsapp.net", "2@s.whatsapp.net", content);
+        data["Info"]["Category"] = json!(category);
+        RawEvent {
+            event_type: "message".into(),
+            timestamp: 0,
+            data: Some(data),
+        }
+        .into_event()
+        .unwrap()
+    }
+
+    #[test]
+    fn a_security_notification_is_a_system_event() {
+        let event = parse("e2e_notification", Value::Null);
+
+        let Event::System(system) = event else {
+            panic!("expected a system notice, got {event:?}");
+        };
+        assert_eq!(system.kind, SystemKind::SecurityCodeChanged);
+        assert_eq!(system.chat, Jid::from("1@s.whatsapp.net"));
+        assert_eq!(system.actor, Some(Jid::from("2@s.whatsapp.net")));
+    }
+
+    #[test]
+    fn a_message_with_content_stays_a_message_whatever_its_category() {
+        let event = parse("security", json!({ "conversation": "hi" }));
+
+        assert!(matches!(event, Event::Message(_)));
+    }
+
+    #[test]
+    fn peer_messages_are_not_notices() {
+        assert_eq!(SystemKind::from_category("peer"), None);
+        assert_eq!(
+            SystemKind::from_category("ephemeral"),
+            Some(SystemKind::DisappearingMessages)
+        );
+    }
+}
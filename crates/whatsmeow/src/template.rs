@@ -0,0 +1,109 @@
+//! Business template messages
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// Template message as sent to the bridge
+#[derive(Serialize)]
+pub(crate) struct TemplatePayload<'a> {
+    pub namespace: &'a str,
+    pub name: &'a str,
+    pub params: &'a [String],
+}
+
+/// Number of distinct `{{n}}` placeholders in an approved template body
+pub(crate) fn placeholder_count(body: &str) -> usize {
+    let mut indices = std::collections::BTreeSet::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        if let Ok(n) = rest[..end].trim().parse::<u32>() {
+            indices.insert(n);
+        }
+        rest = &rest[end + 2..];
+    }
+    indices.len()
+}
+
+/// Reject templates the server is certain to refuse
+pub(crate) fn validate_template(namespace: &str, name: &str, params: &[String]) -> Result<()> {
+    if namespace.trim().is_empty() {
+        return Err(Error::Validation("Template namespace is empty".into()));
+    }
+    if name.trim().is_empty() {
+        return Err(Error::Validation("Template name is empty".into()));
+    }
+    if let Some(i) = params.iter().position(|p| p.is_empty()) {
+        return Err(Error::Validation(format!(
+            "Template parameter {} is empty",
+            i + 1
+        )));
+    }
+    Ok(())
+}
+
+/// Check `params` fill every placeholder of a known template body
+pub(crate) fn check_params(body: &str, params: &[String]) -> Result<()> {
+    let expected = placeholder_count(body);
+    if params.len() != expected {
+        return Err(Error::Validation(format!(
+            "Template expects {} parameters, got {}",
+            expected,
+            params.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::MessageType;
+    use crate::mock_ffi;
+
+    #[test]
+    fn placeholders_are_counted_once_each() {
+        assert_eq!(placeholder_count("Hi {{1}}, order {{2}} ships {{ 3 }}"), 3);
+        assert_eq!(placeholder_count("{{1}} and {{1}} again"), 1);
+        assert_eq!(placeholder_count("No placeholders, {{name}} or {{1"), 0);
+    }
+
+    #[test]
+    fn params_must_fill_a_known_body() {
+        let body = "Hi {{1}}, order {{2}} is ready";
+
+        assert!(MessageType::template_for_body("ns", "ready", body, vec!["Ann".into()]).is_err());
+        assert!(
+            MessageType::template_for_body("ns", "ready", body, vec!["Ann".into(), "42".into()])
+                .is_ok()
+        );
+        assert!(validate_template("ns", "ready", &["".into()]).is_err());
+        assert!(validate_template(" ", "ready", &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn the_template_payload_reaches_the_bridge() {
+        let (client, mock) = mock_ffi::client("template-payload").await;
+
+        client
+            .send(
+                "15550001111@s.whatsapp.net",
+                MessageType::template("acme_ns", "order_ready", vec!["Ann".into(), "42".into()]),
+            )
+            .unwrap();
+
+        let calls = mock.calls("wm_send_template");
+        assert_eq!(calls[0][0], "15550001111@s.whatsapp.net");
+        let payload: Value = serde_json::from_str(&calls[0][1]).unwrap();
+        assert_eq!(
+            payload,
+            json!({ "namespace": "acme_ns", "name": "order_ready", "params": ["Ann", "42"] })
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Thumbnails shown while media downloads
+
+/// Longest edge of generated thumbnails, in pixels
+#[cfg(feature = "image")]
+const THUMBNAIL_SIZE: u32 = 72;
+
+/// JPEG quality of generated thumbnails
+#[cfg(feature = "image")]
+const THUMBNAIL_QUALITY: u8 = 60;
+
+/// Downscale an image to a small JPEG thumbnail
+///
+/// Returns `None` for formats the `image` crate can't decode, or always
+/// when the `image` feature is disabled.
+#[cfg(feature = "image")]
+pub(crate) fn generate(data: &[u8]) -> Option<Vec<u8>> {
+    use image::codecs::jpeg::JpegEncoder;
+
+    let img = match image::load_from_memory(data) {
+        Ok(img) => img,
+        Err(e) => {
+            tracing::debug!(error = %e, "Skipping thumbnail for undecodable image");
+            return None;
+        }
+    };
+
+    // JPEG has no alpha channel
+    let thumb = img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, THUMBNAIL_QUALITY)
+        .encode_image(&thumb)
+        .ok()?;
+    Some(out)
+}
+
+#[cfg(not(feature = "image"))]
+pub(crate) fn generate(_data: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undecodable_data_has_no_thumbnail() {
+        assert_eq!(generate(b"not an image"), None);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn large_png_becomes_a_small_jpeg() {
+        use std::io::Cursor;
+
+        use image::{ImageFormat, Rgba, RgbaImage};
+
+        let img = RgbaImage::from_fn(1600, 1200, |x, y| {
+            Rgba([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 200])
+        });
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let thumb = generate(&png).unwrap();
+
+        assert!(thumb.starts_with(&[0xFF, 0xD8]));
+        assert!(thumb.len() < 16 * 1024, "{} bytes", thumb.len());
+        let decoded = image::load_from_memory_with_format(&thumb, ImageFormat::Jpeg).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (THUMBNAIL_SIZE, 54));
+    }
+}
@@ -0,0 +1,105 @@
+//! Timestamp parsing for Go `time.Time` JSON values
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parse an RFC 3339 timestamp as emitted by Go's `time.Time` JSON encoding
+///
+/// Returns `None` for empty strings, Go's zero time (`0001-01-01T00:00:00Z`)
+/// and anything before the Unix epoch.
+pub(crate) fn parse_rfc3339(s: &str) -> Option<SystemTime> {
+    let s = s.trim();
+    if s.len() < 20 || !s.is_char_boundary(19) {
+        return None;
+    }
+
+    let (datetime, rest) = s.split_at(19);
+    let b = datetime.as_bytes();
+    if b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't' | b' ') || b[13] != b':' {
+        return None;
+    }
+
+    let year: i64 = datetime[0..4].parse().ok()?;
+    let month: u32 = datetime[5..7].parse().ok()?;
+    let day: u32 = datetime[8..10].parse().ok()?;
+    let hour: i64 = datetime[11..13].parse().ok()?;
+    let minute: i64 = datetime[14..16].parse().ok()?;
+    let second: i64 = datetime[17..19].parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // Optional fractional seconds
+    let mut rest = rest;
+    let mut nanos = 0u32;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let digits = frac.bytes().take_while(u8::is_ascii_digit).count();
+        let padded = format!("{:0<9}", &frac[..digits.min(9)]);
+        nanos = padded.parse().ok()?;
+        rest = &frac[digits..];
+    }
+
+    // Zone offset: "Z" or "+HH:MM" / "-HH:MM"
+    let offset_secs = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let h: i64 = rest[1..3].parse().ok()?;
+            let m: i64 = rest[4..6].parse().ok()?;
+            sign * (h * 3600 + m * 60)
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+    if secs < 0 {
+        return None;
+    }
+
+    Some(UNIX_EPOCH + Duration::new(secs as u64, nanos))
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64, nanos: u32) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::new(secs, nanos))
+    }
+
+    #[test]
+    fn parses_utc_offsets_and_fractions() {
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00Z"), at(1_704_067_200, 0));
+        assert_eq!(
+            parse_rfc3339("2024-01-01T02:30:00+02:30"),
+            at(1_704_067_200, 0)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-01-01T00:00:00.25Z"),
+            at(1_704_067_200, 250_000_000)
+        );
+    }
+
+    #[test]
+    fn hidden_or_zero_times_are_none() {
+        assert_eq!(parse_rfc3339(""), None);
+        assert_eq!(parse_rfc3339("0001-01-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Typing indicators
+
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::events::Jid;
+use crate::inner::InnerClient;
+
+/// Indicator shown to the other side of a chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatPresence {
+    /// "typing..."
+    Composing,
+    /// "recording audio..."
+    Recording,
+    /// Clear the indicator
+    Paused,
+}
+
+impl ChatPresence {
+    /// Bridge state and media strings
+    pub(crate) fn wire(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Composing => ("composing", ""),
+            Self::Recording => ("composing", "audio"),
+            Self::Paused => ("paused", ""),
+        }
+    }
+}
+
+/// Handle to a running [`WhatsApp::typing`](crate::WhatsApp::typing) indicator
+///
+/// Dropping the guard clears the indicator immediately.
+#[must_use = "dropping the guard stops the typing indicator"]
+pub struct TypingGuard {
+    /// Dropped to cut the wait short
+    cancel: Option<mpsc::Sender<()>>,
+    done: Option<oneshot::Receiver<()>>,
+}
+
+impl TypingGuard {
+    /// Show "typing..." to `to` for `duration`
+    ///
+    /// The indicator runs on its own thread, so this works with or without
+    /// a Tokio runtime.
+    pub(crate) fn start(inner: Arc<InnerClient>, to: Jid, duration: Duration) -> Self {
+        let (cancel, cancelled) = mpsc::channel();
+        let (finished, done) = oneshot::channel();
+
+        let spawned = std::thread::Builder::new()
+            .name("whatsmeow-typing".into())
+            .spawn(move || {
+                send(&inner, &to, ChatPresence::Composing);
+                // Returns early once the guard drops its sender
+                let _ = cancelled.recv_timeout(duration);
+                send(&inner, &to, ChatPresence::Paused);
+                let _ = finished.send(());
+            });
+        if let Err(e) = spawned {
+            tracing::warn!(error = %e, "Failed to start typing indicator");
+        }
+
+        Self {
+            cancel: Some(cancel),
+            done: Some(done),
+        }
+    }
+
+    /// Clear the indicator now
+    pub fn cancel(self) {
+        // Drop does the work
+    }
+
+    /// Wait until the duration elapsed and the indicator was cleared
+    pub async fn finished(mut self) {
+        if let Some(done) = self.done.take() {
+            let _ = done.await;
+        }
+    }
+}
+
+impl Drop for TypingGuard {
+    fn drop(&mut self) {
+        self.cancel.take();
+    }
+}
+
+fn send(inner: &InnerClient, to: &Jid, presence: ChatPresence) {
+    if let Err(e) = inner.send_chat_presence(to.as_str(), presence) {
+        tracing::warn!(error = %e, ?presence, "Chat presence failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::mock_ffi::{self, Mock};
+
+    use super::*;
+
+    fn states(mock: &Mock) -> Vec<String> {
+        mock.calls("wm_send_chat_presence")
+            .into_iter()
+            .map(|args| args[1].clone())
+            .collect()
+    }
+
+    async fn wait_for_states(mock: &Mock, count: usize) {
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while states(mock).len() < count {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("chat presence not sent");
+    }
+
+    #[tokio::test]
+    async fn composing_is_cleared_after_the_duration() {
+        let (client, mock) = mock_ffi::client("typing-spacing").await;
+        let started = Instant::now();
+
+        client
+            .typing("1@s.whatsapp.net", Duration::from_millis(100))
+            .finished()
+            .await;
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(states(&mock), ["composing", "paused"]);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_guard_clears_the_indicator_early() {
+        let (client, mock) = mock_ffi::client("typing-dropped").await;
+
+        let guard = client.typing("1@s.whatsapp.net", Duration::from_secs(60));
+        wait_for_states(&mock, 1).await;
+        drop(guard);
+
+        wait_for_states(&mock, 2).await;
+        assert_eq!(states(&mock), ["composing", "paused"]);
+    }
+
+    #[tokio::test]
+    async fn typing_works_outside_a_runtime() {
+        let (client, mock) = mock_ffi::client("typing-no-runtime").await;
+
+        let typer = client.clone();
+        std::thread::spawn(move || {
+            typer
+                .typing("1@s.whatsapp.net", Duration::from_millis(10))
+                .cancel()
+        })
+        .join()
+        .unwrap();
+
+        wait_for_states(&mock, 2).await;
+    }
+
+    #[tokio::test]
+    async fn simulated_typing_is_cleared_before_the_message_goes_out() {
+        let (client, mock) = mock_ffi::client("typing-before-send").await;
+
+        let options = crate::SendOptions::new().simulate_typing(Duration::from_millis(30));
+        client
+            .send_async("1@s.whatsapp.net", "hi", options)
+            .await
+            .unwrap();
+
+        let sends: Vec<_> = mock
+            .call_names()
+            .into_iter()
+            .filter(|name| name.starts_with("wm_send"))
+            .collect();
+        assert_eq!(sends.len(), 3, "{:?}", sends);
+        assert_eq!(sends[..2], ["wm_send_chat_presence"; 2]);
+        assert_ne!(sends[2], "wm_send_chat_presence");
+        assert_eq!(states(&mock), ["composing", "paused"]);
+    }
+}